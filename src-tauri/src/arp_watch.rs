@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// What an ARP watch noticed in the local cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArpAnomalyKind {
+    /// A host already seen with one MAC now shows up with another
+    MacChanged,
+    /// The gateway has been seen with more than one MAC, the usual sign of
+    /// someone poisoning our cache to intercept our traffic
+    GatewayMultipleMacs,
+}
+
+/// Payload of the `arp-anomaly` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArpAnomaly {
+    pub watch_id: String,
+    pub kind: ArpAnomalyKind,
+    pub ip: String,
+    /// MAC the host had until now
+    pub previous_mac: String,
+    /// MAC the cache holds for it now
+    pub mac: String,
+    /// Every MAC seen for this IP since the watch started, `mac` last
+    pub seen_macs: Vec<String>,
+    /// Unix time in milliseconds
+    pub detected_at: i64,
+}
+
+/// IP to MAC mappings seen so far by one watch
+#[derive(Debug, Default)]
+pub struct ArpTracker {
+    /// Every MAC seen per IP, the current one last
+    seen: HashMap<String, Vec<String>>,
+}
+
+impl ArpTracker {
+    /// Record one reading of the ARP cache as (ip, mac) pairs and return what
+    /// changed since the previous one. The first reading only learns.
+    pub fn observe(&mut self, watch_id: &str, entries: &[(String, String)], gateways: &[String]) -> Vec<ArpAnomaly> {
+        let mut anomalies = Vec::new();
+
+        for (ip, mac) in entries {
+            let seen_macs = self.seen.entry(ip.clone()).or_default();
+            let previous_mac = match seen_macs.last() {
+                Some(previous_mac) if previous_mac != mac => previous_mac.clone(),
+                Some(_) => continue,
+                None => {
+                    seen_macs.push(mac.clone());
+                    continue;
+                }
+            };
+
+            seen_macs.retain(|seen| seen != mac);
+            seen_macs.push(mac.clone());
+
+            let kind = if gateways.contains(ip) {
+                ArpAnomalyKind::GatewayMultipleMacs
+            } else {
+                ArpAnomalyKind::MacChanged
+            };
+            anomalies.push(ArpAnomaly {
+                watch_id: watch_id.to_string(),
+                kind,
+                ip: ip.clone(),
+                previous_mac,
+                mac: mac.clone(),
+                seen_macs: seen_macs.clone(),
+                detected_at: chrono::Utc::now().timestamp_millis(),
+            });
+        }
+
+        anomalies
+    }
+}
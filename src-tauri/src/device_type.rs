@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+use crate::NetworkDevice;
+
+/// Best guess at what kind of device a host is, used to pick its icon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeviceType {
+    Router,
+    Printer,
+    Phone,
+    Computer,
+    Nas,
+    MediaDevice,
+    IotDevice,
+    VirtualMachine,
+}
+
+/// Ports that give a device away on their own
+const PORT_HINTS: &[(u16, DeviceType)] = &[
+    (9100, DeviceType::Printer),
+    (631, DeviceType::Printer),
+    // iOS lockdown service, open on every iPhone and iPad on Wi-Fi
+    (62078, DeviceType::Phone),
+    // Chromecast and other Cast receivers
+    (8009, DeviceType::MediaDevice),
+    (3389, DeviceType::Computer),
+];
+
+/// Lowercase fragments of registered vendor names, checked in order
+const VENDOR_HINTS: &[(&str, DeviceType)] = &[
+    ("vmware", DeviceType::VirtualMachine),
+    ("pcs systemtechnik", DeviceType::VirtualMachine),
+    ("xensource", DeviceType::VirtualMachine),
+    ("parallels", DeviceType::VirtualMachine),
+    ("synology", DeviceType::Nas),
+    ("qnap", DeviceType::Nas),
+    ("hewlett packard", DeviceType::Printer),
+    ("canon", DeviceType::Printer),
+    ("seiko epson", DeviceType::Printer),
+    ("brother industries", DeviceType::Printer),
+    ("lexmark", DeviceType::Printer),
+    ("xerox", DeviceType::Printer),
+    ("netgear", DeviceType::Router),
+    ("tp-link", DeviceType::Router),
+    ("cisco", DeviceType::Router),
+    ("ubiquiti", DeviceType::Router),
+    ("mikrotik", DeviceType::Router),
+    ("roku", DeviceType::MediaDevice),
+    ("sonos", DeviceType::MediaDevice),
+    ("raspberry pi", DeviceType::IotDevice),
+    ("espressif", DeviceType::IotDevice),
+    ("nest labs", DeviceType::IotDevice),
+    ("philips lighting", DeviceType::IotDevice),
+    ("amazon technologies", DeviceType::IotDevice),
+    ("intel corporate", DeviceType::Computer),
+    ("dell", DeviceType::Computer),
+    ("lenovo", DeviceType::Computer),
+    ("micro-star", DeviceType::Computer),
+];
+
+/// Lowercase fragments of host names, for devices whose vendor says little
+const HOSTNAME_HINTS: &[(&str, DeviceType)] = &[
+    ("iphone", DeviceType::Phone),
+    ("android", DeviceType::Phone),
+    ("galaxy", DeviceType::Phone),
+    ("printer", DeviceType::Printer),
+    ("desktop-", DeviceType::Computer),
+    ("laptop-", DeviceType::Computer),
+    ("macbook", DeviceType::Computer),
+];
+
+/// Guess a device's type from whether it is the gateway, its open ports,
+/// its vendor and its host name, in that order of confidence
+pub fn classify(device: &NetworkDevice, is_gateway: bool) -> Option<DeviceType> {
+    if is_gateway {
+        return Some(DeviceType::Router);
+    }
+
+    let by_port = PORT_HINTS
+        .iter()
+        .find(|(port, _)| device.open_ports.contains(port))
+        .map(|(_, device_type)| *device_type);
+    if by_port.is_some() {
+        return by_port;
+    }
+
+    let vendor = device.vendor.to_lowercase();
+    let hostname = device.hostname.to_lowercase();
+    VENDOR_HINTS
+        .iter()
+        .find(|(fragment, _)| vendor.contains(fragment))
+        .or_else(|| HOSTNAME_HINTS.iter().find(|(fragment, _)| hostname.contains(fragment)))
+        .map(|(_, device_type)| *device_type)
+}
@@ -1,17 +1,41 @@
 use std::fmt;
+use std::io;
+use std::net::AddrParseError;
+use std::num::ParseIntError;
+use std::sync::Arc;
 use log::error;
 use serde::{Serialize, Deserialize};
+use thiserror::Error;
 
-/// Custom error type for the application
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AppError {
-    pub code: ErrorCode,
-    pub message: String,
-    pub details: Option<String>,
+/// Custom error type for the application.
+///
+/// Errors raised by the app itself carry a code, message and details; errors
+/// converted from other sources with `?` keep the original as their `source`.
+/// Either way they serialize to `{ code, message, details }` for the frontend.
+#[derive(Debug, Clone, Error, Serialize, Deserialize)]
+#[serde(into = "ErrorPayload", from = "ErrorPayload")]
+pub enum AppError {
+    #[error("[{code}] {message}")]
+    App {
+        code: ErrorCode,
+        message: String,
+        details: Option<String>,
+    },
+    #[error("[SYSTEM_ERROR] I/O error: {0}")]
+    Io(#[source] Arc<io::Error>),
+    #[error("[SYSTEM_ERROR] Invalid JSON: {0}")]
+    Json(#[source] Arc<serde_json::Error>),
+    #[error("[CONFIG_ERROR] Invalid IP address: {0}")]
+    AddrParse(#[source] AddrParseError),
+    #[error("[CONFIG_ERROR] Invalid number: {0}")]
+    ParseInt(#[source] ParseIntError),
+    #[cfg(windows)]
+    #[error("[SYSTEM_ERROR] Windows API call failed: {0}")]
+    Windows(#[source] ::windows::core::Error),
 }
 
 /// Error codes for different types of errors
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ErrorCode {
     NetworkError,
     InterfaceError,
@@ -36,27 +60,124 @@ impl fmt::Display for ErrorCode {
     }
 }
 
-impl fmt::Display for AppError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "[{}] {}", self.code, self.message)
+impl AppError {
+    /// Category of the error, sent to the frontend
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::App { code, .. } => code.clone(),
+            AppError::Io(_) | AppError::Json(_) => ErrorCode::SystemError,
+            AppError::AddrParse(_) | AppError::ParseInt(_) => ErrorCode::ConfigurationError,
+            #[cfg(windows)]
+            AppError::Windows(_) => ErrorCode::SystemError,
+        }
+    }
+    
+    /// Short description of what failed
+    pub fn message(&self) -> String {
+        match self {
+            AppError::App { message, .. } => message.clone(),
+            AppError::Io(_) => "I/O error".to_string(),
+            AppError::Json(_) => "Invalid JSON".to_string(),
+            AppError::AddrParse(_) => "Invalid IP address".to_string(),
+            AppError::ParseInt(_) => "Invalid number".to_string(),
+            #[cfg(windows)]
+            AppError::Windows(_) => "Windows API call failed".to_string(),
+        }
+    }
+    
+    /// Extra context; for converted errors, the source error and its causes
+    pub fn details(&self) -> Option<String> {
+        match self {
+            AppError::App { details, .. } => details.clone(),
+            _ => {
+                let mut chain = Vec::new();
+                let mut source = std::error::Error::source(self);
+                while let Some(cause) = source {
+                    chain.push(cause.to_string());
+                    source = cause.source();
+                }
+                Some(chain.join(": "))
+            }
+        }
+    }
+    
+    /// Log the error and its details, then hand it back
+    fn logged(self) -> Self {
+        error!("{}", self);
+        if let Some(details) = self.details() {
+            error!("Error details: {}", details);
+        }
+        self
+    }
+}
+
+impl From<io::Error> for AppError {
+    fn from(error: io::Error) -> Self {
+        AppError::Io(Arc::new(error)).logged()
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(error: serde_json::Error) -> Self {
+        AppError::Json(Arc::new(error)).logged()
+    }
+}
+
+impl From<AddrParseError> for AppError {
+    fn from(error: AddrParseError) -> Self {
+        AppError::AddrParse(error).logged()
+    }
+}
+
+impl From<ParseIntError> for AppError {
+    fn from(error: ParseIntError) -> Self {
+        AppError::ParseInt(error).logged()
+    }
+}
+
+#[cfg(windows)]
+impl From<::windows::core::Error> for AppError {
+    fn from(error: ::windows::core::Error) -> Self {
+        AppError::Windows(error).logged()
+    }
+}
+
+/// Wire format of `AppError`, unchanged from when it was a plain struct
+#[derive(Serialize, Deserialize)]
+struct ErrorPayload {
+    code: ErrorCode,
+    message: String,
+    details: Option<String>,
+}
+
+impl From<AppError> for ErrorPayload {
+    fn from(error: AppError) -> Self {
+        ErrorPayload {
+            code: error.code(),
+            message: error.message(),
+            details: error.details(),
+        }
+    }
+}
+
+impl From<ErrorPayload> for AppError {
+    fn from(payload: ErrorPayload) -> Self {
+        AppError::App {
+            code: payload.code,
+            message: payload.message,
+            details: payload.details,
+        }
     }
 }
 
 /// Creates a new AppError
 pub fn new_error(code: ErrorCode, message: &str, details: Option<&str>) -> AppError {
-    let error = AppError {
+    AppError::App {
         code,
         message: message.to_string(),
         details: details.map(|s| s.to_string()),
-    };
-    
-    // Log the error
-    error!("{}", error);
-    if let Some(details) = &error.details {
-        error!("Error details: {}", details);
     }
-    
-    error
+    .logged()
 }
 
 /// Helper function to convert a standard error string to an AppError
@@ -98,11 +219,31 @@ pub fn config_error(message: &str, details: Option<&str>) -> AppError {
     new_error(ErrorCode::ConfigurationError, message, details)
 }
 
-/// Convert AppError to a simple string error message for compatibility
+/// Convert AppError to a string for the frontend, in the
+/// "[ERROR_CODE] message: details" form parsed by errorHandler.ts
 pub fn to_string_error(error: AppError) -> String {
-    if let Some(details) = error.details {
-        format!("{}: {}", error.message, details)
+    if let Some(details) = error.details() {
+        format!("[{}] {}: {}", error.code(), error.message(), details)
     } else {
-        error.message
+        format!("[{}] {}", error.code(), error.message())
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn converted_errors_keep_the_frontend_shape() {
+        let error = AppError::from("10.0.0.x".parse::<std::net::Ipv4Addr>().unwrap_err());
+        
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["code"], "ConfigurationError");
+        assert_eq!(json["message"], "Invalid IP address");
+        assert!(json["details"].as_str().unwrap().contains("invalid IPv4 address syntax"));
+        
+        let parsed: AppError = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.code(), ErrorCode::ConfigurationError);
+        assert_eq!(to_string_error(parsed), to_string_error(error));
+    }
+}
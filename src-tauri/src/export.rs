@@ -0,0 +1,49 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::NetworkDevice;
+
+/// File formats supported by `export_devices`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains(|c| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Render devices as CSV with an `ip,mac,hostname,vendor` header row
+pub fn devices_to_csv(devices: &[NetworkDevice]) -> String {
+    let mut csv = String::from("ip,mac,hostname,vendor\r\n");
+    for device in devices {
+        let row = [&device.ip, &device.mac, &device.hostname, &device.vendor]
+            .iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<_>>()
+            .join(",");
+        csv.push_str(&row);
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// Write devices to `path` in the requested format
+pub fn write_devices(devices: &[NetworkDevice], format: ExportFormat, path: &Path) -> Result<(), String> {
+    let contents = match format {
+        ExportFormat::Csv => devices_to_csv(devices),
+        ExportFormat::Json => serde_json::to_string_pretty(devices)
+            .map_err(|e| format!("Failed to serialize devices: {}", e))?,
+    };
+
+    fs::write(path, contents)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
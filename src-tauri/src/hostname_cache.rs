@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+/// Names resolved by earlier scans, inside the config directory
+const HOSTNAME_CACHE_FILE: &str = "hostnames.json";
+
+/// How long a resolved name is trusted before the host is looked up again
+pub const HOSTNAME_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedHostname {
+    hostname: String,
+    /// Unix time in seconds when the name was resolved
+    resolved_at: u64,
+}
+
+#[derive(Default)]
+struct HostnameCache {
+    entries: HashMap<String, CachedHostname>,
+    /// Entries were added since the cache was last saved
+    dirty: bool,
+}
+
+/// Loaded from disk on first use
+static CACHE: Mutex<Option<HostnameCache>> = Mutex::new(None);
+
+fn now_secs() -> u64 {
+    chrono::Utc::now().timestamp().max(0) as u64
+}
+
+fn is_fresh(entry: &CachedHostname, now: u64) -> bool {
+    now.saturating_sub(entry.resolved_at) < HOSTNAME_CACHE_TTL.as_secs()
+}
+
+fn load() -> HostnameCache {
+    let entries = match storage::load_json::<HashMap<String, CachedHostname>>(HOSTNAME_CACHE_FILE) {
+        Ok(entries) => entries.unwrap_or_default(),
+        Err(e) => {
+            eprintln!("Ignoring cached hostnames: {}", e);
+            HashMap::new()
+        }
+    };
+
+    HostnameCache { entries, dirty: false }
+}
+
+/// Name resolved for `ip` within the last `HOSTNAME_CACHE_TTL`
+pub fn lookup(ip: &str) -> Option<String> {
+    let mut cache = CACHE.lock().ok()?;
+    let cache = cache.get_or_insert_with(load);
+
+    cache.entries
+        .get(ip)
+        .filter(|entry| is_fresh(entry, now_secs()))
+        .map(|entry| entry.hostname.clone())
+}
+
+/// Remember a name that was just resolved. Kept in memory until `save`.
+pub fn record(ip: &str, hostname: &str) {
+    if let Ok(mut cache) = CACHE.lock() {
+        let cache = cache.get_or_insert_with(load);
+        cache.entries.insert(ip.to_string(), CachedHostname {
+            hostname: hostname.to_string(),
+            resolved_at: now_secs(),
+        });
+        cache.dirty = true;
+    }
+}
+
+/// Write names recorded since the last save to disk, dropping expired ones
+pub fn save() {
+    let mut cache = match CACHE.lock() {
+        Ok(cache) => cache,
+        Err(_) => return,
+    };
+    let cache = match cache.as_mut() {
+        Some(cache) if cache.dirty => cache,
+        _ => return,
+    };
+
+    let now = now_secs();
+    cache.entries.retain(|_, entry| is_fresh(entry, now));
+    match storage::save_json(HOSTNAME_CACHE_FILE, &cache.entries) {
+        Ok(()) => cache.dirty = false,
+        Err(e) => eprintln!("Failed to save hostname cache: {}", e),
+    }
+}
+
+/// Forget every cached name, in memory and on disk. Returns the number of entries dropped.
+pub fn clear() -> Result<usize, String> {
+    let cleared = match CACHE.lock() {
+        Ok(mut cache) => cache.take().unwrap_or_else(load).entries.len(),
+        Err(_) => 0,
+    };
+
+    storage::remove(HOSTNAME_CACHE_FILE)?;
+    Ok(cleared)
+}
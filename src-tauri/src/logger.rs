@@ -1,17 +1,136 @@
 use std::io::Write;
 use std::fs::{self, File, OpenOptions};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::Once;
+use std::time::{Duration, SystemTime};
 use chrono::Local;
 use log::{LevelFilter, debug, error, info, warn};
 use env_logger::Builder;
+use kancut_lib::{is_session_log, LOG_DIR};
 
 static INIT: Once = Once::new();
-static LOG_DIR: &str = "logs";
 static LOG_FILE: &str = "kancut.log";
+static LOG_LEVEL_ENV: &str = "KANCUT_LOG_LEVEL";
+static LOG_FORMAT_ENV: &str = "KANCUT_LOG_FORMAT";
+
+/// Default size at which the log file is rotated
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+/// Default number of rotated archives kept next to the live log
+pub const DEFAULT_MAX_ARCHIVES: usize = 5;
+/// Default age after which rotated archives are deleted on startup
+pub const DEFAULT_MAX_ARCHIVE_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Layout of each log record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// `2024-01-01 12:00:00.000 [INFO] - target: message`
+    #[default]
+    Plain,
+    /// One JSON object per line with `timestamp`, `level`, `target` and `message`
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.trim().to_lowercase().as_str() {
+            "plain" => Ok(LogFormat::Plain),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("Unknown log format '{}' (expected plain or json)", format)),
+        }
+    }
+}
+
+/// Logger settings passed to `init`
+#[derive(Debug, Clone)]
+pub struct LoggerConfig {
+    /// Rotate the log file once it grows past this many bytes
+    pub max_file_size: u64,
+    /// Number of `kancut.log.N` archives to keep
+    pub max_archives: usize,
+    /// Delete archives and session logs last written longer ago than this on startup; `None` keeps them
+    pub max_archive_age: Option<Duration>,
+    /// Initial log level; falls back to `KANCUT_LOG_LEVEL`, then the build default
+    pub level: Option<LevelFilter>,
+    /// Record layout; falls back to `KANCUT_LOG_FORMAT`, then plain
+    pub format: Option<LogFormat>,
+}
+
+impl Default for LoggerConfig {
+    fn default() -> Self {
+        Self {
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            max_archives: DEFAULT_MAX_ARCHIVES,
+            max_archive_age: Some(DEFAULT_MAX_ARCHIVE_AGE),
+            level: None,
+            format: None,
+        }
+    }
+}
+
+/// Parse a level name such as "trace" or "WARN" into a filter
+pub fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    LevelFilter::from_str(level.trim())
+        .map_err(|_| format!("Unknown log level '{}' (expected off, error, warn, info, debug or trace)", level))
+}
+
+/// Build-dependent default level: Debug for debug builds, Info otherwise
+fn default_level() -> LevelFilter {
+    if cfg!(debug_assertions) {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Info
+    }
+}
+
+/// Resolve the starting level from the config, then the environment
+fn initial_level(config: &LoggerConfig) -> LevelFilter {
+    if let Some(level) = config.level {
+        return level;
+    }
+
+    match std::env::var(LOG_LEVEL_ENV) {
+        Ok(value) => parse_level(&value).unwrap_or_else(|e| {
+            eprintln!("Ignoring {}: {}", LOG_LEVEL_ENV, e);
+            default_level()
+        }),
+        Err(_) => default_level(),
+    }
+}
+
+/// Resolve the record layout from the config, then the environment
+fn log_format(config: &LoggerConfig) -> LogFormat {
+    if let Some(format) = config.format {
+        return format;
+    }
+
+    match std::env::var(LOG_FORMAT_ENV) {
+        Ok(value) => value.parse().unwrap_or_else(|e| {
+            eprintln!("Ignoring {}: {}", LOG_FORMAT_ENV, e);
+            LogFormat::Plain
+        }),
+        Err(_) => LogFormat::Plain,
+    }
+}
+
+/// Change the log level at runtime
+pub fn set_log_level(level: &str) -> Result<LevelFilter, String> {
+    let level = parse_level(level)?;
+    // The builder accepts every level; the global max level does the filtering
+    log::set_max_level(level);
+    info!("Log level set to {}", level);
+    Ok(level)
+}
+
+/// Currently active log level
+pub fn current_log_level() -> LevelFilter {
+    log::max_level()
+}
 
 /// Initialize the application logger with file and console output
-pub fn init() {
+pub fn init(config: LoggerConfig) {
     INIT.call_once(|| {
         // Create logs directory if it doesn't exist
         let log_dir = Path::new(LOG_DIR);
@@ -22,6 +141,10 @@ pub fn init() {
             }
         }
 
+        let purged = config.max_archive_age
+            .map(|max_age| purge_old_archives(log_dir, max_age))
+            .unwrap_or(0);
+
         // Configure log file path
         let log_path = log_dir.join(LOG_FILE);
         
@@ -38,39 +161,108 @@ pub fn init() {
             };
 
         // Configure logger
+        let format = log_format(&config);
         let mut builder = Builder::new();
         builder
-            .format(|buf, record| {
-                writeln!(
+            .format(move |buf, record| match format {
+                LogFormat::Plain => writeln!(
                     buf,
                     "{} [{}] - {}: {}",
                     Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
                     record.level(),
                     record.target(),
                     record.args()
-                )
+                ),
+                LogFormat::Json => writeln!(
+                    buf,
+                    "{}",
+                    serde_json::json!({
+                        "timestamp": Local::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false),
+                        "level": record.level().to_string(),
+                        "target": record.target(),
+                        "message": record.args().to_string(),
+                    })
+                ),
             })
-            .filter(None, LevelFilter::Info); // Set default log level
+            // Let everything through; the runtime level is applied via log::set_max_level
+            .filter(None, LevelFilter::Trace);
 
-        // Check debug mode
-        #[cfg(debug_assertions)]
-        builder.filter(None, LevelFilter::Debug);
+        // Continue counting from the current size so rotation survives restarts
+        let bytes_written = file.metadata().map(|m| m.len()).unwrap_or(0);
 
         // Set up dual logging to console and file
         builder.target(env_logger::Target::Pipe(Box::new(DualWriter {
             console: std::io::stderr(),
-            file,
+            file: Some(file),
+            path: log_path,
+            bytes_written,
+            max_file_size: config.max_file_size,
+            max_archives: config.max_archives,
         })));
 
         // Initialize the logger
         if let Err(e) = builder.try_init() {
             eprintln!("Failed to initialize logger: {}", e);
         } else {
-            info!("Logger initialized");
+            log::set_max_level(initial_level(&config));
+            info!("Logger initialized at level {}", log::max_level());
+            if purged > 0 {
+                info!("Deleted {} log archives and session logs older than {} days", purged,
+                      config.max_archive_age.unwrap_or_default().as_secs() / 86_400);
+            }
         }
     });
 }
 
+/// Delete `kancut.log.N` archives and `session-<id>.log` files in `log_dir` not
+/// modified within `max_age`, returning how many were removed. The live log is
+/// never touched, and running sessions keep their logs recent by writing to them.
+fn purge_old_archives(log_dir: &Path, max_age: Duration) -> usize {
+    let entries = match fs::read_dir(log_dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Failed to list log directory: {}", e);
+            return 0;
+        }
+    };
+
+    let archive_prefix = format!("{}.", LOG_FILE);
+    let now = SystemTime::now();
+    let mut purged = 0;
+
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        let is_archive = file_name.to_str()
+            .map(|name| {
+                name.strip_prefix(&archive_prefix)
+                    .map(|index| index.parse::<usize>().is_ok())
+                    .unwrap_or(false)
+                    || is_session_log(name)
+            })
+            .unwrap_or(false);
+        if !is_archive {
+            continue;
+        }
+
+        let modified = match entry.metadata().and_then(|metadata| metadata.modified()) {
+            Ok(modified) => modified,
+            Err(_) => continue,
+        };
+        // A clock set back makes the file look new, which keeps it
+        let age = now.duration_since(modified).unwrap_or_default();
+        if age <= max_age {
+            continue;
+        }
+
+        match fs::remove_file(entry.path()) {
+            Ok(()) => purged += 1,
+            Err(e) => eprintln!("Failed to delete old log {}: {}", entry.path().display(), e),
+        }
+    }
+
+    purged
+}
+
 /// Helper function to log performance metrics
 pub fn log_performance(operation: &str, duration_ms: f64) {
     debug!("Performance: {} took {:.2}ms", operation, duration_ms);
@@ -84,7 +276,43 @@ pub fn log_error(context: &str, error: &str) {
 /// Dual writer to output logs to both console and file
 struct DualWriter {
     console: std::io::Stderr,
-    file: File,
+    // Only `None` briefly while the file is being rotated
+    file: Option<File>,
+    path: PathBuf,
+    bytes_written: u64,
+    max_file_size: u64,
+    max_archives: usize,
+}
+
+impl DualWriter {
+    /// Path of the N-th rotated archive, e.g. `logs/kancut.log.1`
+    fn archive_path(&self, index: usize) -> PathBuf {
+        self.path.with_file_name(format!("{}.{}", LOG_FILE, index))
+    }
+
+    /// Shift archives up by one, move the live log to `.1` and start a fresh file
+    fn rotate(&mut self) -> std::io::Result<()> {
+        // Close the current file first; Windows cannot rename open files
+        if let Some(mut file) = self.file.take() {
+            file.flush()?;
+        }
+
+        if self.max_archives > 0 {
+            for index in (1..self.max_archives).rev() {
+                let from = self.archive_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.archive_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.archive_path(1))?;
+        } else {
+            fs::remove_file(&self.path)?;
+        }
+
+        self.file = Some(OpenOptions::new().create(true).append(true).open(&self.path)?);
+        self.bytes_written = 0;
+        Ok(())
+    }
 }
 
 impl Write for DualWriter {
@@ -93,8 +321,24 @@ impl Write for DualWriter {
         let console_result = self.console.write(buf);
         
         // Then write to file
-        match self.file.write(buf) {
+        let file_result = match self.file.as_mut() {
+            Some(file) => file.write(buf),
+            None => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "log file is not open")),
+        };
+
+        match file_result {
             Ok(file_size) => {
+                self.bytes_written += file_size as u64;
+                if self.bytes_written >= self.max_file_size {
+                    if let Err(e) = self.rotate() {
+                        let _ = writeln!(self.console, "Failed to rotate log file: {}", e);
+                        // Keep logging to whatever file we can get
+                        if self.file.is_none() {
+                            self.file = OpenOptions::new().create(true).append(true).open(&self.path).ok();
+                        }
+                    }
+                }
+
                 // Return the console result if successful, or file size otherwise
                 console_result.or(Ok(file_size))
             }
@@ -108,7 +352,10 @@ impl Write for DualWriter {
 
     fn flush(&mut self) -> std::io::Result<()> {
         let console_result = self.console.flush();
-        let file_result = self.file.flush();
+        let file_result = match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        };
         
         // Return error if either flush fails
         console_result.and(file_result)
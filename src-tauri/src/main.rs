@@ -1,74 +1,553 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use tauri::State;
-use kancut_lib::{SpoofingSessions, CustomNetworkInterface, NetworkDevice, SpoofingSession};
-use log::{info, debug};
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, RunEvent, State};
+use kancut_lib::error_handler;
+use kancut_lib::{
+    SpoofingSessions, ActiveScans, ArpWatches, InterfaceWatches, ScanCache, ScanHandle, ScanMode, ScanOptions, CustomNetworkInterface, NetworkDevice,
+    SpoofingSession, SpoofingOptions, SpoofingStart, ExportFormat, PingResult, ScanDiff, ScanResult,
+    SpoofAllResult, ActiveSessions, SessionQuery, SessionSort, HealthReport, ScanProfile, DeviceDetails,
+    InterfaceCapabilities, ArpEntry, ArpDiff, ScanAndSpoofRequest, ScanAndSpoofResult,
+};
+use log::{info, debug, warn};
 
 mod logger;
-mod error_handler;
 
 #[tauri::command]
-fn get_interfaces() -> Result<Vec<CustomNetworkInterface>, String> {
-    info!("Getting network interfaces");
-    match kancut_lib::get_interfaces() {
+fn check_privileges() -> bool {
+    let elevated = kancut_lib::is_elevated();
+    info!("Administrator privileges: {}", if elevated { "yes" } else { "no" });
+    elevated
+}
+
+#[tauri::command(async)]
+fn system_healthcheck() -> HealthReport {
+    let report = kancut_lib::system_healthcheck();
+    for check in report.checks.iter().filter(|check| !check.passed) {
+        warn!("Health check {} failed: {}", check.name, check.message);
+    }
+    info!("System ready: {}", if report.ready { "yes" } else { "no" });
+    report
+}
+
+#[tauri::command]
+fn set_log_level(level: String) -> Result<String, String> {
+    match logger::set_log_level(&level) {
+        Ok(level) => Ok(level.to_string()),
+        Err(e) => {
+            let app_error = error_handler::config_error("Invalid log level", Some(&e));
+            Err(error_handler::to_string_error(app_error))
+        }
+    }
+}
+
+#[tauri::command]
+fn get_log_level() -> String {
+    logger::current_log_level().to_string()
+}
+
+#[tauri::command]
+fn get_interfaces(
+    include_virtual: Option<bool>,
+    include_link_local: Option<bool>,
+) -> Result<Vec<CustomNetworkInterface>, String> {
+    let include_virtual = include_virtual.unwrap_or(false);
+    let include_link_local = include_link_local.unwrap_or(false);
+    info!("Getting network interfaces (include virtual: {}, include link-local: {})",
+          include_virtual, include_link_local);
+    match kancut_lib::get_usable_interfaces(include_virtual, include_link_local) {
         Ok(interfaces) => {
             debug!("Found {} network interfaces", interfaces.len());
             Ok(interfaces)
         },
-        Err(e) => {
-            let app_error = error_handler::interface_error(
-                "Failed to get network interfaces", 
-                Some(&e)
-            );
-            Err(error_handler::to_string_error(app_error))
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn find_interface_by_ip(ip: String) -> Result<CustomNetworkInterface, String> {
+    debug!("Looking up the interface with address {}", ip);
+    match kancut_lib::find_interface_by_ip(ip) {
+        Ok(interface) => {
+            debug!("Address belongs to interface {}", interface.name);
+            Ok(interface)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+/// Scan and spoof commands take an interface name or one of its IP addresses
+fn interface_arg(interface: String) -> Result<String, String> {
+    kancut_lib::resolve_interface_name(interface).map_err(error_handler::to_string_error)
+}
+
+// The arguments are the IPC contract with the frontend, which passes them by name
+#[allow(clippy::too_many_arguments)]
+#[tauri::command(async)]
+fn scan_network(
+    interface_name: String,
+    prefix_len: Option<u8>,
+    mode: Option<ScanMode>,
+    scan_id: Option<String>,
+    options: Option<ScanOptions>,
+    scans: State<ActiveScans>,
+    cache: State<ScanCache>,
+    app: AppHandle,
+) -> Result<ScanResult, String> {
+    let interface_name = interface_arg(interface_name)?;
+    let mode = mode.unwrap_or_default();
+    let options = options.unwrap_or_default();
+    info!("Scanning network on interface: {} (prefix: {:?}, mode: {:?}, concurrency: {:?})",
+          interface_name, prefix_len, mode, options.max_concurrency);
+    
+    // Serve a recent result straight from the cache unless a fresh scan was requested
+    // A cached result covers the whole network, so partial scans bypass the cache
+    if !options.force_refresh && options.max_age_secs > 0 && !options.limits_hosts() {
+        let max_age = Duration::from_secs(options.max_age_secs);
+        if let Some(mut cached) = cache.get_fresh(&interface_name, prefix_len, mode, max_age) {
+            debug!("Returning {} cached devices for {}", cached.devices.len(), interface_name);
+            if options.verify {
+                let online = kancut_lib::verify_devices(&mut cached.devices, options.scan_concurrency(&interface_name));
+                debug!("{} of {} cached devices still online", online, cached.devices.len());
+            }
+            if options.background_refresh {
+                kancut_lib::refresh_scan_in_background(
+                    interface_name, prefix_len, mode, options, cache.inner().clone(), app,
+                );
+            }
+            return Ok(cached);
         }
     }
+    
+    // Register the scan so it can be cancelled from the UI
+    let scan = match &scan_id {
+        Some(id) => scans.register(id),
+        None => ScanHandle::new(),
+    };
+    let result = kancut_lib::scan_network(interface_name.clone(), prefix_len, mode, &options, &scan, &app);
+    if let Some(id) = &scan_id {
+        scans.remove(id);
+    }
+    
+    match result {
+        Ok(scan_result) => {
+            debug!("Found {} devices on {} in {}ms via {} with {} workers",
+                   scan_result.devices.len(), scan_result.network, scan_result.duration_ms,
+                   scan_result.methods_used.join(", "), scan_result.concurrency);
+            // Partial results of a cancelled or ranged scan are not worth caching
+            if !scan.is_cancelled() && !options.limits_hosts() {
+                cache.store(&interface_name, prefix_len, mode, &scan_result);
+            }
+            Ok(scan_result)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command(async)]
+fn scan_diff(
+    interface_name: String,
+    prefix_len: Option<u8>,
+    mode: Option<ScanMode>,
+    options: Option<ScanOptions>,
+    cache: State<ScanCache>,
+    app: AppHandle,
+) -> Result<ScanDiff, String> {
+    let interface_name = interface_arg(interface_name)?;
+    let mode = mode.unwrap_or_default();
+    let options = options.unwrap_or_default();
+    info!("Scanning {} for changes since the last scan", interface_name);
+    
+    let previous = cache.last(&interface_name).unwrap_or_default();
+    let scan = ScanHandle::new();
+    
+    match kancut_lib::scan_network(interface_name.clone(), prefix_len, mode, &options, &scan, &app) {
+        Ok(scan_result) => {
+            if !options.limits_hosts() {
+                cache.store(&interface_name, prefix_len, mode, &scan_result);
+            }
+            let diff = kancut_lib::diff_devices(&previous, &scan_result.devices);
+            info!("Scan diff for {}: {} added, {} removed, {} changed",
+                  interface_name, diff.added.len(), diff.removed.len(), diff.changed.len());
+            Ok(diff)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command(async)]
+fn snapshot_arp_table() -> Result<Vec<ArpEntry>, String> {
+    info!("Taking a snapshot of the ARP table");
+    match kancut_lib::snapshot_arp_table() {
+        Ok(entries) => {
+            debug!("ARP table snapshot has {} entries", entries.len());
+            Ok(entries)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
 }
 
 #[tauri::command]
-fn scan_network(interface_name: String) -> Result<Vec<NetworkDevice>, String> {
-    info!("Scanning network on interface: {}", interface_name);
-    match kancut_lib::scan_network(interface_name) {
+fn diff_arp_snapshots(previous: Vec<ArpEntry>, current: Vec<ArpEntry>) -> ArpDiff {
+    let diff = kancut_lib::diff_arp_snapshots(&previous, &current);
+    info!("ARP table diff: {} added, {} removed, {} changed",
+          diff.added.len(), diff.removed.len(), diff.changed.len());
+    diff
+}
+
+#[tauri::command(async)]
+fn scan_cidr(
+    interface_name: String,
+    cidr: String,
+    scan_id: Option<String>,
+    options: Option<ScanOptions>,
+    scans: State<ActiveScans>,
+    app: AppHandle,
+) -> Result<Vec<NetworkDevice>, String> {
+    let interface_name = interface_arg(interface_name)?;
+    let options = options.unwrap_or_default();
+    info!("Scanning range {} through interface: {}", cidr, interface_name);
+    
+    let scan = match &scan_id {
+        Some(id) => scans.register(id),
+        None => ScanHandle::new(),
+    };
+    let result = kancut_lib::scan_cidr(interface_name, cidr, &options, &scan, &app);
+    if let Some(id) = &scan_id {
+        scans.remove(id);
+    }
+    
+    match result {
         Ok(devices) => {
-            debug!("Found {} devices on network", devices.len());
+            debug!("Found {} devices in range", devices.len());
             Ok(devices)
         },
-        Err(e) => {
-            let app_error = error_handler::network_error(
-                "Failed to scan network", 
-                Some(&e)
-            );
-            Err(error_handler::to_string_error(app_error))
-        }
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn list_scan_profiles() -> Result<Vec<ScanProfile>, String> {
+    debug!("Listing scan profiles");
+    kancut_lib::list_scan_profiles().map_err(error_handler::to_string_error)
+}
+
+#[tauri::command]
+fn save_scan_profile(profile: ScanProfile) -> Result<Vec<ScanProfile>, String> {
+    info!("Saving scan profile '{}' for interface {}", profile.name, profile.interface);
+    match kancut_lib::save_scan_profile(profile) {
+        Ok(profiles) => Ok(profiles),
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command(async)]
+fn run_scan_profile(
+    name: String,
+    scan_id: Option<String>,
+    scans: State<ActiveScans>,
+    app: AppHandle,
+) -> Result<ScanResult, String> {
+    info!("Running scan profile '{}'", name);
+    
+    let scan = match &scan_id {
+        Some(id) => scans.register(id),
+        None => ScanHandle::new(),
+    };
+    let result = kancut_lib::run_scan_profile(&name, &scan, &app);
+    if let Some(id) = &scan_id {
+        scans.remove(id);
+    }
+    
+    match result {
+        Ok(scan_result) => {
+            debug!("Profile '{}' found {} devices on {} in {}ms",
+                   name, scan_result.devices.len(), scan_result.network, scan_result.duration_ms);
+            Ok(scan_result)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
     }
 }
 
 #[tauri::command]
+fn quick_scan(interface_name: String) -> Result<Vec<NetworkDevice>, String> {
+    let interface_name = interface_arg(interface_name)?;
+    info!("Quick scan on interface: {}", interface_name);
+    match kancut_lib::quick_scan(interface_name) {
+        Ok(devices) => {
+            debug!("Quick scan found {} devices", devices.len());
+            Ok(devices)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn group_devices_by_vendor(devices: Vec<NetworkDevice>) -> HashMap<String, Vec<NetworkDevice>> {
+    let groups = kancut_lib::group_devices_by_vendor(devices);
+    debug!("Grouped devices into {} vendors", groups.len());
+    groups
+}
+
+#[tauri::command]
+fn export_devices(
+    devices: Vec<NetworkDevice>,
+    format: ExportFormat,
+    path: String,
+) -> Result<(), String> {
+    info!("Exporting {} devices as {:?} to {}", devices.len(), format, path);
+    match kancut_lib::export_devices(devices, format, path) {
+        Ok(()) => Ok(()),
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn cancel_scan(
+    scan_id: String,
+    scans: State<ActiveScans>,
+) -> Result<bool, String> {
+    info!("Cancelling scan: {}", scan_id);
+    match kancut_lib::cancel_scan(scan_id, scans) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn clear_arp_cache(interface_name: Option<String>) -> Result<(), String> {
+    info!("Clearing ARP cache (interface: {:?})", interface_name);
+    match kancut_lib::clear_arp_cache(interface_name) {
+        Ok(()) => Ok(()),
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn start_arp_watch(
+    interface_name: String,
+    watches: State<ArpWatches>,
+    app: AppHandle,
+) -> Result<String, String> {
+    info!("Watching the ARP cache on interface: {}", interface_name);
+    match kancut_lib::start_arp_watch(interface_name, watches, app) {
+        Ok(watch_id) => {
+            debug!("ARP watch started: {}", watch_id);
+            Ok(watch_id)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn stop_arp_watch(
+    watch_id: String,
+    watches: State<ArpWatches>,
+) -> Result<bool, String> {
+    info!("Stopping ARP watch: {}", watch_id);
+    match kancut_lib::stop_arp_watch(watch_id, watches) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn start_interface_watch(
+    interval_ms: Option<u64>,
+    include_virtual: Option<bool>,
+    include_link_local: Option<bool>,
+    watches: State<InterfaceWatches>,
+    app: AppHandle,
+) -> Result<String, String> {
+    info!("Watching network interfaces for changes (interval: {:?}ms)", interval_ms);
+    let include_virtual = include_virtual.unwrap_or(false);
+    let include_link_local = include_link_local.unwrap_or(false);
+    match kancut_lib::start_interface_watch(interval_ms, include_virtual, include_link_local, watches, app) {
+        Ok(watch_id) => {
+            debug!("Interface watch started: {}", watch_id);
+            Ok(watch_id)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn stop_interface_watch(
+    watch_id: String,
+    watches: State<InterfaceWatches>,
+) -> Result<bool, String> {
+    info!("Stopping interface watch: {}", watch_id);
+    match kancut_lib::stop_interface_watch(watch_id, watches) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command(async)]
+fn ping_host(ip: String) -> Result<PingResult, String> {
+    debug!("Pinging host: {}", ip);
+    match kancut_lib::ping_host(ip) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command(async)]
+fn resolve_host(ip: String) -> Result<Option<String>, String> {
+    debug!("Resolving hostname of {}", ip);
+    match kancut_lib::resolve_host(ip) {
+        Ok(hostname) => {
+            debug!("Resolved hostname: {:?}", hostname);
+            Ok(hostname)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command(async)]
+fn inspect_device(ip: String, interface_name: String) -> Result<DeviceDetails, String> {
+    let interface_name = interface_arg(interface_name)?;
+    info!("Inspecting {} on interface {}", ip, interface_name);
+    match kancut_lib::inspect_device(ip, interface_name) {
+        Ok(details) => {
+            debug!("{}: MAC {:?}, hostname {:?}, {} open ports, reachable: {}",
+                   details.ip, details.mac, details.hostname, details.open_ports.len(), details.reachable);
+            Ok(details)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn wake_on_lan(mac: String, broadcast_ip: Option<String>) -> Result<(), String> {
+    info!("Sending Wake-on-LAN packet to {}", mac);
+    match kancut_lib::wake_on_lan(mac, broadcast_ip) {
+        Ok(()) => Ok(()),
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command(async)]
+fn scan_ports(ip: String, ports: Vec<u16>, timeout_ms: u64) -> Result<Vec<u16>, String> {
+    info!("Scanning {} ports on {}", if ports.is_empty() { "default".to_string() } else { ports.len().to_string() }, ip);
+    match kancut_lib::scan_ports(ip, ports, timeout_ms) {
+        Ok(open_ports) => {
+            info!("Found {} open ports", open_ports.len());
+            Ok(open_ports)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command(async)]
+fn update_oui_database(url: Option<String>) -> Result<usize, String> {
+    info!("Updating vendor database");
+    match kancut_lib::update_oui_database(url) {
+        Ok(count) => {
+            info!("Vendor database updated with {} entries", count);
+            Ok(count)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn clear_vendor_cache() -> usize {
+    let cleared = kancut_lib::clear_vendor_cache();
+    info!("Cleared {} cached vendors", cleared);
+    cleared
+}
+
+#[tauri::command]
+fn clear_hostname_cache() -> Result<usize, String> {
+    info!("Clearing hostname cache");
+    match kancut_lib::clear_hostname_cache() {
+        Ok(cleared) => {
+            info!("Cleared {} cached hostnames", cleared);
+            Ok(cleared)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn get_default_gateway(interface_name: String) -> Result<String, String> {
+    info!("Detecting default gateway on interface: {}", interface_name);
+    match kancut_lib::get_default_gateway(&interface_name) {
+        Ok(gateway) => {
+            debug!("Default gateway for {}: {}", interface_name, gateway);
+            Ok(gateway)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command(async)]
+fn get_gateway_device(interface_name: String) -> Result<NetworkDevice, String> {
+    let interface_name = interface_arg(interface_name)?;
+    info!("Looking up the gateway device on interface: {}", interface_name);
+    match kancut_lib::get_gateway_device(interface_name) {
+        Ok(device) => {
+            debug!("Gateway device: {} ({})", device.ip, device.mac);
+            Ok(device)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command(async)]
+fn test_interface(interface_name: String) -> Result<InterfaceCapabilities, String> {
+    let interface_name = interface_arg(interface_name)?;
+    info!("Testing ARP capabilities of interface: {}", interface_name);
+    match kancut_lib::test_interface(&interface_name) {
+        Ok(capabilities) => {
+            debug!("Interface {} capabilities: {:?}", interface_name, capabilities);
+            Ok(capabilities)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command(async)]
 fn start_spoofing(
     target_ip: String,
     gateway_ip: String, 
+    gateway_ips: Option<Vec<String>>,
     interface_name: String,
+    options: Option<SpoofingOptions>,
     state: State<SpoofingSessions>,
-) -> Result<String, String> {
-    info!("Starting spoofing attack - Target: {}, Gateway: {}, Interface: {}", 
-          target_ip, gateway_ip, interface_name);
-    match kancut_lib::start_spoofing(target_ip, gateway_ip, interface_name, state) {
+    app: AppHandle,
+) -> Result<SpoofingStart, String> {
+    let interface_name = interface_arg(interface_name)?;
+    let options = options.unwrap_or_default();
+    
+    if options.dry_run {
+        info!("Dry run for spoofing - Target: {}, Gateway: {}, Interface: {}",
+              target_ip, gateway_ip, interface_name);
+        let report = kancut_lib::preflight_spoofing(&target_ip, &gateway_ip, &interface_name, &options);
+        info!("Dry run {}", if report.passed { "passed" } else { "failed" });
+        return Ok(SpoofingStart::DryRun(report));
+    }
+    
+    // Extra gateways for networks where the target may route through more than one
+    let mut gateways = vec![gateway_ip];
+    gateways.extend(gateway_ips.unwrap_or_default());
+    
+    info!("Starting spoofing attack - Target: {}, Gateways: {}, Interface: {}", 
+          target_ip, gateways.join(", "), interface_name);
+    match kancut_lib::start_spoofing(target_ip, gateways, interface_name, options, state, app) {
         Ok(session_id) => {
             info!("Spoofing started successfully with session ID: {}", session_id);
-            Ok(session_id)
+            Ok(SpoofingStart::Started(session_id))
         },
-        Err(e) => {
-            let app_error = error_handler::spoofing_error(
-                "Failed to start spoofing attack", 
-                Some(&e)
-            );
-            Err(error_handler::to_string_error(app_error))
-        }
+        Err(e) => Err(error_handler::to_string_error(e)),
     }
 }
 
-#[tauri::command]
+#[tauri::command(async)]
 fn stop_spoofing(
     session_id: String,
     state: State<SpoofingSessions>,
@@ -79,77 +558,315 @@ fn stop_spoofing(
             info!("Spoofing session stopped: {}", session_id);
             Ok(result)
         },
-        Err(e) => {
-            let app_error = error_handler::spoofing_error(
-                &format!("Failed to stop spoofing session {}", session_id),
-                Some(&e)
-            );
-            Err(error_handler::to_string_error(app_error))
-        }
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command(async)]
+fn stop_all_sessions(state: State<SpoofingSessions>) -> Result<usize, String> {
+    info!("Stopping all spoofing sessions");
+    match kancut_lib::stop_all_sessions(state) {
+        Ok(count) => {
+            info!("Stopped {} spoofing sessions", count);
+            Ok(count)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn pause_spoofing(
+    session_id: String,
+    state: State<SpoofingSessions>,
+) -> Result<bool, String> {
+    info!("Pausing spoofing session: {}", session_id);
+    match kancut_lib::pause_spoofing(session_id, state) {
+        Ok(changed) => Ok(changed),
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn resume_spoofing(
+    session_id: String,
+    state: State<SpoofingSessions>,
+) -> Result<bool, String> {
+    info!("Resuming spoofing session: {}", session_id);
+    match kancut_lib::resume_spoofing(session_id, state) {
+        Ok(changed) => Ok(changed),
+        Err(e) => Err(error_handler::to_string_error(e)),
     }
 }
 
 #[tauri::command]
 fn get_active_sessions(
+    sort_by: Option<SessionSort>,
+    limit: Option<usize>,
+    offset: Option<usize>,
     state: State<SpoofingSessions>,
-) -> Result<Vec<SpoofingSession>, String> {
+) -> Result<ActiveSessions, String> {
     debug!("Getting active spoofing sessions");
-    match kancut_lib::get_active_sessions(state) {
-        Ok(sessions) => {
+    // Without any paging argument, keep returning the plain unsorted list
+    let query = if sort_by.is_some() || limit.is_some() || offset.is_some() {
+        Some(SessionQuery {
+            sort_by: sort_by.unwrap_or_default(),
+            limit,
+            offset: offset.unwrap_or(0),
+        })
+    } else {
+        None
+    };
+    match kancut_lib::get_active_sessions(query, state) {
+        Ok(ActiveSessions::All(sessions)) => {
             debug!("Found {} active sessions", sessions.len());
-            Ok(sessions)
+            Ok(ActiveSessions::All(sessions))
         },
-        Err(e) => {
-            let app_error = error_handler::system_error(
-                "Failed to get active sessions", 
-                Some(&e)
-            );
-            Err(error_handler::to_string_error(app_error))
-        }
+        Ok(ActiveSessions::Page(page)) => {
+            debug!("Returning {} of {} active sessions", page.sessions.len(), page.total);
+            Ok(ActiveSessions::Page(page))
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
     }
 }
 
 #[tauri::command]
+fn get_recovered_sessions() -> Result<Vec<SpoofingSession>, String> {
+    debug!("Getting sessions recovered from a previous run");
+    kancut_lib::get_recovered_sessions().map_err(error_handler::to_string_error)
+}
+
+#[tauri::command(async)]
+fn restore_recovered_sessions() -> Result<usize, String> {
+    info!("Restoring ARP entries for recovered sessions");
+    match kancut_lib::restore_recovered_sessions() {
+        Ok(count) => {
+            info!("Restored {} recovered sessions", count);
+            Ok(count)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command(async)]
 fn start_spoof_all(
     devices: Vec<NetworkDevice>,
     gateway_ip: String,
     interface_name: String,
-    state: State<SpoofingSessions>
-) -> Result<Vec<String>, String> {
+    protected_ips: Option<Vec<String>>,
+    options: Option<SpoofingOptions>,
+    state: State<SpoofingSessions>,
+    app: AppHandle,
+) -> Result<SpoofAllResult, String> {
+    let interface_name = interface_arg(interface_name)?;
     info!("Starting spoofing for all {} devices on interface {} with gateway {}", 
           devices.len(), interface_name, gateway_ip);
-    match kancut_lib::start_spoof_all(devices, gateway_ip, interface_name, state) {
-        Ok(session_ids) => {
-            info!("Started spoofing for {} devices", session_ids.len());
-            Ok(session_ids)
+    let options = options.unwrap_or_default();
+    let protected_ips = protected_ips.unwrap_or_default();
+    match kancut_lib::start_spoof_all(devices, gateway_ip, interface_name, protected_ips, options, state, app) {
+        Ok(result) => {
+            info!("Started spoofing for {} devices, skipped {}, failed {}",
+                  result.session_ids.len(), result.skipped.len(), result.failed.len());
+            for skipped in &result.skipped {
+                debug!("Skipped {} ({}): {}", skipped.ip, skipped.mac, skipped.reason);
+            }
+            for failed in &result.failed {
+                warn!("Failed to spoof {} ({}): {}", failed.ip, failed.mac, failed.error);
+            }
+            Ok(result)
         },
-        Err(e) => {
-            let app_error = error_handler::spoofing_error(
-                "Failed to start spoofing for all devices", 
-                Some(&e)
-            );
-            Err(error_handler::to_string_error(app_error))
-        }
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command(async)]
+fn scan_and_spoof_all(
+    mut request: ScanAndSpoofRequest,
+    scan_id: Option<String>,
+    scans: State<ActiveScans>,
+    state: State<SpoofingSessions>,
+    app: AppHandle,
+) -> Result<ScanAndSpoofResult, String> {
+    request.interface_name = interface_arg(request.interface_name)?;
+    info!("Scanning {} and spoofing every device found with gateway {}",
+          request.interface_name, request.gateway_ip);
+    
+    // Register the scan so it can be cancelled from the UI
+    let scan = match &scan_id {
+        Some(id) => scans.register(id),
+        None => ScanHandle::new(),
+    };
+    let result = kancut_lib::scan_and_spoof_all(request, &scan, state, app);
+    if let Some(id) = &scan_id {
+        scans.remove(id);
+    }
+    
+    match result {
+        Ok(result) => {
+            match &result.spoof {
+                Some(spoof) => {
+                    info!("Found {} devices, started spoofing for {}, skipped {}, failed {}",
+                          result.scan.devices.len(), spoof.session_ids.len(),
+                          spoof.skipped.len(), spoof.failed.len());
+                    for failed in &spoof.failed {
+                        warn!("Failed to spoof {} ({}): {}", failed.ip, failed.mac, failed.error);
+                    }
+                },
+                None => info!("Scan found {} devices but did not finish; nothing was spoofed",
+                              result.scan.devices.len()),
+            }
+            Ok(result)
+        },
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn get_protected_ips() -> Result<Vec<String>, String> {
+    match kancut_lib::get_protected_ips() {
+        Ok(protected) => Ok(protected),
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn set_protected_ips(entries: Vec<String>) -> Result<Vec<String>, String> {
+    info!("Saving {} protected devices", entries.len());
+    match kancut_lib::set_protected_ips(entries) {
+        Ok(protected) => Ok(protected),
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn get_max_concurrent_sessions() -> usize {
+    kancut_lib::max_concurrent_sessions()
+}
+
+#[tauri::command]
+fn set_max_concurrent_sessions(limit: usize) -> Result<usize, String> {
+    info!("Setting the session limit to {}", limit);
+    match kancut_lib::set_max_concurrent_sessions(limit) {
+        Ok(limit) => Ok(limit),
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+#[tauri::command]
+fn set_device_label(mac: String, label: Option<String>) -> Result<HashMap<String, String>, String> {
+    info!("Setting label of {} to {:?}", mac, label);
+    match kancut_lib::set_device_label(mac, label) {
+        Ok(labels) => Ok(labels),
+        Err(e) => Err(error_handler::to_string_error(e)),
+    }
+}
+
+/// Longest the app waits on exit for sessions to restore the ARP caches they poisoned
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Stop every spoofing session so its thread restores the real ARP entries
+/// before the process exits and kills it
+fn stop_sessions_on_exit(app: &AppHandle) {
+    let (done, finished) = mpsc::channel();
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let _ = done.send(kancut_lib::stop_all_sessions(app.state::<SpoofingSessions>()));
+    });
+    
+    match finished.recv_timeout(SHUTDOWN_TIMEOUT) {
+        Ok(Ok(0)) => {},
+        Ok(Ok(count)) => info!("Stopped {} spoofing sessions before exiting", count),
+        Ok(Err(e)) => warn!("Failed to stop spoofing sessions on exit: {}", e),
+        Err(_) => warn!("Gave up waiting for spoofing sessions to restore ARP entries"),
     }
 }
 
 fn main() {
     // Initialize custom logger
-    logger::init();
+    logger::init(logger::LoggerConfig::default());
     
     info!("Starting KanCut application");
     
+    let vendors = kancut_lib::load_oui_database();
+    debug!("Loaded vendor database with {} entries", vendors);
+    
+    // Pick up sessions a crashed or killed previous run left behind
+    match kancut_lib::recover_sessions() {
+        Ok(recovered) if !recovered.is_empty() => {
+            warn!("Recovered {} spoofing sessions from a previous run; ARP entries may still be poisoned", recovered.len());
+        },
+        Ok(_) => {},
+        Err(e) => warn!("Session recovery failed: {}", e),
+    }
+    
     tauri::Builder::default()
         .manage(kancut_lib::SpoofingSessions::default())
+        .manage(kancut_lib::ActiveScans::default())
+        .manage(kancut_lib::ScanCache::default())
+        .manage(kancut_lib::ArpWatches::default())
+        .manage(kancut_lib::InterfaceWatches::default())
+        .setup(|app| {
+            let sessions = app.state::<SpoofingSessions>().inner().inner();
+            kancut_lib::spawn_session_stats_emitter(sessions, app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
+            check_privileges,
+            system_healthcheck,
+            set_log_level,
+            get_log_level,
             get_interfaces,
+            find_interface_by_ip,
             scan_network,
+            scan_cidr,
+            list_scan_profiles,
+            save_scan_profile,
+            run_scan_profile,
+            scan_diff,
+            snapshot_arp_table,
+            diff_arp_snapshots,
+            quick_scan,
+            group_devices_by_vendor,
+            export_devices,
+            cancel_scan,
+            get_default_gateway,
+            get_gateway_device,
+            test_interface,
+            ping_host,
+            resolve_host,
+            inspect_device,
+            scan_ports,
+            update_oui_database,
+            clear_vendor_cache,
+            clear_hostname_cache,
             start_spoofing,
             stop_spoofing,
+            stop_all_sessions,
+            pause_spoofing,
+            resume_spoofing,
             get_active_sessions,
-            start_spoof_all
+            start_spoof_all,
+            scan_and_spoof_all,
+            get_protected_ips,
+            set_protected_ips,
+            get_max_concurrent_sessions,
+            set_max_concurrent_sessions,
+            set_device_label,
+            get_recovered_sessions,
+            restore_recovered_sessions,
+            start_arp_watch,
+            stop_arp_watch,
+            start_interface_watch,
+            stop_interface_watch,
+            clear_arp_cache,
+            wake_on_lan
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Sessions already stopped are skipped, so handling both events is cheap
+            if let RunEvent::ExitRequested { .. } | RunEvent::Exit = event {
+                stop_sessions_on_exit(app);
+            }
+        });
 }
 
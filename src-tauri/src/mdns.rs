@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::time::{Duration, Instant};
+
+const MDNS_ADDR: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+const MDNS_PORT: u16 = 5353;
+
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const CLASS_IN: u16 = 1;
+
+/// Meta-query that every DNS-SD responder answers
+const SERVICES_QUERY: &str = "_services._dns-sd._udp.local";
+
+/// A resource record from a DNS message, with its data still in the packet
+struct Record {
+    name: String,
+    rtype: u16,
+    data_offset: usize,
+    data_len: usize,
+}
+
+/// Build a single-question DNS query
+fn build_query(id: u16, name: &str, qtype: u16) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(64);
+
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&[0x00, 0x00]);
+    packet.extend_from_slice(&1u16.to_be_bytes());
+    packet.extend_from_slice(&[0x00; 6]);
+
+    for label in name.split('.').filter(|label| !label.is_empty()) {
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+
+    packet.extend_from_slice(&qtype.to_be_bytes());
+    packet.extend_from_slice(&CLASS_IN.to_be_bytes());
+
+    packet
+}
+
+/// Read a possibly compressed name, returning it and the offset just past it
+fn read_name(packet: &[u8], mut offset: usize) -> Option<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    // Guard against pointer loops in malformed packets
+    let mut jumps = 0;
+
+    loop {
+        let len = *packet.get(offset)? as usize;
+        if len == 0 {
+            end.get_or_insert(offset + 1);
+            break;
+        }
+
+        if len & 0xc0 == 0xc0 {
+            let pointer = ((len & 0x3f) << 8) | *packet.get(offset + 1)? as usize;
+            end.get_or_insert(offset + 2);
+            jumps += 1;
+            if jumps > 16 {
+                return None;
+            }
+            offset = pointer;
+            continue;
+        }
+
+        let label = packet.get(offset + 1..offset + 1 + len)?;
+        labels.push(String::from_utf8_lossy(label).to_string());
+        offset += 1 + len;
+    }
+
+    Some((labels.join("."), end?))
+}
+
+/// Collect every answer, authority and additional record of a response
+fn parse_records(packet: &[u8]) -> Option<Vec<Record>> {
+    if packet.len() < 12 {
+        return None;
+    }
+
+    let count = |i: usize| u16::from_be_bytes([packet[i], packet[i + 1]]) as usize;
+    let questions = count(4);
+    let records = count(6) + count(8) + count(10);
+
+    let mut offset = 12;
+    for _ in 0..questions {
+        let (_, next) = read_name(packet, offset)?;
+        offset = next + 4;
+    }
+
+    let mut parsed = Vec::with_capacity(records);
+    for _ in 0..records {
+        let (name, next) = read_name(packet, offset)?;
+        let header = packet.get(next..next + 10)?;
+        let rtype = u16::from_be_bytes([header[0], header[1]]);
+        let data_len = u16::from_be_bytes([header[8], header[9]]) as usize;
+        let data_offset = next + 10;
+        packet.get(data_offset..data_offset + data_len)?;
+
+        parsed.push(Record { name, rtype, data_offset, data_len });
+        offset = data_offset + data_len;
+    }
+
+    Some(parsed)
+}
+
+/// Reverse lookup name for an IPv4 address, e.g. `4.3.2.1.in-addr.arpa`
+fn reverse_name(ip: Ipv4Addr) -> String {
+    let [a, b, c, d] = ip.octets();
+    format!("{}.{}.{}.{}.in-addr.arpa", d, c, b, a)
+}
+
+/// Pull `.local` host names out of a response: A records map a name to an
+/// address, reverse PTR records map the responder's address to its name.
+fn collect_hostnames(packet: &[u8], responder: Ipv4Addr, names: &mut HashMap<Ipv4Addr, String>) {
+    let records = match parse_records(packet) {
+        Some(records) => records,
+        None => return,
+    };
+
+    for record in records {
+        match record.rtype {
+            TYPE_A if record.data_len == 4 && record.name.ends_with(".local") => {
+                let data = &packet[record.data_offset..record.data_offset + 4];
+                let ip = Ipv4Addr::new(data[0], data[1], data[2], data[3]);
+                names.entry(ip).or_insert_with(|| record.name.clone());
+            }
+            TYPE_PTR if record.name == reverse_name(responder) => {
+                if let Some((hostname, _)) = read_name(packet, record.data_offset) {
+                    if hostname.ends_with(".local") {
+                        names.insert(responder, hostname);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Receive responses until `timeout` elapses, feeding each one to `collect_hostnames`
+fn receive_responses(socket: &UdpSocket, timeout: Duration, names: &mut HashMap<Ipv4Addr, String>) -> Vec<Ipv4Addr> {
+    let mut responders = Vec::new();
+    let mut buffer = [0u8; 9000];
+    let deadline = Instant::now() + timeout;
+
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        if remaining.is_zero() || socket.set_read_timeout(Some(remaining)).is_err() {
+            break;
+        }
+
+        match socket.recv_from(&mut buffer) {
+            Ok((len, SocketAddr::V4(from))) => {
+                let responder = *from.ip();
+                collect_hostnames(&buffer[..len], responder, names);
+                if !responders.contains(&responder) {
+                    responders.push(responder);
+                }
+            }
+            Ok(_) => {}
+            Err(_) => break,
+        }
+    }
+
+    responders
+}
+
+/// Discover `.local` host names of mDNS responders reachable from `local_ip`.
+///
+/// Sends a DNS-SD service enumeration query to the mDNS group, then asks every
+/// responder that did not announce its own address for its reverse PTR name.
+pub fn discover_hostnames(local_ip: Ipv4Addr, timeout: Duration) -> Result<HashMap<Ipv4Addr, String>, String> {
+    // A non-5353 source port makes this a legacy unicast query, so replies come straight back
+    let socket = UdpSocket::bind(SocketAddrV4::new(local_ip, 0))
+        .map_err(|e| format!("Failed to bind mDNS socket on {}: {}", local_ip, e))?;
+
+    socket.send_to(&build_query(0, SERVICES_QUERY, TYPE_PTR), SocketAddrV4::new(MDNS_ADDR, MDNS_PORT))
+        .map_err(|e| format!("Failed to send mDNS query: {}", e))?;
+
+    let mut names = HashMap::new();
+    let responders = receive_responses(&socket, timeout, &mut names);
+
+    let unnamed: Vec<Ipv4Addr> = responders
+        .into_iter()
+        .filter(|ip| *ip != local_ip && !names.contains_key(ip))
+        .collect();
+
+    if !unnamed.is_empty() {
+        for ip in &unnamed {
+            let query = build_query(0, &reverse_name(*ip), TYPE_PTR);
+            let _ = socket.send_to(&query, SocketAddr::new(IpAddr::V4(*ip), MDNS_PORT));
+        }
+        receive_responses(&socket, timeout, &mut names);
+    }
+
+    names.remove(&local_ip);
+    Ok(names)
+}
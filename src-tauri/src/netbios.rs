@@ -0,0 +1,107 @@
+use std::net::{Ipv4Addr, SocketAddrV4, UdpSocket};
+use std::time::Duration;
+
+/// NetBIOS name service port
+const NETBIOS_NS_PORT: u16 = 137;
+
+const NBSTAT_TYPE: u16 = 0x0021;
+const CLASS_IN: u16 = 0x0001;
+const TRANSACTION_ID: u16 = 0x4b43;
+
+/// Name suffix of the workstation service entry
+const WORKSTATION_SUFFIX: u8 = 0x00;
+/// Flag bit marking a group (rather than unique) name
+const GROUP_NAME_FLAG: u16 = 0x8000;
+/// Length of one entry in the node status name table
+const NAME_ENTRY_LEN: usize = 18;
+
+/// Build a node status request for the wildcard name `*`
+fn build_node_status_request() -> Vec<u8> {
+    let mut packet = Vec::with_capacity(50);
+
+    // Header: transaction id, flags, 1 question, no other records
+    packet.extend_from_slice(&TRANSACTION_ID.to_be_bytes());
+    packet.extend_from_slice(&[0x00, 0x00]);
+    packet.extend_from_slice(&1u16.to_be_bytes());
+    packet.extend_from_slice(&[0x00; 6]);
+
+    // Wildcard name, NUL padded to 16 bytes and first-level encoded
+    let mut name = [0u8; 16];
+    name[0] = b'*';
+    packet.push(32);
+    for byte in name {
+        packet.push(b'A' + (byte >> 4));
+        packet.push(b'A' + (byte & 0x0f));
+    }
+    packet.push(0);
+
+    packet.extend_from_slice(&NBSTAT_TYPE.to_be_bytes());
+    packet.extend_from_slice(&CLASS_IN.to_be_bytes());
+
+    packet
+}
+
+/// Skip an encoded name starting at `offset`, returning the offset just past it
+fn skip_name(packet: &[u8], mut offset: usize) -> Option<usize> {
+    loop {
+        let len = *packet.get(offset)? as usize;
+        if len == 0 {
+            return Some(offset + 1);
+        }
+        // Compression pointer: two bytes, ends the name
+        if len & 0xc0 == 0xc0 {
+            return Some(offset + 2);
+        }
+        offset += 1 + len;
+    }
+}
+
+/// Extract the workstation name from a node status response
+fn parse_node_status_response(packet: &[u8]) -> Option<String> {
+    if packet.len() < 12 || packet[0..2] != TRANSACTION_ID.to_be_bytes() {
+        return None;
+    }
+
+    let answers = u16::from_be_bytes([packet[6], packet[7]]);
+    if answers == 0 {
+        return None;
+    }
+
+    // Answer record: name, type, class, TTL, data length, then the name table
+    let offset = skip_name(packet, 12)?;
+    let rdata = offset + 10;
+    let name_count = *packet.get(rdata)? as usize;
+
+    for i in 0..name_count {
+        let entry = packet.get(rdata + 1 + i * NAME_ENTRY_LEN..rdata + 1 + (i + 1) * NAME_ENTRY_LEN)?;
+        let suffix = entry[15];
+        let flags = u16::from_be_bytes([entry[16], entry[17]]);
+
+        if suffix == WORKSTATION_SUFFIX && flags & GROUP_NAME_FLAG == 0 {
+            let name = String::from_utf8_lossy(&entry[..15]).trim_end().to_string();
+            if !name.is_empty() {
+                return Some(name);
+            }
+        }
+    }
+
+    None
+}
+
+/// Ask a host for its NetBIOS name table and return its workstation name
+pub fn query_workstation_name(ip: Ipv4Addr, timeout: Duration) -> Result<Option<String>, String> {
+    let socket = UdpSocket::bind("0.0.0.0:0")
+        .map_err(|e| format!("Failed to bind NetBIOS socket: {}", e))?;
+    socket.set_read_timeout(Some(timeout))
+        .map_err(|e| format!("Failed to set NetBIOS timeout: {}", e))?;
+
+    socket.send_to(&build_node_status_request(), SocketAddrV4::new(ip, NETBIOS_NS_PORT))
+        .map_err(|e| format!("Failed to send NetBIOS query to {}: {}", ip, e))?;
+
+    let mut buffer = [0u8; 1024];
+    match socket.recv_from(&mut buffer) {
+        Ok((len, _)) => Ok(parse_node_status_response(&buffer[..len])),
+        // No NetBIOS responder on the host
+        Err(_) => Ok(None),
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+
+use crate::storage;
+
+/// Official IEEE MA-L registry export
+pub const DEFAULT_OUI_URL: &str = "https://standards-oui.ieee.org/oui/oui.csv";
+
+/// Cached copy of the last successful download, inside the config directory
+const OUI_CACHE_FILE: &str = "oui.json";
+
+/// A real registry export has tens of thousands of rows; anything much smaller is not one
+const MIN_OUI_ENTRIES: usize = 10_000;
+
+/// Small built-in table used until a full registry has been downloaded
+const EMBEDDED_OUI: &[(&str, &str)] = &[
+    ("000569", "VMware, Inc."),
+    ("000C29", "VMware, Inc."),
+    ("005056", "VMware, Inc."),
+    ("080027", "PCS Systemtechnik GmbH"),
+    ("00155D", "Microsoft Corporation"),
+    ("0003FF", "Microsoft Corporation"),
+    ("00163E", "Xensource, Inc."),
+    ("001C42", "Parallels, Inc."),
+    ("B827EB", "Raspberry Pi Foundation"),
+    ("DCA632", "Raspberry Pi Trading Ltd"),
+    ("E45F01", "Raspberry Pi Trading Ltd"),
+    ("001A11", "Google, Inc."),
+    ("F4F5D8", "Google, Inc."),
+    ("18B430", "Nest Labs Inc."),
+    ("001788", "Philips Lighting BV"),
+    ("44650D", "Amazon Technologies Inc."),
+    ("FCA667", "Amazon Technologies Inc."),
+    ("001B63", "Apple, Inc."),
+    ("F01898", "Apple, Inc."),
+    ("0024D7", "Intel Corporate"),
+    ("00E04C", "Realtek Semiconductor Corp."),
+    ("00044B", "NVIDIA"),
+    ("00095B", "NETGEAR"),
+    ("00146C", "NETGEAR"),
+    ("50C7BF", "TP-LINK TECHNOLOGIES CO.,LTD."),
+    ("F4EC38", "TP-LINK TECHNOLOGIES CO.,LTD."),
+    ("00259C", "Cisco-Linksys, LLC"),
+    ("001132", "Synology Incorporated"),
+];
+
+/// Active vendor table; `None` until something has been loaded
+static OUI_TABLE: RwLock<Option<HashMap<String, String>>> = RwLock::new(None);
+
+/// Vendors already looked up, by OUI key, shared by every scan
+static VENDOR_CACHE: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+
+fn embedded_table() -> HashMap<String, String> {
+    EMBEDDED_OUI
+        .iter()
+        .map(|(prefix, vendor)| (prefix.to_string(), vendor.to_string()))
+        .collect()
+}
+
+fn install(table: HashMap<String, String>) {
+    if let Ok(mut active) = OUI_TABLE.write() {
+        *active = Some(table);
+    }
+    // Cached vendors may differ in the new table
+    clear_cache();
+}
+
+/// Forget every cached vendor. Returns the number of entries dropped.
+pub fn clear_cache() -> usize {
+    match VENDOR_CACHE.lock() {
+        Ok(mut cache) => cache.take().map(|cache| cache.len()).unwrap_or(0),
+        Err(_) => 0,
+    }
+}
+
+/// Table key for a MAC: its first three bytes as uppercase hex, e.g. `B827EB`
+fn oui_key(mac: &str) -> Option<String> {
+    let hex: String = mac.chars().filter(|c| c.is_ascii_hexdigit()).take(6).collect();
+    if hex.len() == 6 {
+        Some(hex.to_uppercase())
+    } else {
+        None
+    }
+}
+
+/// Vendor registered for the MAC's OUI
+pub fn lookup(mac: &str) -> Option<String> {
+    let key = oui_key(mac)?;
+
+    if let Ok(cache) = VENDOR_CACHE.lock() {
+        if let Some(vendor) = cache.as_ref().and_then(|cache| cache.get(&key)) {
+            return Some(vendor.clone());
+        }
+    }
+
+    let vendor = lookup_in_table(&key)?;
+    if let Ok(mut cache) = VENDOR_CACHE.lock() {
+        cache.get_or_insert_with(HashMap::new).insert(key, vendor.clone());
+    }
+    Some(vendor)
+}
+
+fn lookup_in_table(key: &str) -> Option<String> {
+    if let Ok(active) = OUI_TABLE.read() {
+        if let Some(table) = active.as_ref() {
+            return table.get(key).cloned();
+        }
+    }
+
+    EMBEDDED_OUI
+        .iter()
+        .find(|(prefix, _)| *prefix == key)
+        .map(|(_, vendor)| vendor.to_string())
+}
+
+/// Split one CSV line into fields, honoring double-quoted fields with `""` escapes
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+/// Parse the IEEE `Registry,Assignment,Organization Name,Organization Address` export
+pub fn parse_oui_csv(csv: &str) -> Result<HashMap<String, String>, String> {
+    let mut lines = csv.lines();
+
+    let header = lines.next().ok_or("OUI file is empty")?;
+    if !header.starts_with("Registry,Assignment,Organization Name") {
+        return Err("OUI file does not look like an IEEE registry export".to_string());
+    }
+
+    let mut table = HashMap::new();
+    for line in lines {
+        let fields = split_csv_line(line);
+        if fields.len() < 3 {
+            continue;
+        }
+
+        let assignment = fields[1].trim().to_uppercase();
+        let vendor = fields[2].trim();
+        if assignment.len() == 6 && assignment.chars().all(|c| c.is_ascii_hexdigit()) && !vendor.is_empty() {
+            table.insert(assignment, vendor.to_string());
+        }
+    }
+
+    if table.len() < MIN_OUI_ENTRIES {
+        return Err(format!(
+            "OUI file only contained {} entries (expected at least {})",
+            table.len(), MIN_OUI_ENTRIES
+        ));
+    }
+
+    Ok(table)
+}
+
+/// Load the cached registry if there is one, otherwise the embedded table.
+/// Returns the number of entries now active.
+pub fn load() -> usize {
+    let table = match storage::load_json::<HashMap<String, String>>(OUI_CACHE_FILE) {
+        Ok(Some(table)) if !table.is_empty() => table,
+        Ok(_) => embedded_table(),
+        Err(e) => {
+            eprintln!("Ignoring cached OUI database: {}", e);
+            embedded_table()
+        }
+    };
+
+    let count = table.len();
+    install(table);
+    count
+}
+
+/// Download, validate and cache a fresh registry, then make it active.
+/// On failure the previously active table stays in place.
+pub fn update(url: &str) -> Result<usize, String> {
+    let csv = ureq::get(url)
+        .call()
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?
+        .into_string()
+        .map_err(|e| format!("Failed to read OUI download: {}", e))?;
+
+    let table = parse_oui_csv(&csv)?;
+    storage::save_json(OUI_CACHE_FILE, &table)?;
+
+    let count = table.len();
+    install(table);
+    Ok(count)
+}
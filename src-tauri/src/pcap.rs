@@ -0,0 +1,113 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Classic libpcap magic number, microsecond timestamps
+const PCAP_MAGIC: u32 = 0xa1b2_c3d4;
+const PCAP_VERSION_MAJOR: u16 = 2;
+const PCAP_VERSION_MINOR: u16 = 4;
+/// Largest frame stored in full
+const PCAP_SNAPLEN: u32 = 65_535;
+/// LINKTYPE_ETHERNET
+const PCAP_LINKTYPE_ETHERNET: u32 = 1;
+
+const GLOBAL_HEADER_LEN: u64 = 24;
+const RECORD_HEADER_LEN: u64 = 16;
+
+/// Writes Ethernet frames to a `.pcap` file readable by Wireshark and tcpdump,
+/// dropping frames once the file would grow past its size cap
+pub struct PcapWriter {
+    file: BufWriter<File>,
+    bytes_written: u64,
+    max_bytes: u64,
+}
+
+impl PcapWriter {
+    /// Create or truncate `path` and write the global header
+    pub fn create(path: &Path, max_bytes: u64) -> Result<Self, String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Failed to create capture file {}: {}", path.display(), e))?;
+
+        let mut writer = Self {
+            file: BufWriter::new(file),
+            bytes_written: 0,
+            max_bytes,
+        };
+
+        let mut header = Vec::with_capacity(GLOBAL_HEADER_LEN as usize);
+        header.extend_from_slice(&PCAP_MAGIC.to_le_bytes());
+        header.extend_from_slice(&PCAP_VERSION_MAJOR.to_le_bytes());
+        header.extend_from_slice(&PCAP_VERSION_MINOR.to_le_bytes());
+        // Timestamps are UTC, with no accuracy claim
+        header.extend_from_slice(&0i32.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&PCAP_SNAPLEN.to_le_bytes());
+        header.extend_from_slice(&PCAP_LINKTYPE_ETHERNET.to_le_bytes());
+        writer.write_all(&header)?;
+
+        Ok(writer)
+    }
+
+    /// Append one frame stamped with the current time. Returns false without
+    /// writing anything once the size cap is reached.
+    #[cfg_attr(not(feature = "raw-packets"), allow(dead_code))]
+    pub fn write_frame(&mut self, frame: &[u8]) -> Result<bool, String> {
+        let captured = &frame[..frame.len().min(PCAP_SNAPLEN as usize)];
+        if self.bytes_written + RECORD_HEADER_LEN + captured.len() as u64 > self.max_bytes {
+            return Ok(false);
+        }
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let mut record = Vec::with_capacity(RECORD_HEADER_LEN as usize + captured.len());
+        record.extend_from_slice(&(timestamp.as_secs() as u32).to_le_bytes());
+        record.extend_from_slice(&timestamp.subsec_micros().to_le_bytes());
+        record.extend_from_slice(&(captured.len() as u32).to_le_bytes());
+        record.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        record.extend_from_slice(captured);
+        self.write_all(&record)?;
+
+        Ok(true)
+    }
+
+    /// Flush everything written so far and close the file
+    #[cfg_attr(not(feature = "raw-packets"), allow(dead_code))]
+    pub fn finish(mut self) -> Result<(), String> {
+        self.file.flush()
+            .map_err(|e| format!("Failed to flush capture file: {}", e))
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.file.write_all(bytes)
+            .map_err(|e| format!("Failed to write capture file: {}", e))?;
+        self.bytes_written += bytes.len() as u64;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stops_writing_at_the_size_cap() {
+        let path = std::env::temp_dir().join(format!("kancut-pcap-{}.pcap", std::process::id()));
+        let frame = [0xabu8; 60];
+        let max_bytes = GLOBAL_HEADER_LEN + 2 * (RECORD_HEADER_LEN + frame.len() as u64);
+
+        let mut writer = PcapWriter::create(&path, max_bytes).unwrap();
+        assert!(writer.write_frame(&frame).unwrap());
+        assert!(writer.write_frame(&frame).unwrap());
+        assert!(!writer.write_frame(&frame).unwrap());
+        writer.finish().unwrap();
+
+        let written = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(written.len() as u64, max_bytes);
+        assert_eq!(written[0..4], PCAP_MAGIC.to_le_bytes());
+        assert_eq!(written[20..24], PCAP_LINKTYPE_ETHERNET.to_le_bytes());
+        // incl_len and orig_len of the first record
+        assert_eq!(written[32..36], 60u32.to_le_bytes());
+        assert_eq!(written[36..40], 60u32.to_le_bytes());
+    }
+}
@@ -0,0 +1,116 @@
+#[cfg(test)]
+use std::collections::HashMap;
+use std::env;
+use std::path::Path;
+use std::process::Command;
+#[cfg(test)]
+use std::sync::Mutex;
+
+/// What a finished command printed and whether it exited successfully
+#[derive(Debug, Clone, Default)]
+pub struct CommandOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs external programs, so code shelling out to ping, arp, netsh and
+/// friends can be exercised against a mock
+pub trait CommandRunner: Send + Sync {
+    /// Run `program` with `args` and wait for it to exit. Only failing to
+    /// start the program is an error; a non-zero exit is reported in the output.
+    fn run(&self, program: &str, args: &[&str]) -> Result<CommandOutput, String>;
+}
+
+/// Spawns real processes through `std::process::Command`
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn run(&self, program: &str, args: &[&str]) -> Result<CommandOutput, String> {
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+        Ok(CommandOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        })
+    }
+}
+
+/// Whether `program` can be found in one of the `PATH` directories
+pub fn command_exists(program: &str) -> bool {
+    let path = match env::var_os("PATH") {
+        Some(path) => path,
+        None => return false,
+    };
+
+    // Windows resolves `arp` to `arp.exe`
+    let names: Vec<String> = if cfg!(windows) {
+        vec![format!("{}.exe", program), program.to_string()]
+    } else {
+        vec![program.to_string()]
+    };
+
+    env::split_paths(&path).any(|dir| names.iter().any(|name| is_file(&dir.join(name))))
+}
+
+fn is_file(path: &Path) -> bool {
+    path.metadata().map(|metadata| metadata.is_file()).unwrap_or(false)
+}
+
+/// Records every command it is asked to run and answers with canned output.
+/// Programs without canned output succeed and print nothing.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockRunner {
+    outputs: Mutex<HashMap<String, CommandOutput>>,
+    calls: Mutex<Vec<Vec<String>>>,
+}
+
+#[cfg(test)]
+impl MockRunner {
+    /// Answer every run of `program` with `stdout` and a successful exit
+    pub fn with_stdout(self, program: &str, stdout: &str) -> Self {
+        self.with_output(program, CommandOutput {
+            success: true,
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+        })
+    }
+
+    /// Answer every run of `program` with `stderr` and a failed exit
+    pub fn with_failure(self, program: &str, stderr: &str) -> Self {
+        self.with_output(program, CommandOutput {
+            success: false,
+            stdout: String::new(),
+            stderr: stderr.to_string(),
+        })
+    }
+
+    fn with_output(self, program: &str, output: CommandOutput) -> Self {
+        self.outputs.lock().unwrap().insert(program.to_string(), output);
+        self
+    }
+
+    /// Every command run so far, each as the program followed by its arguments
+    pub fn calls(&self) -> Vec<Vec<String>> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl CommandRunner for MockRunner {
+    fn run(&self, program: &str, args: &[&str]) -> Result<CommandOutput, String> {
+        let mut call = vec![program.to_string()];
+        call.extend(args.iter().map(|arg| arg.to_string()));
+        self.calls.lock().unwrap().push(call);
+
+        Ok(self.outputs.lock().unwrap().get(program).cloned().unwrap_or_else(|| CommandOutput {
+            success: true,
+            ..CommandOutput::default()
+        }))
+    }
+}
@@ -0,0 +1,460 @@
+use std::fs;
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, UdpSocket};
+use std::process::Command;
+
+use super::{format_mac, ArpEntry, CommandRunner, NetworkPlatform};
+use crate::arp_packet::parse_mac;
+
+const IP_FORWARD_PATH: &str = "/proc/sys/net/ipv4/ip_forward";
+
+/// `ATF_PERM` in the flags column of /proc/net/arp: a static entry
+const ATF_PERM: u32 = 0x04;
+
+/// Discard service; a datagram to it makes the kernel resolve the host
+const DISCARD_PORT: u16 = 9;
+
+/// procfs, sysfs and iproute2
+pub struct LinuxPlatform;
+
+impl NetworkPlatform for LinuxPlatform {
+    const ELEVATION_HINT: &'static str = "Run KanCut as root, e.g. with sudo";
+
+    const REQUIRED_COMMANDS: &'static [&'static str] = &["ip", "ping", "iptables"];
+
+    const DNS_REDIRECT_SUPPORTED: bool = true;
+
+    fn is_elevated() -> bool {
+        unsafe { libc::geteuid() == 0 }
+    }
+
+    /// Read `operstate` and `speed` from sysfs
+    fn link_status(interface_name: &str) -> Result<(bool, Option<u64>), String> {
+        let dir = format!("/sys/class/net/{}", interface_name);
+        let operstate = fs::read_to_string(format!("{}/operstate", dir))
+            .map_err(|e| format!("Failed to read link state of {}: {}", interface_name, e))?;
+
+        // Some virtual drivers never report a state even while working fine
+        let is_up = matches!(operstate.trim(), "up" | "unknown");
+        // Reading the speed fails while the link is down and gives -1 when unknown
+        let speed_mbps = fs::read_to_string(format!("{}/speed", dir))
+            .ok()
+            .and_then(|speed| speed.trim().parse::<i64>().ok())
+            .filter(|speed| is_up && *speed > 0)
+            .map(|speed| speed as u64);
+
+        Ok((is_up, speed_mbps))
+    }
+
+    /// Wireless drivers expose a `wireless` directory in sysfs
+    fn is_wireless(interface_name: &str) -> Result<bool, String> {
+        let dir = format!("/sys/class/net/{}", interface_name);
+        if fs::metadata(&dir).is_err() {
+            return Err(format!("Interface {} not found", interface_name));
+        }
+
+        Ok(fs::metadata(format!("{}/wireless", dir)).is_ok()
+            || fs::metadata(format!("{}/phy80211", dir)).is_ok())
+    }
+
+    /// Parse /proc/net/route, which prints addresses as native-endian hex words
+    fn default_routes(interface_name: Option<&str>) -> Result<Vec<Ipv4Addr>, String> {
+        let routes = fs::read_to_string("/proc/net/route")
+            .map_err(|e| format!("Failed to read routing table: {}", e))?;
+
+        let mut gateways = Vec::new();
+        for line in routes.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 8 || fields[1] != "00000000" || fields[7] != "00000000" {
+                continue;
+            }
+            if interface_name.is_some_and(|name| fields[0] != name) {
+                continue;
+            }
+
+            if let Ok(gateway) = u32::from_str_radix(fields[2], 16) {
+                gateways.push(Ipv4Addr::from(gateway.to_ne_bytes()));
+            }
+        }
+
+        Ok(gateways)
+    }
+
+    /// Parse /proc/net/arp: `IP address  HW type  Flags  HW address  Mask  Device`
+    fn arp_table() -> Result<Vec<ArpEntry>, String> {
+        let table = fs::read_to_string("/proc/net/arp")
+            .map_err(|e| format!("Failed to read ARP table: {}", e))?;
+
+        let mut entries = Vec::new();
+        for line in table.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 4 {
+                continue;
+            }
+
+            let ip = match fields[0].parse::<Ipv4Addr>() {
+                Ok(ip) => ip,
+                Err(_) => continue,
+            };
+            let flags = u32::from_str_radix(fields[2].trim_start_matches("0x"), 16).unwrap_or(0);
+            // Incomplete entries list a zero MAC, like the Windows table does
+            let mac = parse_mac(fields[3]).map(format_mac)
+                .unwrap_or_else(|| "00:00:00:00:00:00".to_string());
+
+            entries.push(ArpEntry {
+                ip: ip.to_string(),
+                mac,
+                is_static: flags & ATF_PERM != 0,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    /// Parse `ip -4 neigh show`: `192.168.1.1 dev wlan0 lladdr aa:bb:cc:dd:ee:ff REACHABLE`
+    fn neighbors(runner: &dyn CommandRunner) -> Result<Vec<(Ipv4Addr, [u8; 6])>, String> {
+        let output = runner.run("ip", &["-4", "neigh", "show"])?;
+
+        if !output.success {
+            return Ok(Vec::new());
+        }
+
+        Ok(output.stdout.lines()
+            .filter_map(|line| {
+                let ip = line.split_whitespace().next()?.parse::<Ipv4Addr>().ok()?;
+                Some((ip, lladdr(line)?))
+            })
+            .collect())
+    }
+
+    /// Parse `ip -6 neigh show dev <interface>`; link-local addresses get the
+    /// interface name as scope id (`fe80::1%wlan0`)
+    fn ipv6_neighbors(interface_name: &str) -> Result<Vec<(String, String)>, String> {
+        let output = Command::new("ip")
+            .arg("-6")
+            .arg("neigh")
+            .arg("show")
+            .arg("dev")
+            .arg(interface_name)
+            .output()
+            .map_err(|e| format!("Failed to run ip neigh: {}", e))?;
+
+        if !output.status.success() {
+            return Ok(Vec::new());
+        }
+
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        let mut neighbors = Vec::new();
+
+        for line in output_str.lines() {
+            let ip_addr = match line.split_whitespace().next().and_then(|ip| ip.parse::<Ipv6Addr>().ok()) {
+                Some(ip_addr) => ip_addr,
+                None => continue,
+            };
+            // FAILED and INCOMPLETE entries have no link-layer address
+            let mac = match lladdr(line) {
+                Some(mac) if mac != [0u8; 6] => format_mac(mac),
+                _ => continue,
+            };
+            if ip_addr.is_multicast() || ip_addr.is_unspecified() {
+                continue;
+            }
+
+            let ip = if (ip_addr.segments()[0] & 0xffc0) == 0xfe80 {
+                format!("{}%{}", ip_addr, interface_name)
+            } else {
+                ip_addr.to_string()
+            };
+
+            neighbors.push((ip, mac));
+        }
+
+        Ok(neighbors)
+    }
+
+    /// Run iputils `ping` and read the round trip from its `time=` field
+    fn ping(ip: Ipv4Addr, timeout_ms: u32) -> Result<Option<u32>, String> {
+        let output = Command::new("ping")
+            .arg("-c")
+            .arg("1")
+            .arg("-W")
+            .arg(timeout_secs(timeout_ms))
+            .arg(ip.to_string())
+            .output()
+            .map_err(|e| format!("Failed to run ping: {}", e))?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        let rtt_ms = output_str
+            .split_whitespace()
+            .find_map(|field| field.strip_prefix("time="))
+            .and_then(|time| time.parse::<f64>().ok())
+            .map(|time| time.round() as u32)
+            .unwrap_or(0);
+
+        Ok(Some(rtt_ms))
+    }
+
+    fn native_icmp_available() -> bool {
+        // `ping` above runs the ping command
+        false
+    }
+
+    fn probe_host(runner: &dyn CommandRunner, ip: Ipv4Addr, timeout_ms: u32) {
+        let _ = runner.run("ping", &["-c", "1", "-W", &timeout_secs(timeout_ms), &ip.to_string()]);
+    }
+
+    /// Kernel-resolved through a UDP datagram, so no command is run
+    fn request_arp(_runner: &dyn CommandRunner, ip: Ipv4Addr) {
+        if let Ok(socket) = UdpSocket::bind("0.0.0.0:0") {
+            let _ = socket.send_to(&[0], SocketAddrV4::new(ip, DISCARD_PORT));
+        }
+    }
+
+    fn set_static_arp(runner: &dyn CommandRunner, interface_name: &str, ip: Ipv4Addr, mac: [u8; 6]) -> Result<(), String> {
+        let output = runner.run("ip", &[
+            "neigh", "replace", &ip.to_string(),
+            "lladdr", &format_mac(mac),
+            "nud", "permanent",
+            "dev", interface_name,
+        ])?;
+
+        if !output.success {
+            return Err(format!("ip neigh replace failed: {}", output.stderr));
+        }
+
+        Ok(())
+    }
+
+    fn delete_arp_entry(interface_name: &str, ip: Ipv4Addr) -> Result<(), String> {
+        let output = Command::new("ip")
+            .arg("neigh")
+            .arg("del")
+            .arg(ip.to_string())
+            .arg("dev")
+            .arg(interface_name)
+            .output()
+            .map_err(|e| format!("Failed to run ip neigh: {}", e))?;
+
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).to_string());
+        }
+
+        Ok(())
+    }
+
+    fn clear_arp_cache(interface_name: Option<&str>) -> Result<(), String> {
+        let mut command = Command::new("ip");
+        command.arg("-4").arg("neigh").arg("flush");
+        match interface_name {
+            Some(interface_name) => command.arg("dev").arg(interface_name),
+            None => command.arg("all"),
+        };
+
+        let output = command.output()
+            .map_err(|e| format!("Failed to run ip neigh: {}", e))?;
+
+        if !output.status.success() {
+            let error_msg = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("ip neigh flush failed: {}", error_msg));
+        }
+
+        Ok(())
+    }
+
+    fn is_ip_forwarding_enabled() -> Result<bool, String> {
+        let value = fs::read_to_string(IP_FORWARD_PATH)
+            .map_err(|e| format!("Failed to read {}: {}", IP_FORWARD_PATH, e))?;
+        Ok(value.trim() == "1")
+    }
+
+    fn set_ip_forwarding(enabled: bool) -> Result<(), String> {
+        fs::write(IP_FORWARD_PATH, if enabled { "1" } else { "0" })
+            .map_err(|e| format!("Failed to write {}: {}", IP_FORWARD_PATH, e))
+    }
+
+    /// Append or delete an iptables `REDIRECT` rule in the nat table
+    fn redirect_dns(interface_name: &str, target_ip: Ipv4Addr, enabled: bool) -> Result<(), String> {
+        let output = Command::new("iptables")
+            .arg("-t")
+            .arg("nat")
+            .arg(if enabled { "-A" } else { "-D" })
+            .arg("PREROUTING")
+            .arg("-i")
+            .arg(interface_name)
+            .arg("-s")
+            .arg(target_ip.to_string())
+            .arg("-p")
+            .arg("udp")
+            .arg("--dport")
+            .arg("53")
+            .arg("-j")
+            .arg("REDIRECT")
+            .arg("--to-ports")
+            .arg("53")
+            .output()
+            .map_err(|e| format!("Failed to run iptables: {}", e))?;
+
+        if !output.status.success() {
+            let error_msg = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("iptables failed: {}", error_msg));
+        }
+
+        Ok(())
+    }
+}
+
+/// The MAC following `lladdr` in an `ip neigh` line
+fn lladdr(line: &str) -> Option<[u8; 6]> {
+    let mut fields = line.split_whitespace();
+    fields.find(|field| *field == "lladdr")?;
+    parse_mac(fields.next()?)
+}
+
+/// `ping -W` takes whole seconds on older iputils
+fn timeout_secs(timeout_ms: u32) -> String {
+    ((timeout_ms + 999) / 1000).max(1).to_string()
+}
+
+/// Sends Ethernet frames through an `AF_PACKET` socket on one interface
+pub struct PacketSocket {
+    fd: libc::c_int,
+    ifindex: libc::c_int,
+}
+
+impl PacketSocket {
+    /// Open a packet socket on the interface whose hardware address is `mac`
+    pub fn open(mac: [u8; 6]) -> Result<Self, String> {
+        let ifindex = find_ifindex(mac)?;
+
+        let protocol = (libc::ETH_P_ARP as u16).to_be() as libc::c_int;
+        let fd = unsafe { libc::socket(libc::AF_PACKET, libc::SOCK_RAW, protocol) };
+        if fd < 0 {
+            return Err(format!("Failed to open packet socket: {}", io::Error::last_os_error()));
+        }
+
+        Ok(Self { fd, ifindex })
+    }
+
+    /// Send a complete Ethernet frame; the destination is taken from its header
+    pub fn send(&self, frame: &[u8]) -> Result<(), String> {
+        if frame.len() < 14 {
+            return Err("Frame is shorter than an Ethernet header".to_string());
+        }
+
+        let mut addr: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+        addr.sll_family = libc::AF_PACKET as libc::c_ushort;
+        addr.sll_protocol = (libc::ETH_P_ARP as u16).to_be();
+        addr.sll_ifindex = self.ifindex;
+        addr.sll_halen = 6;
+        addr.sll_addr[..6].copy_from_slice(&frame[0..6]);
+
+        let sent = unsafe {
+            libc::sendto(
+                self.fd,
+                frame.as_ptr() as *const libc::c_void,
+                frame.len(),
+                0,
+                &addr as *const libc::sockaddr_ll as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t,
+            )
+        };
+        if sent < 0 {
+            return Err(format!("Failed to send frame: {}", io::Error::last_os_error()));
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for PacketSocket {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+/// Index of the interface whose sysfs `address` is `mac`
+fn find_ifindex(mac: [u8; 6]) -> Result<libc::c_int, String> {
+    let interfaces = fs::read_dir("/sys/class/net")
+        .map_err(|e| format!("Failed to list interfaces: {}", e))?;
+
+    for interface in interfaces.flatten() {
+        let dir = interface.path();
+        let address = fs::read_to_string(dir.join("address")).unwrap_or_default();
+        if parse_mac(address.trim()) != Some(mac) {
+            continue;
+        }
+
+        return fs::read_to_string(dir.join("ifindex"))
+            .map_err(|e| format!("Failed to read interface index: {}", e))?
+            .trim()
+            .parse()
+            .map_err(|e| format!("Invalid interface index: {}", e));
+    }
+
+    Err(format!("No interface found with MAC {}", format_mac(mac)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::MockRunner;
+
+    /// Captured from `ip -4 neigh show` on Debian 12
+    const IP_NEIGH: &str = "\
+192.168.1.1 dev wlan0 lladdr a4:2b:b0:12:34:56 REACHABLE
+192.168.1.23 dev wlan0 FAILED
+192.168.1.40 dev wlan0 lladdr 3c:22:fb:01:02:03 STALE
+172.17.0.2 dev docker0 lladdr 02:42:ac:11:00:02 PERMANENT
+";
+
+    #[test]
+    fn neighbors_parses_ip_neigh() {
+        let runner = MockRunner::default().with_stdout("ip", IP_NEIGH);
+
+        let neighbors = LinuxPlatform::neighbors(&runner).unwrap();
+
+        assert_eq!(neighbors, vec![
+            (Ipv4Addr::new(192, 168, 1, 1), [0xa4, 0x2b, 0xb0, 0x12, 0x34, 0x56]),
+            (Ipv4Addr::new(192, 168, 1, 40), [0x3c, 0x22, 0xfb, 0x01, 0x02, 0x03]),
+            (Ipv4Addr::new(172, 17, 0, 2), [0x02, 0x42, 0xac, 0x11, 0x00, 0x02]),
+        ]);
+        assert_eq!(runner.calls(), vec![vec!["ip", "-4", "neigh", "show"]]);
+    }
+
+    #[test]
+    fn neighbors_empty_when_ip_fails() {
+        let runner = MockRunner::default().with_failure("ip", "Cannot open netlink socket");
+
+        assert_eq!(LinuxPlatform::neighbors(&runner).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn static_arp_adds_permanent_neighbor() {
+        let runner = MockRunner::default();
+
+        LinuxPlatform::set_static_arp(&runner, "wlan0", Ipv4Addr::new(192, 168, 1, 1), [0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff]).unwrap();
+
+        assert_eq!(runner.calls(), vec![vec![
+            "ip", "neigh", "replace", "192.168.1.1", "lladdr", "aa:bb:cc:dd:ee:ff", "nud", "permanent", "dev", "wlan0",
+        ]]);
+    }
+
+    #[test]
+    fn probe_rounds_timeout_up_to_whole_seconds() {
+        let runner = MockRunner::default();
+
+        LinuxPlatform::probe_host(&runner, Ipv4Addr::new(10, 0, 0, 5), 50);
+        LinuxPlatform::probe_host(&runner, Ipv4Addr::new(10, 0, 0, 5), 1500);
+
+        assert_eq!(runner.calls(), vec![
+            vec!["ping", "-c", "1", "-W", "1", "10.0.0.5"],
+            vec!["ping", "-c", "1", "-W", "2", "10.0.0.5"],
+        ]);
+    }
+}
@@ -0,0 +1,543 @@
+use std::ffi::CString;
+use std::io::{self, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, UdpSocket};
+use std::process::{Command, Stdio};
+
+use network_interface::{NetworkInterface, NetworkInterfaceConfig};
+
+use super::{format_mac, ArpEntry, CommandRunner, NetworkPlatform, SystemRunner};
+use crate::arp_packet::parse_mac;
+
+const IP_FORWARDING_SYSCTL: &str = "net.inet.ip.forwarding";
+
+/// Discard service; a datagram to it makes the kernel resolve the host
+const DISCARD_PORT: u16 = 9;
+
+/// The stock pf.conf evaluates `rdr-anchor "com.apple/*"`, so rules loaded
+/// below it take effect without editing the main ruleset
+const PF_ANCHOR_PREFIX: &str = "com.apple/kancut";
+
+/// How many `/dev/bpfN` devices to try before giving up
+const MAX_BPF_DEVICES: u32 = 256;
+
+/// BSD userland tools: arp, ndp, route, ifconfig and sysctl, plus networksetup
+pub struct MacosPlatform;
+
+impl NetworkPlatform for MacosPlatform {
+    const ELEVATION_HINT: &'static str = "Run KanCut as root, e.g. with sudo";
+
+    const REQUIRED_COMMANDS: &'static [&'static str] = &["arp", "ndp", "ping", "route", "ifconfig", "sysctl", "pfctl", "networksetup"];
+
+    const DNS_REDIRECT_SUPPORTED: bool = true;
+
+    fn is_elevated() -> bool {
+        unsafe { libc::geteuid() == 0 }
+    }
+
+    /// Read `status:` and the media line from `ifconfig <interface>`
+    fn link_status(interface_name: &str) -> Result<(bool, Option<u64>), String> {
+        let output = Command::new("ifconfig")
+            .arg(interface_name)
+            .output()
+            .map_err(|e| format!("Failed to run ifconfig: {}", e))?;
+
+        if !output.status.success() {
+            return Err(format!("Interface {} not found", interface_name));
+        }
+
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        let mut status = None;
+        let mut speed_mbps = None;
+
+        for line in output_str.lines().map(str::trim) {
+            if let Some(value) = line.strip_prefix("status:") {
+                status = Some(value.trim() == "active");
+            } else if let Some(media) = line.strip_prefix("media:") {
+                // e.g. `autoselect (1000baseT <full-duplex>)`
+                speed_mbps = media
+                    .split(|c: char| c == '(' || c.is_whitespace())
+                    .find_map(|field| field.split("base").next()?.parse::<u64>().ok());
+            }
+        }
+
+        // Loopback and tunnels print no status line but are always up
+        let is_up = status.unwrap_or(true);
+        Ok((is_up, speed_mbps.filter(|_| is_up)))
+    }
+
+    /// Look the device up among the Wi-Fi hardware ports
+    fn is_wireless(interface_name: &str) -> Result<bool, String> {
+        Ok(wifi_devices(&SystemRunner)?.iter().any(|device| device == interface_name))
+    }
+
+    /// Parse the `gateway:` and `interface:` lines of `route -n get default`
+    fn default_routes(interface_name: Option<&str>) -> Result<Vec<Ipv4Addr>, String> {
+        let output = Command::new("route")
+            .arg("-n")
+            .arg("get")
+            .arg("default")
+            .output()
+            .map_err(|e| format!("Failed to run route: {}", e))?;
+
+        // Exits non-zero when there is no default route
+        if !output.status.success() {
+            return Ok(Vec::new());
+        }
+
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        let route_interface = output_str.lines()
+            .find_map(|line| line.trim().strip_prefix("interface:"))
+            .map(|name| name.trim());
+        if interface_name.is_some_and(|name| route_interface != Some(name)) {
+            return Ok(Vec::new());
+        }
+
+        Ok(output_str.lines()
+            .filter_map(|line| line.trim().strip_prefix("gateway:"))
+            .filter_map(|gateway| gateway.trim().parse::<Ipv4Addr>().ok())
+            .collect())
+    }
+
+    /// Parse `arp -an`: `? (192.168.1.1) at aa:bb:cc:dd:ee:ff on en0 ifscope [ethernet]`
+    fn arp_table() -> Result<Vec<ArpEntry>, String> {
+        Ok(read_arp_cache(&SystemRunner)?
+            .into_iter()
+            .map(|(ip, mac, is_static)| ArpEntry {
+                ip: ip.to_string(),
+                mac: format_mac(mac),
+                is_static,
+            })
+            .collect())
+    }
+
+    fn neighbors(runner: &dyn CommandRunner) -> Result<Vec<(Ipv4Addr, [u8; 6])>, String> {
+        Ok(read_arp_cache(runner)?
+            .into_iter()
+            .map(|(ip, mac, _)| (ip, mac))
+            .collect())
+    }
+
+    /// Parse `ndp -an`: `fe80::1%en0  aa:bb:cc:dd:ee:ff  en0 23h59m58s S R`.
+    /// Link-local addresses already carry the interface as scope id.
+    fn ipv6_neighbors(interface_name: &str) -> Result<Vec<(String, String)>, String> {
+        let output = Command::new("ndp")
+            .arg("-an")
+            .output()
+            .map_err(|e| format!("Failed to run ndp: {}", e))?;
+
+        if !output.status.success() {
+            return Ok(Vec::new());
+        }
+
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        let mut neighbors = Vec::new();
+
+        for line in output_str.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 3 || fields[2] != interface_name {
+                continue;
+            }
+
+            let ip_addr = match fields[0].split('%').next().and_then(|ip| ip.parse::<Ipv6Addr>().ok()) {
+                Some(ip_addr) => ip_addr,
+                None => continue,
+            };
+            // Unresolved entries show `(incomplete)` instead of a MAC
+            let mac = match parse_mac(fields[1]) {
+                Some(mac) if mac != [0u8; 6] => format_mac(mac),
+                _ => continue,
+            };
+            if ip_addr.is_multicast() || ip_addr.is_unspecified() {
+                continue;
+            }
+
+            neighbors.push((fields[0].to_string(), mac));
+        }
+
+        Ok(neighbors)
+    }
+
+    /// Run BSD `ping` and read the round trip from its `time=` field
+    fn ping(ip: Ipv4Addr, timeout_ms: u32) -> Result<Option<u32>, String> {
+        let output = Command::new("ping")
+            .arg("-c")
+            .arg("1")
+            .arg("-W")
+            .arg(timeout_ms.to_string())
+            .arg(ip.to_string())
+            .output()
+            .map_err(|e| format!("Failed to run ping: {}", e))?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        let rtt_ms = output_str
+            .split_whitespace()
+            .find_map(|field| field.strip_prefix("time="))
+            .and_then(|time| time.parse::<f64>().ok())
+            .map(|time| time.round() as u32)
+            .unwrap_or(0);
+
+        Ok(Some(rtt_ms))
+    }
+
+    fn native_icmp_available() -> bool {
+        // `ping` above runs the ping command
+        false
+    }
+
+    fn probe_host(runner: &dyn CommandRunner, ip: Ipv4Addr, timeout_ms: u32) {
+        let _ = runner.run("ping", &["-c", "1", "-W", &timeout_ms.to_string(), &ip.to_string()]);
+    }
+
+    /// Kernel-resolved through a UDP datagram, so no command is run
+    fn request_arp(_runner: &dyn CommandRunner, ip: Ipv4Addr) {
+        if let Ok(socket) = UdpSocket::bind("0.0.0.0:0") {
+            let _ = socket.send_to(&[0], SocketAddrV4::new(ip, DISCARD_PORT));
+        }
+    }
+
+    fn set_static_arp(runner: &dyn CommandRunner, interface_name: &str, ip: Ipv4Addr, mac: [u8; 6]) -> Result<(), String> {
+        // -S replaces an existing entry instead of failing on it
+        let output = runner.run("arp", &["-S", &ip.to_string(), &format_mac(mac), "ifscope", interface_name])?;
+
+        if !output.success {
+            return Err(format!("ARP command failed: {}", output.stderr));
+        }
+
+        Ok(())
+    }
+
+    fn delete_arp_entry(interface_name: &str, ip: Ipv4Addr) -> Result<(), String> {
+        let output = Command::new("arp")
+            .arg("-d")
+            .arg(ip.to_string())
+            .arg("ifscope")
+            .arg(interface_name)
+            .output()
+            .map_err(|e| format!("Failed to execute arp command: {}", e))?;
+
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).to_string());
+        }
+
+        Ok(())
+    }
+
+    fn clear_arp_cache(interface_name: Option<&str>) -> Result<(), String> {
+        let mut command = Command::new("arp");
+        command.arg("-a").arg("-d");
+        if let Some(interface_name) = interface_name {
+            command.arg("-i").arg(interface_name);
+        }
+
+        let output = command.output()
+            .map_err(|e| format!("Failed to execute arp command: {}", e))?;
+
+        if !output.status.success() {
+            let error_msg = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("ARP command failed: {}", error_msg));
+        }
+
+        Ok(())
+    }
+
+    fn is_ip_forwarding_enabled() -> Result<bool, String> {
+        let output = Command::new("sysctl")
+            .arg("-n")
+            .arg(IP_FORWARDING_SYSCTL)
+            .output()
+            .map_err(|e| format!("Failed to run sysctl: {}", e))?;
+
+        if !output.status.success() {
+            return Err(format!("Failed to read {}", IP_FORWARDING_SYSCTL));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim() == "1")
+    }
+
+    fn set_ip_forwarding(enabled: bool) -> Result<(), String> {
+        let output = Command::new("sysctl")
+            .arg("-w")
+            .arg(format!("{}={}", IP_FORWARDING_SYSCTL, if enabled { 1 } else { 0 }))
+            .output()
+            .map_err(|e| format!("Failed to run sysctl: {}", e))?;
+
+        if !output.status.success() {
+            let error_msg = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Failed to set {}: {}", IP_FORWARDING_SYSCTL, error_msg));
+        }
+
+        Ok(())
+    }
+
+    /// Load or flush a pf `rdr` rule in an anchor of its own per target
+    fn redirect_dns(interface_name: &str, target_ip: Ipv4Addr, enabled: bool) -> Result<(), String> {
+        let anchor = format!("{}.{}", PF_ANCHOR_PREFIX, target_ip);
+
+        if !enabled {
+            let output = Command::new("pfctl")
+                .arg("-a")
+                .arg(&anchor)
+                .arg("-F")
+                .arg("all")
+                .output()
+                .map_err(|e| format!("Failed to run pfctl: {}", e))?;
+
+            if !output.status.success() {
+                return Err(String::from_utf8_lossy(&output.stderr).to_string());
+            }
+            return Ok(());
+        }
+
+        let rule = format!(
+            "rdr pass on {} inet proto udp from {} to any port 53 -> 127.0.0.1 port 53\n",
+            interface_name, target_ip
+        );
+        let mut child = Command::new("pfctl")
+            .arg("-a")
+            .arg(&anchor)
+            .arg("-f")
+            .arg("-")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run pfctl: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(rule.as_bytes())
+                .map_err(|e| format!("Failed to pass rule to pfctl: {}", e))?;
+        }
+
+        let output = child.wait_with_output()
+            .map_err(|e| format!("Failed to run pfctl: {}", e))?;
+        if !output.status.success() {
+            let error_msg = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("pfctl failed: {}", error_msg));
+        }
+
+        // pf is off by default; enabling it again is harmless
+        let _ = Command::new("pfctl").arg("-e").output();
+
+        Ok(())
+    }
+}
+
+/// Devices of the hardware ports `networksetup -listallhardwareports` calls
+/// Wi-Fi, or AirPort on older releases
+fn wifi_devices(runner: &dyn CommandRunner) -> Result<Vec<String>, String> {
+    let output = runner.run("networksetup", &["-listallhardwareports"])?;
+
+    if !output.success {
+        return Err("networksetup failed to list hardware ports".to_string());
+    }
+
+    let mut devices = Vec::new();
+    let mut is_wifi_port = false;
+
+    for line in output.stdout.lines().map(str::trim) {
+        if let Some(port) = line.strip_prefix("Hardware Port:") {
+            let port = port.trim();
+            is_wifi_port = port == "Wi-Fi" || port == "AirPort";
+        } else if let Some(device) = line.strip_prefix("Device:") {
+            if is_wifi_port {
+                devices.push(device.trim().to_string());
+            }
+        }
+    }
+
+    Ok(devices)
+}
+
+/// Resolved rows of `arp -an` as (ip, mac, is_static). macOS drops leading
+/// zeros from MAC octets (`0:1c:b3:9:85:15`), which `parse_mac` accepts.
+fn read_arp_cache(runner: &dyn CommandRunner) -> Result<Vec<(Ipv4Addr, [u8; 6], bool)>, String> {
+    let output = runner.run("arp", &["-an"])?;
+
+    if !output.success {
+        return Err("ARP command failed".to_string());
+    }
+
+    let mut entries = Vec::new();
+
+    for line in output.stdout.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 4 || fields[2] != "at" {
+            continue;
+        }
+
+        let ip = match fields[1].trim_matches(|c| c == '(' || c == ')').parse::<Ipv4Addr>() {
+            Ok(ip) => ip,
+            Err(_) => continue,
+        };
+        // `(incomplete)` entries have no MAC yet
+        let mac = match parse_mac(fields[3]) {
+            Some(mac) => mac,
+            None => continue,
+        };
+
+        entries.push((ip, mac, fields.contains(&"permanent")));
+    }
+
+    Ok(entries)
+}
+
+/// `struct ifreq` as far as `BIOCSETIF` reads it
+#[repr(C)]
+struct IfReq {
+    name: [libc::c_char; libc::IFNAMSIZ],
+    _union: [u8; 16],
+}
+
+/// Sends Ethernet frames through a BPF device bound to one interface
+pub struct PacketSocket {
+    fd: libc::c_int,
+}
+
+impl PacketSocket {
+    /// Open a free `/dev/bpfN` and bind it to the interface whose hardware address is `mac`
+    pub fn open(mac: [u8; 6]) -> Result<Self, String> {
+        let interface_name = find_interface_name(mac)?;
+        let fd = open_bpf_device()?;
+        let socket = Self { fd };
+
+        let mut request = IfReq { name: [0; libc::IFNAMSIZ], _union: [0; 16] };
+        for (dst, src) in request.name.iter_mut().zip(interface_name.bytes().take(libc::IFNAMSIZ - 1)) {
+            *dst = src as libc::c_char;
+        }
+        if unsafe { libc::ioctl(socket.fd, libc::BIOCSETIF, &request) } < 0 {
+            return Err(format!("Failed to bind BPF device to {}: {}", interface_name, io::Error::last_os_error()));
+        }
+
+        // Keep our own source MAC instead of letting the kernel fill in the interface's
+        let header_complete: libc::c_uint = 1;
+        if unsafe { libc::ioctl(socket.fd, libc::BIOCSHDRCMPLT, &header_complete) } < 0 {
+            return Err(format!("Failed to configure BPF device: {}", io::Error::last_os_error()));
+        }
+
+        Ok(socket)
+    }
+
+    /// Send a complete Ethernet frame
+    pub fn send(&self, frame: &[u8]) -> Result<(), String> {
+        let written = unsafe {
+            libc::write(self.fd, frame.as_ptr() as *const libc::c_void, frame.len())
+        };
+        if written < 0 {
+            return Err(format!("Failed to send frame: {}", io::Error::last_os_error()));
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for PacketSocket {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+/// Open the first BPF device no other process holds
+fn open_bpf_device() -> Result<libc::c_int, String> {
+    for index in 0..MAX_BPF_DEVICES {
+        let path = CString::new(format!("/dev/bpf{}", index)).expect("path contains no NUL");
+        let fd = unsafe { libc::open(path.as_ptr(), libc::O_RDWR) };
+        if fd >= 0 {
+            return Ok(fd);
+        }
+
+        let error = io::Error::last_os_error();
+        if error.raw_os_error() != Some(libc::EBUSY) {
+            return Err(format!("Failed to open /dev/bpf{}: {}", index, error));
+        }
+    }
+
+    Err("No free BPF device available".to_string())
+}
+
+/// Name of the interface whose hardware address is `mac`
+fn find_interface_name(mac: [u8; 6]) -> Result<String, String> {
+    let interfaces = NetworkInterface::show()
+        .map_err(|e| format!("Failed to list interfaces: {}", e))?;
+
+    interfaces
+        .into_iter()
+        .find(|iface| iface.mac_addr.as_deref().and_then(parse_mac) == Some(mac))
+        .map(|iface| iface.name)
+        .ok_or_else(|| format!("No interface found with MAC {}", format_mac(mac)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::MockRunner;
+
+    /// Captured from `arp -an` on macOS 14
+    const ARP_AN: &str = "\
+? (192.168.1.1) at a4:2b:b0:12:34:56 on en0 ifscope [ethernet]
+? (192.168.1.23) at (incomplete) on en0 ifscope [ethernet]
+? (192.168.1.40) at 0:1c:b3:9:85:15 on en0 ifscope permanent [ethernet]
+? (224.0.0.251) at 1:0:5e:0:0:fb on en0 ifscope permanent [ethernet]
+";
+
+    #[test]
+    fn reads_arp_cache() {
+        let runner = MockRunner::default().with_stdout("arp", ARP_AN);
+
+        let entries = read_arp_cache(&runner).unwrap();
+
+        assert_eq!(entries, vec![
+            (Ipv4Addr::new(192, 168, 1, 1), [0xa4, 0x2b, 0xb0, 0x12, 0x34, 0x56], false),
+            (Ipv4Addr::new(192, 168, 1, 40), [0x00, 0x1c, 0xb3, 0x09, 0x85, 0x15], true),
+            (Ipv4Addr::new(224, 0, 0, 251), [0x01, 0x00, 0x5e, 0x00, 0x00, 0xfb], true),
+        ]);
+        assert_eq!(runner.calls(), vec![vec!["arp", "-an"]]);
+    }
+
+    #[test]
+    fn finds_wifi_devices() {
+        let runner = MockRunner::default().with_stdout("networksetup", "\
+Hardware Port: Thunderbolt Ethernet Slot 0
+Device: en1
+Ethernet Address: 36:fa:62:1c:48:00
+
+Hardware Port: Wi-Fi
+Device: en0
+Ethernet Address: a4:83:e7:11:22:33
+
+VLAN Configurations
+===================
+");
+
+        assert_eq!(wifi_devices(&runner).unwrap(), vec!["en0".to_string()]);
+    }
+
+    #[test]
+    fn arp_cache_fails_with_command() {
+        let runner = MockRunner::default().with_failure("arp", "arp: cannot open routing socket");
+
+        assert!(read_arp_cache(&runner).is_err());
+    }
+
+    #[test]
+    fn static_arp_is_scoped_to_interface() {
+        let runner = MockRunner::default();
+
+        MacosPlatform::set_static_arp(&runner, "en0", Ipv4Addr::new(192, 168, 1, 1), [0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff]).unwrap();
+
+        assert_eq!(runner.calls(), vec![vec!["arp", "-S", "192.168.1.1", "aa:bb:cc:dd:ee:ff", "ifscope", "en0"]]);
+    }
+
+    #[test]
+    fn probe_passes_timeout_in_milliseconds() {
+        let runner = MockRunner::default();
+
+        MacosPlatform::probe_host(&runner, Ipv4Addr::new(10, 0, 0, 5), 50);
+
+        assert_eq!(runner.calls(), vec![vec!["ping", "-c", "1", "-W", "50", "10.0.0.5"]]);
+    }
+}
@@ -0,0 +1,106 @@
+use std::net::Ipv4Addr;
+
+use serde::{Deserialize, Serialize};
+
+mod command;
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(windows)]
+mod windows;
+
+#[cfg(target_os = "linux")]
+pub use self::linux::{LinuxPlatform as Current, PacketSocket};
+#[cfg(target_os = "macos")]
+pub use self::macos::{MacosPlatform as Current, PacketSocket};
+#[cfg(windows)]
+pub use self::windows::WindowsPlatform as Current;
+
+pub use self::command::{command_exists, CommandRunner, SystemRunner};
+#[cfg(test)]
+pub use self::command::MockRunner;
+
+/// One row of the system ARP cache
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArpEntry {
+    pub ip: String,
+    pub mac: String,
+    pub is_static: bool,
+}
+
+/// Operating system specific networking, implemented once per supported OS.
+///
+/// `lib.rs` only talks to the OS through this trait, via `Current`. Methods
+/// taking a `CommandRunner` run their external commands through it.
+pub trait NetworkPlatform {
+    /// Shown with permission errors, telling the user how to get enough privileges
+    const ELEVATION_HINT: &'static str;
+
+    /// External programs the implementation shells out to
+    const REQUIRED_COMMANDS: &'static [&'static str];
+
+    /// Whether `redirect_dns` can redirect anything, i.e. DNS-only interception works
+    const DNS_REDIRECT_SUPPORTED: bool;
+
+    /// Whether the process may send raw frames and change system network settings
+    fn is_elevated() -> bool;
+
+    /// Operational status and link speed in Mbps of an adapter
+    fn link_status(interface_name: &str) -> Result<(bool, Option<u64>), String>;
+
+    /// Whether the adapter is a Wi-Fi (802.11) one
+    fn is_wireless(interface_name: &str) -> Result<bool, String>;
+
+    /// Next hops of the IPv4 default routes, of all of them or only of those
+    /// leaving through `interface_name`
+    fn default_routes(interface_name: Option<&str>) -> Result<Vec<Ipv4Addr>, String>;
+
+    /// Every entry of the IPv4 ARP cache, MACs in canonical form
+    fn arp_table() -> Result<Vec<ArpEntry>, String>;
+
+    /// IPv4 neighbors known to the OS, including incomplete and multicast entries
+    fn neighbors(runner: &dyn CommandRunner) -> Result<Vec<(Ipv4Addr, [u8; 6])>, String>;
+
+    /// IPv6 neighbors of one interface as (ip, mac) pairs. Link-local
+    /// addresses carry a scope id so they stay usable outside the interface.
+    fn ipv6_neighbors(interface_name: &str) -> Result<Vec<(String, String)>, String>;
+
+    /// Send one ICMP echo and return the round trip time if it was answered
+    fn ping(ip: Ipv4Addr, timeout_ms: u32) -> Result<Option<u32>, String>;
+
+    /// Whether `ping` sends the echo itself rather than running the ping
+    /// command, which makes it cheap enough to sweep a whole network with
+    fn native_icmp_available() -> bool;
+
+    /// Fire a quick echo at `ip` so it answers ARP, ignoring the outcome
+    fn probe_host(runner: &dyn CommandRunner, ip: Ipv4Addr, timeout_ms: u32);
+
+    /// Nudge the OS into resolving `ip` without waiting for the result
+    fn request_arp(runner: &dyn CommandRunner, ip: Ipv4Addr);
+
+    /// Pin `ip` to `mac` in the local ARP cache
+    fn set_static_arp(runner: &dyn CommandRunner, interface_name: &str, ip: Ipv4Addr, mac: [u8; 6]) -> Result<(), String>;
+
+    /// Drop whatever the local ARP cache holds for `ip`
+    fn delete_arp_entry(interface_name: &str, ip: Ipv4Addr) -> Result<(), String>;
+
+    /// Flush the ARP cache of one interface, or of all of them
+    fn clear_arp_cache(interface_name: Option<&str>) -> Result<(), String>;
+
+    /// Whether the OS routes IPv4 packets between interfaces
+    fn is_ip_forwarding_enabled() -> Result<bool, String>;
+
+    /// Turn IPv4 routing on or off system-wide
+    fn set_ip_forwarding(enabled: bool) -> Result<(), String>;
+
+    /// Add or remove a firewall rule sending `target_ip`'s DNS queries (UDP 53)
+    /// arriving on `interface_name` to a resolver on this machine
+    fn redirect_dns(interface_name: &str, target_ip: Ipv4Addr, enabled: bool) -> Result<(), String>;
+}
+
+/// Format MAC bytes as `aa:bb:cc:dd:ee:ff`
+pub fn format_mac(bytes: [u8; 6]) -> String {
+    format!("{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+        bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5])
+}
@@ -0,0 +1,588 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::process::Command;
+
+use ::windows::core::PCWSTR;
+use ::windows::Win32::NetworkManagement::IpHelper::{
+    ConvertInterfaceAliasToLuid, GetIfEntry2, GetIpForwardTable, GetIpNetTable, IcmpCloseHandle,
+    IcmpCreateFile, IcmpSendEcho, ICMP_ECHO_REPLY, MIB_IF_ROW2,
+    MIB_IPFORWARDROW, MIB_IPFORWARDTABLE, MIB_IPNETROW_LH, MIB_IPNETTABLE, MIB_IPNET_TYPE_DYNAMIC, MIB_IPNET_TYPE_STATIC,
+    MIB_IPNET_TYPE_OTHER
+};
+use ::windows::Win32::NetworkManagement::Ndis::{IfOperStatusUp, NET_LUID_LH};
+use ::windows::Win32::Foundation::{CloseHandle, ERROR_INSUFFICIENT_BUFFER, ERROR_NO_DATA, HANDLE, NO_ERROR};
+use ::windows::Win32::Security::{GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY};
+use ::windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+use super::{format_mac, ArpEntry, CommandRunner, NetworkPlatform};
+use crate::arp_packet::parse_mac;
+
+const TCPIP_PARAMETERS_KEY: &str = r"HKLM\SYSTEM\CurrentControlSet\Services\Tcpip\Parameters";
+
+/// `IF_TYPE_IEEE80211` from ipifcons.h
+const IF_TYPE_IEEE80211: u32 = 71;
+
+/// Read the `GetIfEntry2` row of the adapter with the given alias
+fn if_entry(interface_name: &str) -> Result<MIB_IF_ROW2, String> {
+    let alias: Vec<u16> = interface_name.encode_utf16().chain(std::iter::once(0)).collect();
+
+    unsafe {
+        let mut luid = NET_LUID_LH::default();
+        let result = ConvertInterfaceAliasToLuid(PCWSTR(alias.as_ptr()), &mut luid);
+        if result != NO_ERROR {
+            return Err(format!("Failed to find interface LUID: {}", result.0));
+        }
+
+        let mut row = MIB_IF_ROW2 {
+            InterfaceLuid: luid,
+            ..Default::default()
+        };
+        let result = GetIfEntry2(&mut row);
+        if result != NO_ERROR {
+            return Err(format!("Failed to read interface entry: {}", result.0));
+        }
+
+        Ok(row)
+    }
+}
+
+/// IP Helper API, netsh and the arp/reg/net commands
+pub struct WindowsPlatform;
+
+impl NetworkPlatform for WindowsPlatform {
+    const ELEVATION_HINT: &'static str = "Restart KanCut using \"Run as administrator\"";
+
+    const REQUIRED_COMMANDS: &'static [&'static str] = &["arp", "netsh", "ping", "reg", "net"];
+
+    const DNS_REDIRECT_SUPPORTED: bool = false;
+
+    /// Check whether the current process token is elevated (run as administrator)
+    fn is_elevated() -> bool {
+        unsafe {
+            let mut token = HANDLE::default();
+            if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token).is_err() {
+                return false;
+            }
+
+            let mut elevation = TOKEN_ELEVATION::default();
+            let mut returned_size = 0u32;
+            let result = GetTokenInformation(
+                token,
+                TokenElevation,
+                Some(&mut elevation as *mut TOKEN_ELEVATION as *mut std::ffi::c_void),
+                std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+                &mut returned_size,
+            );
+            let _ = CloseHandle(token);
+
+            result.is_ok() && elevation.TokenIsElevated != 0
+        }
+    }
+
+    /// Read operational status and link speed of an adapter via its LUID
+    fn link_status(interface_name: &str) -> Result<(bool, Option<u64>), String> {
+        let row = if_entry(interface_name)?;
+
+        let is_up = row.OperStatus == IfOperStatusUp;
+        // Speeds are reported in bits per second; u64::MAX means unknown
+        let speed = row.ReceiveLinkSpeed.max(row.TransmitLinkSpeed);
+        let speed_mbps = if is_up && speed > 0 && speed != u64::MAX {
+            Some(speed / 1_000_000)
+        } else {
+            None
+        };
+
+        Ok((is_up, speed_mbps))
+    }
+
+    /// Check the interface type reported by `GetIfEntry2`
+    fn is_wireless(interface_name: &str) -> Result<bool, String> {
+        Ok(if_entry(interface_name)?.Type == IF_TYPE_IEEE80211)
+    }
+
+    /// Read the next hops of the 0.0.0.0/0 routes from the IPv4 routing table,
+    /// matching the interface by its `dwForwardIfIndex`
+    fn default_routes(interface_name: Option<&str>) -> Result<Vec<Ipv4Addr>, String> {
+        let mut gateways = Vec::new();
+        let if_index = match interface_name {
+            Some(name) => Some(if_entry(name)?.InterfaceIndex),
+            None => None,
+        };
+
+        unsafe {
+            let mut buffer_size = 0u32;
+
+            // First call to get the required buffer size
+            let result = GetIpForwardTable(None, &mut buffer_size, false);
+
+            // An empty routing table is reported as success or as ERROR_NO_DATA
+            if result == NO_ERROR.0 || result == ERROR_NO_DATA.0 {
+                return Ok(gateways);
+            }
+            if result != ERROR_INSUFFICIENT_BUFFER.0 {
+                return Err(format!("Failed to get routing table size: {}", result));
+            }
+
+            // Allocate buffer, as u32s so the table is properly aligned
+            let mut buffer = vec![0u32; (buffer_size as usize).div_ceil(4)];
+            let table_ptr = buffer.as_mut_ptr() as *mut MIB_IPFORWARDTABLE;
+
+            let result = GetIpForwardTable(Some(table_ptr), &mut buffer_size, false);
+
+            if result != NO_ERROR.0 {
+                return Err(format!("Failed to get routing table: {}", result));
+            }
+
+            // `table` is declared with one row; the rest follow it in the buffer.
+            // Never read more rows than the buffer actually holds.
+            let rows_ptr = std::ptr::addr_of!((*table_ptr).table) as *const MIB_IPFORWARDROW;
+            let rows_offset = rows_ptr as usize - table_ptr as usize;
+            let rows_capacity = (buffer.len() * 4).saturating_sub(rows_offset)
+                / std::mem::size_of::<MIB_IPFORWARDROW>();
+            let num_entries = ((*table_ptr).dwNumEntries as usize).min(rows_capacity);
+            let rows = std::slice::from_raw_parts(rows_ptr, num_entries);
+
+            for row in rows {
+                if if_index.is_some_and(|index| row.dwForwardIfIndex != index) {
+                    continue;
+                }
+                if row.dwForwardDest == 0 && row.dwForwardMask == 0 {
+                    gateways.push(Ipv4Addr::from(u32::from_be(row.dwForwardNextHop)));
+                }
+            }
+        }
+
+        Ok(gateways)
+    }
+
+    fn arp_table() -> Result<Vec<ArpEntry>, String> {
+        let mut entries = Vec::new();
+
+        unsafe {
+            let mut buffer_size = 0u32;
+
+            // First call to get the required buffer size
+            let result = GetIpNetTable(None, &mut buffer_size, false);
+
+            // An empty cache (e.g. right after boot) is reported as success or as
+            // ERROR_NO_DATA instead of asking for a buffer
+            if result == NO_ERROR.0 || result == ERROR_NO_DATA.0 {
+                return Ok(entries);
+            }
+            if result != ERROR_INSUFFICIENT_BUFFER.0 {
+                return Err(format!("Failed to get ARP table size: {}", result));
+            }
+
+            // Allocate buffer, as u32s so the table is properly aligned
+            let mut buffer = vec![0u32; (buffer_size as usize).div_ceil(4)];
+            let table_ptr = buffer.as_mut_ptr() as *mut MIB_IPNETTABLE;
+
+            // Second call to get the actual data
+            let result = GetIpNetTable(Some(table_ptr), &mut buffer_size, false);
+
+            if result != NO_ERROR.0 {
+                return Err(format!("Failed to get ARP table: {}", result));
+            }
+
+            // `table` is declared with one row; the rest follow it in the buffer.
+            // Never read more rows than the buffer actually holds.
+            let rows_ptr = std::ptr::addr_of!((*table_ptr).table) as *const MIB_IPNETROW_LH;
+            let rows_offset = rows_ptr as usize - table_ptr as usize;
+            let rows_capacity = (buffer.len() * 4).saturating_sub(rows_offset)
+                / std::mem::size_of::<MIB_IPNETROW_LH>();
+            let num_entries = ((*table_ptr).dwNumEntries as usize).min(rows_capacity);
+            let rows = std::slice::from_raw_parts(rows_ptr, num_entries);
+
+            // Process each ARP entry
+            for entry in rows {
+
+                // Convert IP address
+                let ip_addr = Ipv4Addr::from(u32::from_be(entry.dwAddr));
+
+                // Convert MAC address
+                let mac_len = (entry.dwPhysAddrLen as usize).min(entry.bPhysAddr.len());
+                let mac_bytes = &entry.bPhysAddr[..mac_len];
+                let mac_str = match <[u8; 6]>::try_from(mac_bytes) {
+                    Ok(bytes) => format_mac(bytes),
+                    Err(_) => "00:00:00:00:00:00".to_string(),
+                };
+
+                // Only include dynamic and static entries (skip invalid ones)
+                if entry.Anonymous.Type == MIB_IPNET_TYPE_DYNAMIC ||
+                   entry.Anonymous.Type == MIB_IPNET_TYPE_STATIC ||
+                   entry.Anonymous.Type == MIB_IPNET_TYPE_OTHER {
+                    entries.push(ArpEntry {
+                        ip: ip_addr.to_string(),
+                        mac: mac_str,
+                        is_static: entry.Anonymous.Type == MIB_IPNET_TYPE_STATIC,
+                    });
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn neighbors(runner: &dyn CommandRunner) -> Result<Vec<(Ipv4Addr, [u8; 6])>, String> {
+        // Use netsh to get neighbor cache
+        let output = runner.run("netsh", &["interface", "ipv4", "show", "neighbors"])?;
+
+        if !output.success {
+            return Ok(Vec::new());
+        }
+
+        Ok(parse_netsh_neighbors(&output.stdout))
+    }
+
+    /// Read the IPv6 neighbor cache for one interface via netsh; link-local
+    /// addresses get the interface index as scope id (`fe80::1%12`)
+    fn ipv6_neighbors(interface_name: &str) -> Result<Vec<(String, String)>, String> {
+        let mut neighbors = Vec::new();
+
+        let output = Command::new("netsh")
+            .arg("interface")
+            .arg("ipv6")
+            .arg("show")
+            .arg("neighbors")
+            .output()
+            .map_err(|e| format!("Failed to run netsh: {}", e))?;
+
+        if !output.status.success() {
+            return Ok(neighbors);
+        }
+
+        let output_str = String::from_utf8_lossy(&output.stdout);
+
+        // Output is grouped by "Interface <index>: <name>" headers
+        let mut scope_id: Option<u32> = None;
+
+        for line in output_str.lines() {
+            if let Some((index, name)) = parse_netsh_interface_header(line) {
+                scope_id = if name == interface_name { Some(index) } else { None };
+                continue;
+            }
+
+            let scope_id = match scope_id {
+                Some(scope_id) => scope_id,
+                None => continue,
+            };
+
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 2 {
+                continue;
+            }
+
+            let ip_addr = match parts[0].parse::<Ipv6Addr>() {
+                Ok(ip_addr) => ip_addr,
+                Err(_) => continue,
+            };
+
+            let mac = match parse_mac(parts[1]) {
+                Some(mac) => format_mac(mac),
+                None => continue,
+            };
+            if ip_addr.is_multicast() || ip_addr.is_unspecified() || mac == "00:00:00:00:00:00" {
+                continue;
+            }
+
+            // fe80::/10 is only meaningful together with the interface scope
+            let ip = if (ip_addr.segments()[0] & 0xffc0) == 0xfe80 {
+                format!("{}%{}", ip_addr, scope_id)
+            } else {
+                ip_addr.to_string()
+            };
+
+            neighbors.push((ip, mac));
+        }
+
+        Ok(neighbors)
+    }
+
+    fn ping(ip: Ipv4Addr, timeout_ms: u32) -> Result<Option<u32>, String> {
+        let request_data = *b"KanCutPing";
+        // Room for one reply plus the echoed payload and an ICMP error header
+        let mut reply_buffer = vec![0u8; std::mem::size_of::<ICMP_ECHO_REPLY>() + request_data.len() + 8];
+
+        unsafe {
+            let handle = IcmpCreateFile()
+                .map_err(|e| format!("Failed to open ICMP handle: {}", e))?;
+
+            let replies = IcmpSendEcho(
+                handle,
+                u32::from_ne_bytes(ip.octets()),
+                request_data.as_ptr() as *const _,
+                request_data.len() as u16,
+                None,
+                reply_buffer.as_mut_ptr() as *mut _,
+                reply_buffer.len() as u32,
+                timeout_ms,
+            );
+
+            let _ = IcmpCloseHandle(handle);
+
+            if replies == 0 {
+                return Ok(None);
+            }
+
+            let reply = &*(reply_buffer.as_ptr() as *const ICMP_ECHO_REPLY);
+            // Status 0 is IP_SUCCESS; anything else is e.g. an unreachable or TTL error
+            if reply.Status == 0 {
+                Ok(Some(reply.RoundTripTime))
+            } else {
+                Ok(None)
+            }
+        }
+    }
+
+    fn native_icmp_available() -> bool {
+        unsafe {
+            match IcmpCreateFile() {
+                Ok(handle) => {
+                    let _ = IcmpCloseHandle(handle);
+                    true
+                }
+                Err(_) => false,
+            }
+        }
+    }
+
+    fn probe_host(runner: &dyn CommandRunner, ip: Ipv4Addr, timeout_ms: u32) {
+        let _ = runner.run("ping", &["-n", "1", "-w", &timeout_ms.to_string(), &ip.to_string()]);
+    }
+
+    fn request_arp(runner: &dyn CommandRunner, ip: Ipv4Addr) {
+        let _ = runner.run("arp", &["-a", &ip.to_string()]);
+    }
+
+    fn set_static_arp(runner: &dyn CommandRunner, _interface_name: &str, ip: Ipv4Addr, mac: [u8; 6]) -> Result<(), String> {
+        // arp -s expects the dash-separated form
+        let mac = format_mac(mac).replace(':', "-");
+        let ip = ip.to_string();
+
+        // First, delete any existing ARP entry
+        let _ = runner.run("arp", &["-d", &ip]);
+
+        let output = runner.run("arp", &["-s", &ip, &mac])?;
+
+        if !output.success {
+            // arp reports some failures, e.g. access denied, on stdout
+            let error_msg = if output.stderr.trim().is_empty() { &output.stdout } else { &output.stderr };
+            return Err(format!("ARP command failed: {}", error_msg.trim()));
+        }
+
+        Ok(())
+    }
+
+    fn delete_arp_entry(_interface_name: &str, ip: Ipv4Addr) -> Result<(), String> {
+        let output = Command::new("arp")
+            .arg("-d")
+            .arg(ip.to_string())
+            .output()
+            .map_err(|e| format!("Failed to execute arp command: {}", e))?;
+
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Flush the ARP cache of one interface, or of all of them, through netsh
+    fn clear_arp_cache(interface_name: Option<&str>) -> Result<(), String> {
+        let mut command = Command::new("netsh");
+        command.arg("interface").arg("ip").arg("delete").arg("arpcache");
+        if let Some(interface_name) = interface_name {
+            command.arg(format!("name={}", interface_name));
+        }
+
+        let output = command.output()
+            .map_err(|e| format!("Failed to execute netsh command: {}", e))?;
+
+        if !output.status.success() {
+            // netsh reports its errors on stdout
+            let error_msg = String::from_utf8_lossy(&output.stdout);
+            return Err(format!("netsh failed: {}", error_msg.trim()));
+        }
+
+        Ok(())
+    }
+
+    /// Check whether IPv4 routing is enabled system-wide (`IPEnableRouter`)
+    fn is_ip_forwarding_enabled() -> Result<bool, String> {
+        let output = Command::new("reg")
+            .arg("query")
+            .arg(TCPIP_PARAMETERS_KEY)
+            .arg("/v")
+            .arg("IPEnableRouter")
+            .output()
+            .map_err(|e| format!("Failed to execute reg command: {}", e))?;
+
+        // A missing value means routing was never enabled
+        if !output.status.success() {
+            return Ok(false);
+        }
+
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        Ok(output_str.lines()
+            .filter(|line| line.contains("IPEnableRouter"))
+            .any(|line| line.trim_end().ends_with("0x1")))
+    }
+
+    /// Set `IPEnableRouter` and restart the routing service so the change
+    /// takes effect without a reboot
+    fn set_ip_forwarding(enabled: bool) -> Result<(), String> {
+        let output = Command::new("reg")
+            .arg("add")
+            .arg(TCPIP_PARAMETERS_KEY)
+            .arg("/v")
+            .arg("IPEnableRouter")
+            .arg("/t")
+            .arg("REG_DWORD")
+            .arg("/d")
+            .arg(if enabled { "1" } else { "0" })
+            .arg("/f")
+            .output()
+            .map_err(|e| format!("Failed to execute reg command: {}", e))?;
+
+        if !output.status.success() {
+            let error_msg = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Failed to set IPEnableRouter: {}", error_msg));
+        }
+
+        let _ = Command::new("net")
+            .arg("stop")
+            .arg("RemoteAccess")
+            .output();
+
+        if enabled {
+            let output = Command::new("net")
+                .arg("start")
+                .arg("RemoteAccess")
+                .output()
+                .map_err(|e| format!("Failed to execute net command: {}", e))?;
+
+            if !output.status.success() {
+                let error_msg = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("Failed to start RemoteAccess service: {}", error_msg));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `netsh interface portproxy` only handles TCP, and nothing else built
+    /// into Windows redirects UDP, so there is never a rule to remove either
+    fn redirect_dns(_interface_name: &str, _target_ip: Ipv4Addr, enabled: bool) -> Result<(), String> {
+        if enabled {
+            return Err("Redirecting DNS is not supported on Windows".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Parse a netsh section header such as `Interface 12: Ethernet`
+fn parse_netsh_interface_header(line: &str) -> Option<(u32, &str)> {
+    let rest = line.trim().strip_prefix("Interface ")?;
+    let (index, name) = rest.split_once(':')?;
+    let index = index.trim().parse().ok()?;
+    Some((index, name.trim()))
+}
+
+/// IPv4 neighbors listed by `netsh interface ipv4 show neighbors`
+fn parse_netsh_neighbors(output: &str) -> Vec<(Ipv4Addr, [u8; 6])> {
+    // State names are localized, so recognize entries by their columns instead:
+    // an IPv4 address followed by a MAC
+    output.lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let ip_addr = parts.next()?.parse::<Ipv4Addr>().ok()?;
+            Some((ip_addr, parse_mac(parts.next()?)?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::MockRunner;
+
+    /// Captured from `netsh interface ipv4 show neighbors` on Windows 11
+    const NETSH_NEIGHBORS: &str = "
+Interface 1: Loopback Pseudo-Interface 1
+
+
+Internet Address                              Physical Address   Type
+--------------------------------------------  -----------------  -----------
+224.0.0.22                                                       Permanent
+239.255.255.250                                                  Permanent
+
+Interface 12: Ethernet
+
+
+Internet Address                              Physical Address   Type
+--------------------------------------------  -----------------  -----------
+192.168.1.1                                   a4-2b-b0-12-34-56  Reachable
+192.168.1.23                                  00-00-00-00-00-00  Unreachable
+192.168.1.255                                 ff-ff-ff-ff-ff-ff  Permanent
+";
+
+    #[test]
+    fn parses_netsh_neighbors() {
+        let neighbors = parse_netsh_neighbors(NETSH_NEIGHBORS);
+
+        assert_eq!(neighbors, vec![
+            (Ipv4Addr::new(192, 168, 1, 1), [0xa4, 0x2b, 0xb0, 0x12, 0x34, 0x56]),
+            (Ipv4Addr::new(192, 168, 1, 23), [0; 6]),
+            (Ipv4Addr::new(192, 168, 1, 255), [0xff; 6]),
+        ]);
+    }
+
+    #[test]
+    fn parses_netsh_interface_header() {
+        assert_eq!(parse_netsh_interface_header("Interface 12: Ethernet"), Some((12, "Ethernet")));
+        assert_eq!(parse_netsh_interface_header("192.168.1.1  a4-2b-b0-12-34-56  Reachable"), None);
+    }
+
+    #[test]
+    fn neighbors_runs_netsh() {
+        let runner = MockRunner::default().with_stdout("netsh", NETSH_NEIGHBORS);
+
+        let neighbors = WindowsPlatform::neighbors(&runner).unwrap();
+
+        assert_eq!(neighbors.len(), 3);
+        assert_eq!(runner.calls(), vec![vec!["netsh", "interface", "ipv4", "show", "neighbors"]]);
+    }
+
+    #[test]
+    fn static_arp_replaces_existing_entry() {
+        let runner = MockRunner::default();
+
+        WindowsPlatform::set_static_arp(&runner, "Ethernet", Ipv4Addr::new(192, 168, 1, 1), [0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff]).unwrap();
+
+        assert_eq!(runner.calls(), vec![
+            vec!["arp", "-d", "192.168.1.1"],
+            vec!["arp", "-s", "192.168.1.1", "aa-bb-cc-dd-ee-ff"],
+        ]);
+    }
+
+    #[test]
+    fn static_arp_reports_command_failure() {
+        let runner = MockRunner::default().with_failure("arp", "The ARP entry addition failed: Access is denied.");
+
+        let result = WindowsPlatform::set_static_arp(&runner, "Ethernet", Ipv4Addr::new(192, 168, 1, 1), [0xaa; 6]);
+
+        assert!(result.unwrap_err().contains("Access is denied"));
+    }
+
+    #[test]
+    fn probes_with_single_echo() {
+        let runner = MockRunner::default();
+
+        WindowsPlatform::probe_host(&runner, Ipv4Addr::new(10, 0, 0, 5), 50);
+        WindowsPlatform::request_arp(&runner, Ipv4Addr::new(10, 0, 0, 5));
+
+        assert_eq!(runner.calls(), vec![
+            vec!["ping", "-n", "1", "-w", "50", "10.0.0.5"],
+            vec!["arp", "-a", "10.0.0.5"],
+        ]);
+    }
+}
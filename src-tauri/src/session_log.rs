@@ -0,0 +1,51 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use chrono::Local;
+
+/// Directory of the main `kancut.log` and of the session logs
+pub static LOG_DIR: &str = "logs";
+
+/// Path of a session's own log, e.g. `logs/session-<id>.log`
+pub fn path(session_id: &str) -> PathBuf {
+    PathBuf::from(LOG_DIR).join(format!("session-{}.log", session_id))
+}
+
+/// Whether a file name in `LOG_DIR` is one written by `SessionLog`
+pub fn is_session_log(file_name: &str) -> bool {
+    file_name.starts_with("session-") && file_name.ends_with(".log")
+}
+
+/// Log of a single spoofing session: its sends, errors and lifecycle
+pub struct SessionLog {
+    file: File,
+}
+
+impl SessionLog {
+    /// Open the session's log for appending, creating the log directory if needed
+    pub fn open(session_id: &str) -> Result<Self, String> {
+        let path = path(session_id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create log directory: {}", e))?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+
+        Ok(SessionLog { file })
+    }
+
+    /// Append a timestamped line. A full disk should not end the session, so
+    /// failures are only reported.
+    pub fn write(&mut self, message: &str) {
+        let line = format!("{} {}\n", Local::now().format("%Y-%m-%d %H:%M:%S%.3f"), message);
+        if let Err(e) = self.file.write_all(line.as_bytes()) {
+            eprintln!("Failed to write session log: {}", e);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+static CONFIG_DIR: &str = "config";
+
+/// Path of a file inside the application config directory
+pub fn config_path(file_name: &str) -> PathBuf {
+    PathBuf::from(CONFIG_DIR).join(file_name)
+}
+
+/// Load a JSON document from the config directory, `None` if it doesn't exist yet
+pub fn load_json<T: DeserializeOwned>(file_name: &str) -> Result<Option<T>, String> {
+    let path = config_path(file_name);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let value = serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+    Ok(Some(value))
+}
+
+/// Save a JSON document to the config directory.
+///
+/// The file is written next to its final location and renamed into place so
+/// a crash mid-write never leaves a truncated document behind.
+pub fn save_json<T: Serialize>(file_name: &str, value: &T) -> Result<(), String> {
+    let path = config_path(file_name);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+
+    let contents = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", file_name, e))?;
+
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, contents)
+        .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+    fs::rename(&tmp_path, &path)
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))?;
+
+    Ok(())
+}
+
+/// Delete a file from the config directory if present
+pub fn remove(file_name: &str) -> Result<(), String> {
+    let path = config_path(file_name);
+    if path.exists() {
+        fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::pcap::PcapWriter;
+
+const ETHERTYPE_IPV4: [u8; 2] = [0x08, 0x00];
+/// Ethernet header plus the fixed part of an IPv4 header
+const MIN_IPV4_FRAME_LEN: usize = 34;
+
+/// Byte totals for traffic relayed on behalf of one target
+#[derive(Debug, Default)]
+pub struct TrafficCounters {
+    /// Bytes sent by the target
+    pub bytes_up: AtomicU64,
+    /// Bytes addressed to the target
+    pub bytes_down: AtomicU64,
+}
+
+impl TrafficCounters {
+    pub fn totals(&self) -> (u64, u64) {
+        (self.bytes_up.load(Ordering::Relaxed), self.bytes_down.load(Ordering::Relaxed))
+    }
+
+    /// Attribute a captured Ethernet frame to the target, returning whether it belongs to it.
+    ///
+    /// Only frames addressed to our own MAC are counted: every relayed packet
+    /// shows up twice on the wire, once coming in and once going back out.
+    #[cfg_attr(not(feature = "raw-packets"), allow(dead_code))]
+    pub fn record_frame(&self, frame: &[u8], local_mac: [u8; 6], target_ip: Ipv4Addr) -> bool {
+        let (source, destination) = match relayed_addresses(frame, local_mac) {
+            Some(addresses) => addresses,
+            None => return false,
+        };
+        let len = frame.len() as u64;
+
+        if source == target_ip {
+            self.bytes_up.fetch_add(len, Ordering::Relaxed);
+        } else if destination == target_ip {
+            self.bytes_down.fetch_add(len, Ordering::Relaxed);
+        } else {
+            return false;
+        }
+        true
+    }
+}
+
+/// Source and destination of an IPv4 frame addressed to our own MAC
+#[cfg_attr(not(feature = "raw-packets"), allow(dead_code))]
+fn relayed_addresses(frame: &[u8], local_mac: [u8; 6]) -> Option<(Ipv4Addr, Ipv4Addr)> {
+    if frame.len() < MIN_IPV4_FRAME_LEN || frame[0..6] != local_mac || frame[12..14] != ETHERTYPE_IPV4 {
+        return None;
+    }
+
+    let source = Ipv4Addr::new(frame[26], frame[27], frame[28], frame[29]);
+    let destination = Ipv4Addr::new(frame[30], frame[31], frame[32], frame[33]);
+    Some((source, destination))
+}
+
+/// One session counted by `monitor_targets`
+#[cfg_attr(not(feature = "raw-packets"), allow(dead_code))]
+pub struct MonitoredTarget {
+    pub counters: Arc<TrafficCounters>,
+    pub stop_flag: Arc<Mutex<bool>>,
+}
+
+impl MonitoredTarget {
+    #[cfg_attr(not(feature = "raw-packets"), allow(dead_code))]
+    fn is_stopped(&self) -> bool {
+        self.stop_flag.lock().map(|stop| *stop).unwrap_or(true)
+    }
+}
+
+/// Capture frames on the interface with `local_mac` until `stop_flag` is set,
+/// counting the target's relayed traffic into `counters` and saving it to
+/// `capture` if given. The capture file is closed before returning.
+#[cfg(feature = "raw-packets")]
+pub fn monitor_target(
+    local_mac: &str,
+    target_ip: Ipv4Addr,
+    counters: Arc<TrafficCounters>,
+    mut capture: Option<PcapWriter>,
+    stop_flag: Arc<Mutex<bool>>,
+) -> Result<(), String> {
+    let (mut rx, mac_bytes, interface_name) = open_capture_device(local_mac)?;
+
+    let result = loop {
+        if stop_flag.lock().map(|stop| *stop).unwrap_or(true) {
+            break Ok(());
+        }
+
+        match rx.next() {
+            Ok(frame) => {
+                if !counters.record_frame(frame, mac_bytes, target_ip) {
+                    continue;
+                }
+                let saved = match capture.as_mut() {
+                    Some(writer) => writer.write_frame(frame),
+                    None => continue,
+                };
+                match saved {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        eprintln!("Capture file for {} reached its size limit, no longer saving packets", target_ip);
+                        close_capture(capture.take());
+                    }
+                    Err(e) => {
+                        eprintln!("Stopped saving packets of {}: {}", target_ip, e);
+                        close_capture(capture.take());
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {}
+            Err(e) => break Err(format!("Capture on {} failed: {}", interface_name, e)),
+        }
+    };
+
+    close_capture(capture);
+    result
+}
+
+/// How often `monitor_targets` drops stopped sessions
+#[cfg(feature = "raw-packets")]
+const STOP_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Count the relayed traffic of every session in `targets` from a single
+/// capture on the interface with `local_mac`, crediting each frame to the
+/// target it came from or went to. Returns once every session is stopped.
+#[cfg(feature = "raw-packets")]
+pub fn monitor_targets(local_mac: &str, mut targets: HashMap<Ipv4Addr, MonitoredTarget>) -> Result<(), String> {
+    use std::time::Instant;
+
+    let (mut rx, mac_bytes, interface_name) = open_capture_device(local_mac)?;
+    let mut last_stop_check = Instant::now();
+
+    loop {
+        // Checking every session's flag per frame would cost more than the counting
+        if last_stop_check.elapsed() >= STOP_CHECK_INTERVAL {
+            last_stop_check = Instant::now();
+            targets.retain(|_, target| !target.is_stopped());
+            if targets.is_empty() {
+                return Ok(());
+            }
+        }
+
+        match rx.next() {
+            Ok(frame) => {
+                let (source, destination) = match relayed_addresses(frame, mac_bytes) {
+                    Some(addresses) => addresses,
+                    None => continue,
+                };
+                let len = frame.len() as u64;
+                if let Some(target) = targets.get(&source) {
+                    target.counters.bytes_up.fetch_add(len, Ordering::Relaxed);
+                } else if let Some(target) = targets.get(&destination) {
+                    target.counters.bytes_down.fetch_add(len, Ordering::Relaxed);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {}
+            Err(e) => return Err(format!("Capture on {} failed: {}", interface_name, e)),
+        }
+    }
+}
+
+/// Open a receive channel on the interface with `local_mac`, returning it
+/// with the parsed MAC and the interface's name
+#[cfg(feature = "raw-packets")]
+fn open_capture_device(
+    local_mac: &str,
+) -> Result<(Box<dyn pnet::datalink::DataLinkReceiver>, [u8; 6], String), String> {
+    use std::time::Duration;
+    use pnet::datalink::{self, Channel, Config};
+
+    let mac_bytes = crate::arp_packet::parse_mac(local_mac)
+        .ok_or_else(|| format!("Invalid interface MAC: {}", local_mac))?;
+
+    let interface = datalink::interfaces()
+        .into_iter()
+        .find(|iface| iface.mac.map(|mac| mac.octets() == mac_bytes).unwrap_or(false))
+        .ok_or_else(|| format!("No capture device found for MAC {}", local_mac))?;
+
+    // Time out reads so the stop flags are checked regularly on a quiet network
+    let config = Config {
+        read_timeout: Some(Duration::from_millis(500)),
+        ..Default::default()
+    };
+
+    match datalink::channel(&interface, config) {
+        Ok(Channel::Ethernet(_tx, rx)) => Ok((rx, mac_bytes, interface.name)),
+        Ok(_) => Err(format!("Unsupported channel type on {}", interface.name)),
+        Err(e) => Err(format!("Failed to open capture device {}: {}", interface.name, e)),
+    }
+}
+
+#[cfg(feature = "raw-packets")]
+fn close_capture(capture: Option<PcapWriter>) {
+    if let Some(writer) = capture {
+        if let Err(e) = writer.finish() {
+            eprintln!("{}", e);
+        }
+    }
+}
+
+/// Placeholder used when the crate is built without raw packet support
+#[cfg(not(feature = "raw-packets"))]
+pub fn monitor_target(
+    _local_mac: &str,
+    _target_ip: Ipv4Addr,
+    _counters: std::sync::Arc<TrafficCounters>,
+    _capture: Option<PcapWriter>,
+    _stop_flag: std::sync::Arc<std::sync::Mutex<bool>>,
+) -> Result<(), String> {
+    Err("Traffic monitoring requires the `raw-packets` feature".to_string())
+}
+
+/// Placeholder used when the crate is built without raw packet support
+#[cfg(not(feature = "raw-packets"))]
+pub fn monitor_targets(_local_mac: &str, _targets: HashMap<Ipv4Addr, MonitoredTarget>) -> Result<(), String> {
+    Err("Traffic monitoring requires the `raw-packets` feature".to_string())
+}
@@ -0,0 +1,29 @@
+use std::net::{Ipv4Addr, SocketAddrV4, UdpSocket};
+
+/// Discard port, the usual destination for magic packets
+const WOL_PORT: u16 = 9;
+
+/// Length of a magic packet: 6 bytes of 0xFF followed by the MAC 16 times
+const MAGIC_PACKET_LEN: usize = 6 + 16 * 6;
+
+/// Build the magic packet that wakes the adapter with hardware address `mac`
+fn build_magic_packet(mac: [u8; 6]) -> [u8; MAGIC_PACKET_LEN] {
+    let mut packet = [0xffu8; MAGIC_PACKET_LEN];
+    for repetition in packet[6..].chunks_exact_mut(6) {
+        repetition.copy_from_slice(&mac);
+    }
+    packet
+}
+
+/// Broadcast a magic packet for `mac` to `broadcast` on the discard port
+pub fn send_magic_packet(mac: [u8; 6], broadcast: Ipv4Addr) -> Result<(), String> {
+    let socket = UdpSocket::bind("0.0.0.0:0")
+        .map_err(|e| format!("Failed to open UDP socket: {}", e))?;
+    socket.set_broadcast(true)
+        .map_err(|e| format!("Failed to enable broadcast: {}", e))?;
+
+    socket.send_to(&build_magic_packet(mac), SocketAddrV4::new(broadcast, WOL_PORT))
+        .map_err(|e| format!("Failed to send magic packet to {}: {}", broadcast, e))?;
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
 
 // Types
 interface NetworkInterface {
@@ -6,6 +7,7 @@ interface NetworkInterface {
   description: string;
   mac: string;
   ips: string[];
+  ipv6s?: string[];
 }
 
 interface NetworkDevice {
@@ -20,15 +22,24 @@ interface SpoofingSession {
   target_ip: string;
   gateway_ip: string;
   interface: string;
+  target_mac?: string | null;
+  gateway_mac?: string | null;
   is_active: boolean;
   packets_sent: number;
+  method?: "RawFrames" | "StaticArp";
+}
+
+interface SpoofAllResult {
+  session_ids: string[];
+  skipped: { ip: string; mac: string; reason: string }[];
+  failed: { ip: string; mac: string; error: { code: string; message: string; details?: string | null } }[];
 }
 
 // Global state
 let selectedInterface: NetworkInterface | null = null;
 let selectedDevice: NetworkDevice | null = null;
 let activeSessions: SpoofingSession[] = [];
-let sessionUpdateInterval: number | null = null;
+let sessionUnlisteners: UnlistenFn[] = [];
 
 // DOM elements
 let disclaimerModal: HTMLElement;
@@ -160,6 +171,11 @@ function displayInterfaces(interfaces: NetworkInterface[]) {
           <span class="interface-detail-label">IP Addresses:</span>
           <span class="interface-detail-value">${iface.ips.join(", ") || "N/A"}</span>
         </div>
+        ${iface.ipv6s?.length ? `
+        <div class="interface-detail">
+          <span class="interface-detail-label">IPv6 Addresses:</span>
+          <span class="interface-detail-value">${iface.ipv6s.join(", ")}</span>
+        </div>` : ""}
       </div>
     `;
     
@@ -209,13 +225,14 @@ async function scanNetwork() {
     updateStatus("loading", "Scanning network...");
     
     console.log("Calling scan_network with:", { interfaceName: selectedInterface.name });
-    const devices: NetworkDevice[] = await invoke("scan_network", {
-      interfaceName: selectedInterface.name
-    });
-    console.log("Devices received:", devices);
-    
-    displayDevices(devices);
-    updateStatus("ready", "Scan completed");
+    const result: { devices: NetworkDevice[]; network: string; duration_ms: number; methods_used: string[] } =
+      await invoke("scan_network", {
+        interfaceName: selectedInterface.name
+      });
+    console.log("Devices received:", result.devices);
+    
+    displayDevices(result.devices);
+    updateStatus("ready", `Scanned ${result.network} in ${(result.duration_ms / 1000).toFixed(1)}s via ${result.methods_used.length} methods`);
   } catch (error) {
     console.error("Network scan failed:", error);
     updateStatus("error", "Scan failed");
@@ -398,20 +415,30 @@ async function stopSpoofing() {
   }
 }
 
-function startSessionMonitoring() {
-  if (sessionUpdateInterval) {
-    clearInterval(sessionUpdateInterval);
-  }
+async function startSessionMonitoring() {
+  stopSessionMonitoring();
   
-  sessionUpdateInterval = window.setInterval(updateSessions, 2000);
-  updateSessions(); // Initial update
+  // The backend pushes every change; fetch once for the initial state
+  const events = ["session-started", "session-updated", "session-stopped"];
+  sessionUnlisteners = await Promise.all(
+    events.map((event) => listen<SpoofingSession>(event, ({ payload }) => applySessionUpdate(payload)))
+  );
+  updateSessions();
 }
 
 function stopSessionMonitoring() {
-  if (sessionUpdateInterval) {
-    clearInterval(sessionUpdateInterval);
-    sessionUpdateInterval = null;
+  sessionUnlisteners.forEach((unlisten) => unlisten());
+  sessionUnlisteners = [];
+}
+
+function applySessionUpdate(session: SpoofingSession) {
+  const index = activeSessions.findIndex((existing) => existing.id === session.id);
+  if (index === -1) {
+    activeSessions.push(session);
+  } else {
+    activeSessions[index] = session;
   }
+  displaySessions(activeSessions);
 }
 
 async function updateSessions() {
@@ -458,16 +485,20 @@ function displaySessions(sessions: SpoofingSession[]) {
       <div class="session-details">
         <div class="session-detail">
           <span class="session-detail-label">Target:</span>
-          <span class="session-detail-value">${session.target_ip}</span>
+          <span class="session-detail-value">${session.target_ip}${session.target_mac ? ` (${session.target_mac})` : ""}</span>
         </div>
         <div class="session-detail">
           <span class="session-detail-label">Gateway:</span>
-          <span class="session-detail-value">${session.gateway_ip}</span>
+          <span class="session-detail-value">${session.gateway_ip}${session.gateway_mac ? ` (${session.gateway_mac})` : ""}</span>
         </div>
         <div class="session-detail">
           <span class="session-detail-label">Interface:</span>
           <span class="session-detail-value">${session.interface}</span>
         </div>
+        <div class="session-detail">
+          <span class="session-detail-label">Method:</span>
+          <span class="session-detail-value">${session.method === "StaticArp" ? "Static ARP entries" : "Raw ARP frames"}</span>
+        </div>
         <div class="session-detail">
           <span class="session-detail-label">Packets Sent:</span>
           <span class="session-detail-value">${session.packets_sent.toLocaleString()}</span>
@@ -584,13 +615,19 @@ async function startSpoofAll() {
     });
     
     // Call the backend to start spoofing for all devices at once
-    const sessionIds = await invoke("start_spoof_all", {
+    const result = await invoke<SpoofAllResult>("start_spoof_all", {
       devices,
       gatewayIp,
       interfaceName: selectedInterface.name
     });
     
-    console.log("Started spoofing with session IDs:", sessionIds);
+    console.log("Started spoofing with session IDs:", result.session_ids);
+    if (result.skipped.length > 0) {
+      console.log("Skipped devices:", result.skipped);
+    }
+    if (result.failed.length > 0) {
+      console.warn("Devices that could not be spoofed:", result.failed);
+    }
     
     // Start session monitoring
     startSessionMonitoring();
@@ -601,8 +638,8 @@ async function startSpoofAll() {
     document.getElementById("spoof-all-btn")!.style.display = "none";
     
     // Show status message
-    updateStatus("active", `Spoofing active: ${devices.length} devices`);
-    showSuccess(`ARP spoofing started for all ${devices.length} devices`);
+    updateStatus("active", `Spoofing active: ${result.session_ids.length} devices`);
+    showSuccess(`ARP spoofing started for ${result.session_ids.length} devices (${result.skipped.length} skipped, ${result.failed.length} failed)`);
     
   } catch (error) {
     console.error("Failed mass spoofing:", error);
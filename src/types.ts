@@ -10,6 +10,11 @@ export interface CustomNetworkInterface {
   description: string;
   mac: string;
   ips: string[];
+  /** IPv6 addresses; link-local ones only when requested */
+  ipv6s: string[];
+  is_virtual: boolean;
+  is_up: boolean;
+  speed_mbps: number | null;
 }
 
 /**
@@ -20,8 +25,74 @@ export interface NetworkDevice {
   mac: string;
   hostname: string;
   vendor: string;
+  open_ports?: number[];
+  conflict?: boolean;
+  device_type?: DeviceType | null;
+  /** Name given to the device's MAC with `set_device_label` */
+  label?: string | null;
+  /** Whether the device answered; rechecked for cached results when `verify` is set */
+  online?: boolean;
 }
 
+/**
+ * Guessed kind of a discovered device
+ */
+export type DeviceType =
+  | 'Router'
+  | 'Printer'
+  | 'Phone'
+  | 'Computer'
+  | 'Nas'
+  | 'MediaDevice'
+  | 'IotDevice'
+  | 'VirtualMachine';
+
+/**
+ * Result of a single ICMP echo sent by `ping_host`
+ */
+export interface PingResult {
+  reachable: boolean;
+  rtt_ms: number | null;
+}
+
+/**
+ * Everything `inspect_device` learned about a single host
+ */
+export interface DeviceDetails {
+  ip: string;
+  interface: string;
+  mac: string | null;
+  hostname: string | null;
+  vendor: string | null;
+  open_ports: number[];
+  reachable: boolean;
+  rtt_ms: number | null;
+  device_type: DeviceType | null;
+}
+
+/**
+ * Lifecycle state of a spoofing session
+ */
+export type SessionState = 'Active' | 'Paused' | 'Stopped';
+
+/**
+ * Which of the target's traffic a session intercepts. `DnsOnly` relays
+ * everything except DNS queries, which go to a resolver on this machine.
+ */
+export type InterceptMode = 'Full' | 'DnsOnly';
+
+/**
+ * How a session delivers its poisoned mappings: raw ARP frames, or static
+ * entries in the local ARP cache when no capture device is available
+ */
+export type SpoofMethod = 'RawFrames' | 'StaticArp';
+
+/**
+ * Whom the poisoned replies go to: unicast to the target and gateways, or a
+ * gratuitous broadcast claiming each gateway that reaches the whole segment
+ */
+export type SpoofStrategy = 'Unicast' | 'GratuitousBroadcast';
+
 /**
  * Represents an active spoofing session
  */
@@ -29,7 +100,351 @@ export interface SpoofingSession {
   id: string;
   target_ip: string;
   gateway_ip: string;
+  gateway_ips: string[];
   interface: string;
+  /** Real MACs learned before poisoning; null for sessions saved by older versions */
+  target_mac?: string | null;
+  gateway_mac?: string | null;
   is_active: boolean;
+  state: SessionState;
   packets_sent: number;
-} 
\ No newline at end of file
+  forwarding: boolean;
+  interval_ms: number;
+  packets_per_round?: number;
+  intercept: InterceptMode;
+  method: SpoofMethod;
+  strategy?: SpoofStrategy;
+  capture_path?: string | null;
+  /** This session's own log file, when session_log was set */
+  log_path?: string | null;
+  source_mac: string;
+  started_at: number;
+  stopped_at?: number | null;
+  uptime_secs: number;
+  packets_per_second: number;
+  bytes_up: number;
+  bytes_down: number;
+}
+
+/**
+ * Order of the sessions in a `SessionPage`
+ */
+export type SessionSort = 'TargetIp' | 'PacketsSent' | 'Uptime';
+
+/**
+ * Sorting and paging for active sessions
+ */
+export interface SessionQuery {
+  sort_by?: SessionSort;
+  limit?: number;
+  offset?: number;
+}
+
+/**
+ * One page of sorted sessions; `total` counts the sessions across all pages
+ */
+export interface SessionPage {
+  sessions: SpoofingSession[];
+  total: number;
+}
+
+/**
+ * Named scan settings saved by the backend
+ */
+export interface ScanProfile {
+  name: string;
+  interface: string;
+  /** Range to scan through the interface; its own subnet when unset */
+  cidr?: string | null;
+  mode?: ScanMode;
+  options?: ScanOptions;
+}
+
+/**
+ * Optional tuning for a spoofing session; omitted fields use backend defaults
+ */
+export interface SpoofingOptions {
+  forward_traffic?: boolean;
+  interval_ms?: number;
+  /** Times each poisoned reply is sent per round (1-10) */
+  packets_per_round?: number;
+  dry_run?: boolean;
+  force?: boolean;
+  intercept?: InterceptMode;
+  /** MAC to send poisoned replies from, or 'random'; defaults to the interface MAC */
+  source_mac?: string | null;
+  /** Random variation of each delay, as a percentage of interval_ms (0-90) */
+  jitter_percent?: number;
+  jitter_seed?: number | null;
+  shuffle_sends?: boolean;
+  strategy?: SpoofStrategy;
+  /** Save the target's relayed packets to this .pcap file; needs forward_traffic */
+  capture_path?: string | null;
+  /** Size limit of the capture file in bytes (default 100 MiB) */
+  capture_max_bytes?: number;
+  /** Also log the session's sends, errors and lifecycle to logs/session-<id>.log */
+  session_log?: boolean;
+}
+
+/**
+ * Outcome of a single dry-run preflight check
+ */
+export interface PreflightCheck {
+  name: string;
+  passed: boolean;
+  message: string;
+}
+
+/**
+ * Backend error code, as serialized by the Rust `ErrorCode` enum
+ */
+export type ErrorCode =
+  | 'NetworkError'
+  | 'InterfaceError'
+  | 'SpoofingError'
+  | 'SystemError'
+  | 'PermissionError'
+  | 'ConfigurationError'
+  | 'UnknownError';
+
+/**
+ * Outcome of one environment check of `system_healthcheck`
+ */
+export interface HealthCheck {
+  name: string;
+  passed: boolean;
+  message: string;
+  /** Kind of error the failure causes elsewhere; null when the check passed */
+  code: ErrorCode | null;
+  /** What the user can do to fix a failed check */
+  remediation: string | null;
+}
+
+/**
+ * Whether the environment is ready for scanning and spoofing
+ */
+export interface HealthReport {
+  ready: boolean;
+  checks: HealthCheck[];
+}
+
+/**
+ * What an interface can do, as probed by `test_interface`
+ */
+export interface InterfaceCapabilities {
+  can_read_arp: boolean;
+  /** Poisoned replies can be sent, as raw frames or static ARP entries */
+  can_send_arp: boolean;
+  is_elevated: boolean;
+  /** How a session would send on this interface; null when it can't */
+  method: SpoofMethod | null;
+}
+
+/**
+ * Report returned by `start_spoofing` when `dry_run` is set
+ */
+export interface PreflightReport {
+  passed: boolean;
+  checks: PreflightCheck[];
+  target_mac: string | null;
+  gateway_mac: string | null;
+}
+
+/**
+ * A device `start_spoof_all` did not target, and why
+ */
+export interface SkippedDevice {
+  ip: string;
+  mac: string;
+  reason: string;
+}
+
+/**
+ * A device `start_spoof_all` could not target, with the error it hit
+ */
+export interface FailedDevice {
+  ip: string;
+  mac: string;
+  error: {
+    code: string;
+    message: string;
+    details?: string | null;
+  };
+}
+
+/**
+ * Result of `start_spoof_all`
+ */
+export interface SpoofAllResult {
+  session_ids: string[];
+  skipped: SkippedDevice[];
+  failed: FailedDevice[];
+}
+
+/**
+ * What `scan_and_spoof_all` scans, and whom it spares
+ */
+export interface ScanAndSpoofRequest {
+  interface_name: string;
+  gateway_ip: string;
+  /** Never spoofed, in addition to the saved protected list */
+  protected_ips?: string[];
+  scan_options?: ScanOptions;
+  options?: SpoofingOptions;
+}
+
+/**
+ * Result of `scan_and_spoof_all`: the scan and the sessions started from it
+ */
+export interface ScanAndSpoofResult {
+  scan: ScanResult;
+  /** null when the scan was cancelled or timed out, in which case nothing was spoofed */
+  spoof: SpoofAllResult | null;
+}
+
+/**
+ * Address families covered by a network scan
+ */
+export type ScanMode = 'Ipv4Only' | 'Ipv6Only' | 'Both';
+
+/**
+ * Discovery methods a scan runs; omitted fields use backend defaults
+ */
+export interface DiscoveryMethods {
+  arp_table?: boolean;
+  ping_sweep?: boolean;
+  arp_requests?: boolean;
+  port_scan?: boolean;
+  neighbor_discovery?: boolean;
+  mdns?: boolean;
+}
+
+/**
+ * Optional tuning for a network scan; omitted fields use backend defaults
+ */
+export interface ScanOptions {
+  /** Probe workers; scaled down automatically on Wi-Fi when omitted */
+  max_concurrency?: number | null;
+  port_timeout_ms?: number;
+  methods?: DiscoveryMethods;
+  max_age_secs?: number;
+  force_refresh?: boolean;
+  background_refresh?: boolean;
+  /** Ping the devices of a cached result to update their `online` flag */
+  verify?: boolean;
+  /** First and last address to scan, e.g. '192.168.1.1' to '192.168.1.50' */
+  start_host?: string | null;
+  end_host?: string | null;
+  /** Cancel the scan after this many milliseconds and return the partial result */
+  max_duration_ms?: number | null;
+  /** List this machine and the default gateway among the devices (both default true) */
+  include_self?: boolean;
+  include_gateway?: boolean;
+  /** DNS server to retry reverse lookups against when the system resolver has no name */
+  dns_server?: string | null;
+}
+
+/**
+ * Payload entry of the `session-stats` event emitted every second
+ */
+export interface SessionStats {
+  session_id: string;
+  bytes_up: number;
+  bytes_down: number;
+  up_rate: number;
+  down_rate: number;
+}
+
+/**
+ * Kind of change an ARP watch noticed in the local cache
+ */
+export type ArpAnomalyKind = 'MacChanged' | 'GatewayMultipleMacs';
+
+/**
+ * Payload of the `arp-anomaly` event
+ */
+export interface ArpAnomaly {
+  watch_id: string;
+  kind: ArpAnomalyKind;
+  ip: string;
+  previous_mac: string;
+  mac: string;
+  seen_macs: string[];
+  detected_at: number;
+}
+
+/**
+ * Payload of the `session-error` event, emitted when a session stops on its own
+ */
+export interface SessionError {
+  session_id: string;
+  error: {
+    code: string;
+    message: string;
+    details?: string | null;
+  };
+}
+
+/**
+ * Devices found by `scan_network`, with what was scanned and how
+ */
+export interface ScanResult {
+  devices: NetworkDevice[];
+  interface: string;
+  network: string;
+  duration_ms: number;
+  scanned_at: number;
+  methods_used: string[];
+  /** Devices each discovery method found first */
+  method_counts: Record<string, number>;
+  /** The scan hit `max_duration_ms`, so `devices` may be incomplete */
+  timed_out?: boolean;
+  /** Number of probe workers used, chosen automatically unless max_concurrency was set */
+  concurrency?: number;
+}
+
+/**
+ * Differences between the previous and the current scan, returned by `scan_diff`
+ */
+export interface ScanDiff {
+  added: NetworkDevice[];
+  removed: NetworkDevice[];
+  changed: [NetworkDevice, NetworkDevice][];
+}
+
+/**
+ * One row of the system ARP table
+ */
+export interface ArpEntry {
+  ip: string;
+  mac: string;
+  is_static: boolean;
+}
+
+/**
+ * Differences between two ARP table snapshots
+ */
+export interface ArpDiff {
+  added: ArpEntry[];
+  removed: ArpEntry[];
+  /** [old, new] pairs for IPs whose MAC changed or became static */
+  changed: [ArpEntry, ArpEntry][];
+}
+
+/**
+ * File formats accepted by `export_devices`
+ */
+export type ExportFormat = 'Csv' | 'Json';
+
+/**
+ * Payload of the `scan-progress` event emitted after each scan phase
+ */
+export interface ScanProgress {
+  phase: string;
+  completed: number;
+  /** Number of enabled discovery methods */
+  total: number;
+  devices_found: number;
+  /** The phase did not run, because the scan was cancelled or had nothing left for it */
+  skipped: boolean;
+}
@@ -3,18 +3,21 @@
  */
 
 import { invoke } from '@tauri-apps/api/tauri';
-import { logger, debug, info, error } from './logger';
+import { logger, debug, info, warn, error } from './logger';
 import { handleError, createNetworkError, AppError } from './errorHandler';
-import type { CustomNetworkInterface, NetworkDevice, SpoofingSession } from '../types';
+import type { CustomNetworkInterface, NetworkDevice, SpoofingSession, SpoofingOptions, ScanMode, ScanOptions, ExportFormat, PreflightReport, PingResult, ScanDiff, ScanResult, SpoofAllResult, SessionPage, SessionQuery, HealthReport, ScanProfile, DeviceDetails, InterfaceCapabilities, ArpEntry, ArpDiff, ScanAndSpoofRequest, ScanAndSpoofResult } from '../types';
 
 /**
  * Get all network interfaces
  */
-export async function getInterfaces(): Promise<CustomNetworkInterface[]> {
+export async function getInterfaces(
+  includeVirtual?: boolean,
+  includeLinkLocal?: boolean
+): Promise<CustomNetworkInterface[]> {
   info('API: Getting network interfaces');
   
   try {
-    const interfaces = await invoke<CustomNetworkInterface[]>('get_interfaces');
+    const interfaces = await invoke<CustomNetworkInterface[]>('get_interfaces', { includeVirtual, includeLinkLocal });
     debug(`API: Found ${interfaces.length} network interfaces`);
     return interfaces;
   } catch (err) {
@@ -25,17 +28,46 @@ export async function getInterfaces(): Promise<CustomNetworkInterface[]> {
 }
 
 /**
- * Scan the network for devices
+ * Find the interface that has `ip` as one of its addresses
+ *
+ * Scan and spoof calls also accept such an address in place of the interface name.
  */
-export async function scanNetwork(interfaceName: string): Promise<NetworkDevice[]> {
+export async function findInterfaceByIp(ip: string): Promise<CustomNetworkInterface> {
+  debug(`API: Looking up the interface with address ${ip}`);
+  
+  try {
+    return await invoke<CustomNetworkInterface>('find_interface_by_ip', { ip });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: No interface found for address ${ip}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Scan the network for devices, returning them with the scan's metadata
+ *
+ * When `prefixLen` is omitted the interface's own netmask is used.
+ */
+export async function scanNetworkDetailed(
+  interfaceName: string,
+  prefixLen?: number,
+  scanId?: string,
+  mode?: ScanMode,
+  options?: ScanOptions
+): Promise<ScanResult> {
   info(`API: Scanning network on interface: ${interfaceName}`);
   
   try {
-    const devices = await invoke<NetworkDevice[]>('scan_network', {
-      interfaceName
+    const result = await invoke<ScanResult>('scan_network', {
+      interfaceName,
+      prefixLen,
+      scanId,
+      mode,
+      options
     });
-    debug(`API: Found ${devices.length} devices on network`);
-    return devices;
+    debug(`API: Found ${result.devices.length} devices on ${result.network} in ${result.duration_ms}ms`);
+    return result;
   } catch (err) {
     const appError = handleError(err);
     error(`API: Failed to scan network on interface ${interfaceName}`, appError);
@@ -43,13 +75,481 @@ export async function scanNetwork(interfaceName: string): Promise<NetworkDevice[
   }
 }
 
+/**
+ * Scan the network for devices
+ *
+ * Kept for callers that only need the device list; see `scanNetworkDetailed`.
+ */
+export async function scanNetwork(
+  interfaceName: string,
+  prefixLen?: number,
+  scanId?: string,
+  mode?: ScanMode,
+  options?: ScanOptions
+): Promise<NetworkDevice[]> {
+  const result = await scanNetworkDetailed(interfaceName, prefixLen, scanId, mode, options);
+  return result.devices;
+}
+
+/**
+ * Rescan an interface and report what changed since the previous scan
+ */
+export async function scanDiff(
+  interfaceName: string,
+  prefixLen?: number,
+  mode?: ScanMode,
+  options?: ScanOptions
+): Promise<ScanDiff> {
+  info(`API: Scanning ${interfaceName} for changes`);
+  
+  try {
+    const diff = await invoke<ScanDiff>('scan_diff', {
+      interfaceName,
+      prefixLen,
+      mode,
+      options
+    });
+    debug(`API: ${diff.added.length} added, ${diff.removed.length} removed, ${diff.changed.length} changed`);
+    return diff;
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to diff scan on interface ${interfaceName}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Capture the current system ARP table, to compare with a later snapshot
+ */
+export async function snapshotArpTable(): Promise<ArpEntry[]> {
+  info('API: Taking a snapshot of the ARP table');
+  
+  try {
+    return await invoke<ArpEntry[]>('snapshot_arp_table');
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to snapshot the ARP table', appError);
+    throw appError;
+  }
+}
+
+/**
+ * Compare two ARP table snapshots; changed MACs can reveal poisoning by another tool
+ */
+export async function diffArpSnapshots(previous: ArpEntry[], current: ArpEntry[]): Promise<ArpDiff> {
+  debug(`API: Comparing ARP snapshots of ${previous.length} and ${current.length} entries`);
+  
+  try {
+    return await invoke<ArpDiff>('diff_arp_snapshots', { previous, current });
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to compare ARP snapshots', appError);
+    throw appError;
+  }
+}
+
+/**
+ * Scan an arbitrary IPv4 range (e.g. "10.0.5.0/24") through an interface
+ */
+export async function scanCidr(
+  interfaceName: string,
+  cidr: string,
+  scanId?: string,
+  options?: ScanOptions
+): Promise<NetworkDevice[]> {
+  info(`API: Scanning range ${cidr} through interface: ${interfaceName}`);
+  
+  try {
+    const devices = await invoke<NetworkDevice[]>('scan_cidr', {
+      interfaceName,
+      cidr,
+      scanId,
+      options
+    });
+    debug(`API: Found ${devices.length} devices in range ${cidr}`);
+    return devices;
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to scan range ${cidr}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Get every saved scan profile
+ */
+export async function listScanProfiles(): Promise<ScanProfile[]> {
+  debug('API: Listing scan profiles');
+  
+  try {
+    return await invoke<ScanProfile[]>('list_scan_profiles');
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to list scan profiles', appError);
+    throw appError;
+  }
+}
+
+/**
+ * Save a scan profile, replacing one with the same name
+ */
+export async function saveScanProfile(profile: ScanProfile): Promise<ScanProfile[]> {
+  info(`API: Saving scan profile ${profile.name}`);
+  
+  try {
+    return await invoke<ScanProfile[]>('save_scan_profile', { profile });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to save scan profile ${profile.name}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Scan with the settings of a saved profile
+ */
+export async function runScanProfile(name: string, scanId?: string): Promise<ScanResult> {
+  info(`API: Running scan profile ${name}`);
+  
+  try {
+    const result = await invoke<ScanResult>('run_scan_profile', { name, scanId });
+    debug(`API: Profile ${name} found ${result.devices.length} devices`);
+    return result;
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to run scan profile ${name}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * List devices already in the ARP/neighbor caches without active probing
+ */
+export async function quickScan(interfaceName: string): Promise<NetworkDevice[]> {
+  info(`API: Quick scan on interface: ${interfaceName}`);
+  
+  try {
+    const devices = await invoke<NetworkDevice[]>('quick_scan', { interfaceName });
+    debug(`API: Quick scan found ${devices.length} devices`);
+    return devices;
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Quick scan failed on interface ${interfaceName}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Group devices by the manufacturer of their MAC, with an 'Unknown' group
+ */
+export async function groupDevicesByVendor(devices: NetworkDevice[]): Promise<Record<string, NetworkDevice[]>> {
+  debug(`API: Grouping ${devices.length} devices by vendor`);
+  
+  try {
+    return await invoke<Record<string, NetworkDevice[]>>('group_devices_by_vendor', { devices });
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to group devices by vendor', appError);
+    throw appError;
+  }
+}
+
+/**
+ * Save a device list to a CSV or JSON file
+ */
+export async function exportDevices(
+  devices: NetworkDevice[],
+  format: ExportFormat,
+  path: string
+): Promise<void> {
+  info(`API: Exporting ${devices.length} devices as ${format} to ${path}`);
+  
+  try {
+    await invoke('export_devices', { devices, format, path });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to export devices to ${path}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Cancel an in-progress scan started with the given scan id
+ */
+export async function cancelScan(scanId: string): Promise<boolean> {
+  info(`API: Cancelling scan: ${scanId}`);
+  
+  try {
+    return await invoke<boolean>('cancel_scan', { scanId });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to cancel scan ${scanId}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Flush the ARP cache of one interface, or of all interfaces when omitted
+ */
+export async function clearArpCache(interfaceName?: string): Promise<void> {
+  info(`API: Clearing ARP cache${interfaceName ? ` on ${interfaceName}` : ''}`);
+  
+  try {
+    await invoke('clear_arp_cache', { interfaceName });
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to clear ARP cache', appError);
+    throw appError;
+  }
+}
+
+/**
+ * Watch the ARP cache of an interface for changed MACs; anomalies arrive as
+ * `arp-anomaly` events. Returns the watch id.
+ */
+export async function startArpWatch(interfaceName: string): Promise<string> {
+  info(`API: Starting ARP watch on ${interfaceName}`);
+  
+  try {
+    return await invoke<string>('start_arp_watch', { interfaceName });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to start ARP watch on ${interfaceName}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Stop an ARP watch started with startArpWatch
+ */
+export async function stopArpWatch(watchId: string): Promise<boolean> {
+  info(`API: Stopping ARP watch: ${watchId}`);
+  
+  try {
+    return await invoke<boolean>('stop_arp_watch', { watchId });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to stop ARP watch ${watchId}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Watch the network adapters and receive `interfaces-changed` events with the
+ * new list whenever one appears, disappears or changes address
+ */
+export async function startInterfaceWatch(
+  intervalMs?: number,
+  includeVirtual?: boolean,
+  includeLinkLocal?: boolean
+): Promise<string> {
+  info('API: Starting interface watch');
+  
+  try {
+    return await invoke<string>('start_interface_watch', { intervalMs, includeVirtual, includeLinkLocal });
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to start interface watch', appError);
+    throw appError;
+  }
+}
+
+/**
+ * Stop an interface watch started with startInterfaceWatch
+ */
+export async function stopInterfaceWatch(watchId: string): Promise<boolean> {
+  info(`API: Stopping interface watch: ${watchId}`);
+  
+  try {
+    return await invoke<boolean>('stop_interface_watch', { watchId });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to stop interface watch ${watchId}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Detect the default gateway reachable through an interface
+ */
+export async function getDefaultGateway(interfaceName: string): Promise<string> {
+  debug(`API: Detecting default gateway on interface: ${interfaceName}`);
+  
+  try {
+    const gateway = await invoke<string>('get_default_gateway', { interfaceName });
+    debug(`API: Default gateway for ${interfaceName} is ${gateway}`);
+    return gateway;
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to detect default gateway on ${interfaceName}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Look up the router of an interface with its MAC, vendor and hostname
+ */
+export async function getGatewayDevice(interfaceName: string): Promise<NetworkDevice> {
+  debug(`API: Looking up gateway device on interface: ${interfaceName}`);
+  
+  try {
+    return await invoke<NetworkDevice>('get_gateway_device', { interfaceName });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to look up gateway device on ${interfaceName}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Probe whether an interface can read and send ARP, so spoofing can be disabled where it won't work
+ */
+export async function testInterface(interfaceName: string): Promise<InterfaceCapabilities> {
+  debug(`API: Testing ARP capabilities of interface: ${interfaceName}`);
+  
+  try {
+    return await invoke<InterfaceCapabilities>('test_interface', { interfaceName });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to test interface ${interfaceName}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Check whether a host answers ICMP echo and how quickly
+ */
+export async function pingHost(ip: string): Promise<PingResult> {
+  debug(`API: Pinging host: ${ip}`);
+  
+  try {
+    return await invoke<PingResult>('ping_host', { ip });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to ping ${ip}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Look up the hostname of an IP via reverse DNS, NetBIOS or mDNS; null if it has none
+ */
+export async function resolveHost(ip: string): Promise<string | null> {
+  debug(`API: Resolving hostname of ${ip}`);
+  
+  try {
+    return await invoke<string | null>('resolve_host', { ip });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to resolve hostname of ${ip}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Gather the MAC, hostname, vendor, open ports and ping time of one device
+ * without rescanning the network
+ */
+export async function inspectDevice(ip: string, interfaceName: string): Promise<DeviceDetails> {
+  info(`API: Inspecting ${ip} on interface ${interfaceName}`);
+  
+  try {
+    const details = await invoke<DeviceDetails>('inspect_device', { ip, interfaceName });
+    debug(`API: ${ip} has ${details.open_ports.length} open ports`);
+    return details;
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to inspect ${ip}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Wake a sleeping device with a Wake-on-LAN magic packet
+ */
+export async function wakeOnLan(mac: string, broadcastIp?: string): Promise<void> {
+  info(`API: Sending Wake-on-LAN packet to ${mac}`);
+  
+  try {
+    await invoke('wake_on_lan', { mac, broadcastIp });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to wake ${mac}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Probe TCP ports on one host; an empty list scans the backend's default service ports
+ */
+export async function scanPorts(ip: string, ports: number[] = [], timeoutMs = 500): Promise<number[]> {
+  info(`API: Scanning ports on ${ip}`);
+  
+  try {
+    return await invoke<number[]>('scan_ports', { ip, ports, timeoutMs });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to scan ports on ${ip}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Refresh the MAC vendor database from the IEEE registry (or a custom URL)
+ */
+export async function updateOuiDatabase(url?: string): Promise<number> {
+  info('API: Updating vendor database');
+  
+  try {
+    const count = await invoke<number>('update_oui_database', { url });
+    info(`API: Vendor database updated with ${count} entries`);
+    return count;
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to update vendor database', appError);
+    throw appError;
+  }
+}
+
+/**
+ * Forget vendors cached by earlier scans; returns how many were cached
+ */
+export async function clearVendorCache(): Promise<number> {
+  info('API: Clearing vendor cache');
+  
+  try {
+    return await invoke<number>('clear_vendor_cache');
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to clear vendor cache', appError);
+    throw appError;
+  }
+}
+
+/**
+ * Forget hostnames cached by earlier scans; returns how many were cached
+ */
+export async function clearHostnameCache(): Promise<number> {
+  info('API: Clearing hostname cache');
+  
+  try {
+    return await invoke<number>('clear_hostname_cache');
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to clear hostname cache', appError);
+    throw appError;
+  }
+}
+
 /**
  * Start spoofing a specific device
  */
 export async function startSpoofing(
   targetIp: string,
   gatewayIp: string,
-  interfaceName: string
+  interfaceName: string,
+  options?: SpoofingOptions,
+  extraGatewayIps?: string[]
 ): Promise<string> {
   info(`API: Starting spoofing - Target: ${targetIp}, Gateway: ${gatewayIp}, Interface: ${interfaceName}`);
   
@@ -57,7 +557,9 @@ export async function startSpoofing(
     const sessionId = await invoke<string>('start_spoofing', {
       targetIp,
       gatewayIp,
-      interfaceName
+      gatewayIps: extraGatewayIps,
+      interfaceName,
+      options
     });
     info(`API: Spoofing started with session ID: ${sessionId}`);
     return sessionId;
@@ -68,6 +570,33 @@ export async function startSpoofing(
   }
 }
 
+/**
+ * Run the spoofing preflight checks without starting a session
+ */
+export async function dryRunSpoofing(
+  targetIp: string,
+  gatewayIp: string,
+  interfaceName: string,
+  options?: SpoofingOptions
+): Promise<PreflightReport> {
+  info(`API: Dry run for spoofing - Target: ${targetIp}, Gateway: ${gatewayIp}, Interface: ${interfaceName}`);
+  
+  try {
+    const report = await invoke<PreflightReport>('start_spoofing', {
+      targetIp,
+      gatewayIp,
+      interfaceName,
+      options: { ...options, dry_run: true }
+    });
+    debug(`API: Dry run ${report.passed ? 'passed' : 'failed'}`);
+    return report;
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Dry run failed for ${targetIp}`, appError);
+    throw appError;
+  }
+}
+
 /**
  * Stop spoofing a specific session
  */
@@ -87,6 +616,53 @@ export async function stopSpoofing(sessionId: string): Promise<boolean> {
   }
 }
 
+/**
+ * Stop every running spoofing session
+ */
+export async function stopAllSessions(): Promise<number> {
+  info('API: Stopping all spoofing sessions');
+  
+  try {
+    const count = await invoke<number>('stop_all_sessions');
+    info(`API: Stopped ${count} spoofing sessions`);
+    return count;
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to stop all spoofing sessions', appError);
+    throw appError;
+  }
+}
+
+/**
+ * Pause a spoofing session, letting the target through until resumed
+ */
+export async function pauseSpoofing(sessionId: string): Promise<boolean> {
+  info(`API: Pausing spoofing session: ${sessionId}`);
+  
+  try {
+    return await invoke<boolean>('pause_spoofing', { sessionId });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to pause spoofing session ${sessionId}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Resume a paused spoofing session
+ */
+export async function resumeSpoofing(sessionId: string): Promise<boolean> {
+  info(`API: Resuming spoofing session: ${sessionId}`);
+  
+  try {
+    return await invoke<boolean>('resume_spoofing', { sessionId });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to resume spoofing session ${sessionId}`, appError);
+    throw appError;
+  }
+}
+
 /**
  * Get all active spoofing sessions
  */
@@ -104,24 +680,81 @@ export async function getActiveSessions(): Promise<SpoofingSession[]> {
   }
 }
 
+/**
+ * Get one page of active spoofing sessions in a stable order
+ */
+export async function getSessionsPage(query: SessionQuery = {}): Promise<SessionPage> {
+  debug(`API: Getting active spoofing sessions sorted by ${query.sort_by ?? 'TargetIp'}`);
+  
+  try {
+    const page = await invoke<SessionPage>('get_active_sessions', {
+      sortBy: query.sort_by ?? 'TargetIp',
+      limit: query.limit,
+      offset: query.offset
+    });
+    debug(`API: Got ${page.sessions.length} of ${page.total} active sessions`);
+    return page;
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to get active sessions page', appError);
+    throw appError;
+  }
+}
+
+/**
+ * Get sessions left running by a previous run of the app
+ */
+export async function getRecoveredSessions(): Promise<SpoofingSession[]> {
+  debug('API: Getting recovered spoofing sessions');
+  
+  try {
+    return await invoke<SpoofingSession[]>('get_recovered_sessions');
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to get recovered sessions', appError);
+    throw appError;
+  }
+}
+
+/**
+ * Restore ARP entries for all recovered sessions
+ */
+export async function restoreRecoveredSessions(): Promise<number> {
+  info('API: Restoring recovered spoofing sessions');
+  
+  try {
+    const count = await invoke<number>('restore_recovered_sessions');
+    info(`API: Restored ${count} recovered sessions`);
+    return count;
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to restore recovered sessions', appError);
+    throw appError;
+  }
+}
+
 /**
  * Start spoofing for all devices in the network
  */
 export async function startSpoofAll(
   devices: NetworkDevice[],
   gatewayIp: string,
-  interfaceName: string
-): Promise<string[]> {
+  interfaceName: string,
+  options?: SpoofingOptions,
+  protectedIps?: string[]
+): Promise<SpoofAllResult> {
   info(`API: Starting spoofing for all ${devices.length} devices on interface ${interfaceName}`);
   
   try {
-    const sessionIds = await invoke<string[]>('start_spoof_all', {
+    const result = await invoke<SpoofAllResult>('start_spoof_all', {
       devices,
       gatewayIp,
-      interfaceName
+      interfaceName,
+      protectedIps,
+      options
     });
-    info(`API: Started spoofing for ${sessionIds.length} devices`);
-    return sessionIds;
+    info(`API: Started spoofing for ${result.session_ids.length} devices, skipped ${result.skipped.length}, failed ${result.failed.length}`);
+    return result;
   } catch (err) {
     const appError = handleError(err);
     error('API: Failed to start spoofing for all devices', appError);
@@ -129,6 +762,165 @@ export async function startSpoofAll(
   }
 }
 
+/**
+ * Scan the network and immediately spoof every device found, except the
+ * gateway, this machine and protected devices
+ */
+export async function scanAndSpoofAll(
+  interfaceName: string,
+  gatewayIp: string,
+  protectedIps?: string[],
+  scanId?: string,
+  scanOptions?: ScanOptions,
+  options?: SpoofingOptions
+): Promise<ScanAndSpoofResult> {
+  info(`API: Scanning ${interfaceName} and spoofing every device found`);
+  
+  try {
+    const request: ScanAndSpoofRequest = {
+      interface_name: interfaceName,
+      gateway_ip: gatewayIp,
+      protected_ips: protectedIps,
+      scan_options: scanOptions,
+      options
+    };
+    const result = await invoke<ScanAndSpoofResult>('scan_and_spoof_all', { request, scanId });
+    if (result.spoof) {
+      info(`API: Found ${result.scan.devices.length} devices, started spoofing for ${result.spoof.session_ids.length}`);
+    } else {
+      warn(`API: Scan on ${interfaceName} did not finish, nothing was spoofed`);
+    }
+    return result;
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to scan and spoof on interface ${interfaceName}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Get the saved list of IPs and MACs that spoof-all never targets
+ */
+export async function getProtectedIps(): Promise<string[]> {
+  debug('API: Getting protected devices');
+  
+  try {
+    return await invoke<string[]>('get_protected_ips');
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to get protected devices', appError);
+    throw appError;
+  }
+}
+
+/**
+ * Replace the saved list of IPs and MACs that spoof-all never targets
+ */
+export async function setProtectedIps(entries: string[]): Promise<string[]> {
+  info(`API: Saving ${entries.length} protected devices`);
+  
+  try {
+    return await invoke<string[]>('set_protected_ips', { entries });
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to save protected devices', appError);
+    throw appError;
+  }
+}
+
+/**
+ * Get the most spoofing sessions allowed to run at once
+ */
+export async function getMaxConcurrentSessions(): Promise<number> {
+  return invoke<number>('get_max_concurrent_sessions');
+}
+
+/**
+ * Change the most spoofing sessions allowed to run at once; new sessions past it are refused
+ */
+export async function setMaxConcurrentSessions(limit: number): Promise<number> {
+  info(`API: Setting the session limit to ${limit}`);
+  
+  try {
+    return await invoke<number>('set_max_concurrent_sessions', { limit });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to set the session limit to ${limit}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Give a device a friendly name by MAC, or remove it with a null label;
+ * returns every saved label by MAC
+ */
+export async function setDeviceLabel(mac: string, label: string | null): Promise<Record<string, string>> {
+  info(`API: Setting label of ${mac}`);
+  
+  try {
+    return await invoke<Record<string, string>>('set_device_label', { mac, label });
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to save device label', appError);
+    throw appError;
+  }
+}
+
+/**
+ * Change the backend log level (off, error, warn, info, debug or trace)
+ */
+export async function setLogLevel(level: string): Promise<string> {
+  info(`API: Setting backend log level to ${level}`);
+  
+  try {
+    return await invoke<string>('set_log_level', { level });
+  } catch (err) {
+    const appError = handleError(err);
+    error(`API: Failed to set backend log level to ${level}`, appError);
+    throw appError;
+  }
+}
+
+/**
+ * Get the current backend log level
+ */
+export async function getLogLevel(): Promise<string> {
+  return invoke<string>('get_log_level');
+}
+
+/**
+ * Check whether the backend is running with administrator privileges
+ */
+export async function checkPrivileges(): Promise<boolean> {
+  debug('API: Checking administrator privileges');
+  
+  try {
+    return await invoke<boolean>('check_privileges');
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to check administrator privileges', appError);
+    throw appError;
+  }
+}
+
+/**
+ * Check privileges, interfaces and required commands in one go
+ */
+export async function systemHealthcheck(): Promise<HealthReport> {
+  info('API: Running system health check');
+  
+  try {
+    const report = await invoke<HealthReport>('system_healthcheck');
+    const failed = report.checks.filter((check) => !check.passed);
+    info(`API: System ${report.ready ? 'ready' : `not ready, ${failed.length} checks failed`}`);
+    return report;
+  } catch (err) {
+    const appError = handleError(err);
+    error('API: Failed to run system health check', appError);
+    throw appError;
+  }
+}
+
 /**
  * Check if the application has sufficient permissions
  */